@@ -0,0 +1,198 @@
+use crate::checks::generic::accepts;
+use crate::{Checker, ZXTyped};
+use util::ast::Expression;
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::{ScopeType, Scopes};
+use util::token::{Position, Token};
+
+impl Checker {
+    /// `[a, b, c]`, every element has the type of the first one. The
+    /// elements of `[]` are `Void` until the type it is assigned to gives
+    /// them.
+    pub(crate) fn array(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        left_square_brackets: Token,
+        elements: Vec<Expression>,
+        right_square_brackets: Token,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let pos = left_square_brackets.pos.to(&right_square_brackets.pos);
+        let mut element_type: Option<ZXTyped> = None;
+        let mut values: Vec<Option<BytecodeType>> = vec![];
+
+        for element in elements {
+            let (value_type, value_pos, value) =
+                self.auto_type(global_scopes, None, currently, element)?;
+
+            match &element_type {
+                Some(element_type) if !accepts(element_type, &value_type) => {
+                    return Err(ZXError::TypeError {
+                        message: format!(
+                            "mismatched types in the array, expected `{}` found `{}`",
+                            element_type, value_type
+                        ),
+                        pos: value_pos.unwrap_or(pos),
+                    })
+                }
+                Some(_) => {}
+                None => element_type = Some(value_type),
+            }
+            values.push(value);
+        }
+
+        let element_type = element_type.unwrap_or(ZXTyped::Void);
+        Ok((
+            ZXTyped::Array {
+                element: Box::new(element_type.clone()),
+                nullable: false,
+            },
+            Some(pos),
+            values
+                .into_iter()
+                .collect::<Option<Vec<BytecodeType>>>()
+                .map(|elements| BytecodeType::Array {
+                    element_type,
+                    elements,
+                }),
+        ))
+    }
+
+    /// `target[index]`, the target is an array and the index an `Int` or a
+    /// `Long`
+    pub(crate) fn index(
+        &mut self,
+        global_scopes: &mut Scopes,
+        sub_scopes: Option<&mut Scopes>,
+        currently: &mut Vec<Scopes>,
+        expression: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (target, index, right_square_brackets, next) = match expression {
+            Expression::Index {
+                target,
+                index,
+                right_square_brackets,
+                next,
+                ..
+            } => (target, index, right_square_brackets, next),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not an index expression".into(),
+                })
+            }
+        };
+        let (target_type, target_pos, array) =
+            self.auto_type(global_scopes, sub_scopes, currently, *target)?;
+        let target_pos = target_pos.ok_or(ZXError::InternalError {
+            message: "indexed expression has no position".into(),
+        })?;
+        let (element_type, index) =
+            self.index_of(global_scopes, currently, target_type, &target_pos, *index)?;
+        let pos = target_pos.to(&right_square_brackets.pos);
+        let value_type = if let Some(next) = next {
+            let scope = self.class_scope(global_scopes, currently, &element_type, pos.clone())?;
+
+            if let ScopeType::DefClass { mut members, .. } = scope.scope_type {
+                self.auto_type(global_scopes, Some(&mut members), currently, *next)?
+                    .0
+            } else {
+                return Err(ZXError::TypeError {
+                    message: format!("`{}` has no members", element_type),
+                    pos,
+                });
+            }
+        } else {
+            element_type.clone()
+        };
+
+        Ok((
+            value_type,
+            Some(pos.clone()),
+            match (array, index) {
+                (Some(array), Some(index)) => Some(BytecodeType::Index {
+                    element_type,
+                    array: Box::new(array),
+                    index: Box::new(index),
+                    pos,
+                }),
+                _ => None,
+            },
+        ))
+    }
+
+    /// Element type and index value of indexing a value of `target_type`
+    pub(crate) fn index_of(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        target_type: ZXTyped,
+        target_pos: &Position,
+        index: Expression,
+    ) -> Result<(ZXTyped, Option<BytecodeType>), ZXError> {
+        let element_type = match target_type {
+            ZXTyped::Array { element, .. } => *element,
+            target_type => {
+                return Err(ZXError::TypeError {
+                    message: format!("cannot index into a value of type `{}`", target_type),
+                    pos: target_pos.clone(),
+                })
+            }
+        };
+        let (index_type, index_pos, index) =
+            self.auto_type(global_scopes, None, currently, index)?;
+
+        if !matches!(index_type, ZXTyped::Integer { .. } | ZXTyped::Long { .. }) {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "array index must be `Int` or `Long`, found `{}`",
+                    index_type
+                ),
+                pos: index_pos.unwrap_or_else(|| target_pos.clone()),
+            });
+        }
+
+        Ok((element_type, index))
+    }
+
+    /// The built-in `len(array)`, used when no function `len` is declared
+    pub(crate) fn len(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        call_name: Token,
+        arguments: Vec<Expression>,
+        right_parentheses: Token,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let pos = call_name.pos.to(&right_parentheses.pos);
+        let argument = match <[Expression; 1]>::try_from(arguments) {
+            Ok([argument]) => argument,
+            Err(arguments) => {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "`len` takes 1 argument but {} arguments were supplied",
+                        arguments.len()
+                    ),
+                    pos,
+                })
+            }
+        };
+        let (array_type, array_pos, array) =
+            self.auto_type(global_scopes, None, currently, argument)?;
+
+        if !matches!(array_type, ZXTyped::Array { .. }) {
+            return Err(ZXError::TypeError {
+                message: format!("`len` expects an array, found `{}`", array_type),
+                pos: array_pos.unwrap_or(pos),
+            });
+        }
+
+        Ok((
+            ZXTyped::Integer { nullable: false },
+            Some(pos),
+            array.map(|array| BytecodeType::Len {
+                array: Box::new(array),
+            }),
+        ))
+    }
+}
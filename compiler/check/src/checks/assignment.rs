@@ -0,0 +1,235 @@
+use crate::checks::class::field_of;
+use crate::checks::generic::accepts;
+use crate::checks::operator::same_type;
+use crate::{Checker, ZXTyped};
+use util::ast::{Expression, Operator};
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::{ScopeType, Scopes};
+use util::token::Position;
+
+// where an assignment stores its value
+enum Place {
+    Variable(String),
+    Field {
+        field_type: ZXTyped,
+        object: Box<BytecodeType>,
+        class: String,
+        index: usize,
+    },
+    Element {
+        element_type: ZXTyped,
+        array: Box<BytecodeType>,
+        index: Option<Box<BytecodeType>>,
+        pos: Position,
+    },
+}
+
+impl Place {
+    fn load(&self) -> Option<BytecodeType> {
+        match self {
+            Place::Variable(path) => Some(BytecodeType::Load { path: path.clone() }),
+            Place::Field {
+                field_type,
+                object,
+                class,
+                index,
+            } => Some(BytecodeType::FieldLoad {
+                object: object.clone(),
+                class: class.clone(),
+                index: *index,
+                field_type: field_type.clone(),
+            }),
+            Place::Element {
+                element_type,
+                array,
+                index,
+                pos,
+            } => index.clone().map(|index| BytecodeType::Index {
+                element_type: element_type.clone(),
+                array: array.clone(),
+                index,
+                pos: pos.clone(),
+            }),
+        }
+    }
+
+    fn store(self, value: BytecodeType) -> Option<BytecodeType> {
+        match self {
+            Place::Variable(path) => Some(BytecodeType::Store {
+                path,
+                value: Box::new(value),
+            }),
+            Place::Field {
+                object,
+                class,
+                index,
+                ..
+            } => Some(BytecodeType::FieldStore {
+                object,
+                class,
+                index,
+                value: Box::new(value),
+            }),
+            Place::Element {
+                element_type,
+                array,
+                index,
+                pos,
+            } => index.map(|index| BytecodeType::IndexStore {
+                element_type,
+                array,
+                index,
+                value: Box::new(value),
+                pos,
+            }),
+        }
+    }
+}
+
+impl Checker {
+    /// Type check `target = value`, the target must be a variable, a field
+    /// of one or an element of an array with the type of the value.
+    /// `target += value` stores `target + value`.
+    pub(crate) fn assignment(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        target: Expression,
+        operator_type: Option<Operator>,
+        value: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (target_type, target_pos, place) =
+            self.assign_target(global_scopes, currently, target)?;
+        let value = self.auto_type(global_scopes, None, currently, value)?;
+        let (value_type, value_pos, value) = match operator_type {
+            Some(operator) => self.binary(
+                operator,
+                (target_type.clone(), Some(target_pos.clone()), place.load()),
+                value,
+            )?,
+            None => value,
+        };
+
+        if !same_type(&target_type, &value_type) && !accepts(&target_type, &value_type) {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "mismatched types, expected `{}` found `{}`",
+                    target_type, value_type
+                ),
+                pos: value_pos.unwrap_or(target_pos),
+            });
+        }
+
+        Ok((
+            ZXTyped::Void,
+            None,
+            value.and_then(|value| place.store(value)),
+        ))
+    }
+
+    // type, position and place of `a`, `a.b`, `a.b.c` or `a.b[0]`, a field
+    // is stored in the object holding it
+    fn assign_target(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        target: Expression,
+    ) -> Result<(ZXTyped, Position, Place), ZXError> {
+        let not_assignable = || ZXError::InternalError {
+            message: "not an assignable expression".into(),
+        };
+        let (target, mut indexes) = indexed(target);
+        let (identifier, mut next) = match target {
+            Expression::Identifier { identifier, next } => (identifier, next),
+            _ => return Err(not_assignable()),
+        };
+        let scope = self.find_scope(global_scopes, currently, &identifier)?;
+        let mut target_type = match scope.scope_type {
+            ScopeType::DefVariable { var_type, .. } => var_type,
+            _ => {
+                return Err(ZXError::TypeError {
+                    message: format!("cannot assign to `{}`, it is not a variable", scope.name),
+                    pos: identifier.pos,
+                })
+            }
+        };
+        let mut place = Place::Variable(scope.path);
+        let mut pos = identifier.pos;
+
+        while let Some(member) = next {
+            let (field, field_next) = match *member {
+                Expression::SubMember { sub_member } => {
+                    let (sub_member, field_indexes) = indexed(*sub_member);
+                    indexes = field_indexes;
+
+                    match sub_member {
+                        Expression::Identifier { identifier, next } => (identifier, next),
+                        _ => return Err(not_assignable()),
+                    }
+                }
+                _ => return Err(not_assignable()),
+            };
+            let name = field.get_symbol()?;
+            let class = match &target_type {
+                ZXTyped::Other(_) | ZXTyped::Generic { .. } => self
+                    .class_scope(global_scopes, currently, &target_type, pos.clone())
+                    .ok(),
+                _ => None,
+            };
+            let (class, (index, field_type)) = class
+                .and_then(|class| field_of(&class, &field).map(|field| (class.path, field)))
+                .ok_or_else(|| ZXError::TypeError {
+                    message: format!("no field `{}` on type `{}`", name, target_type),
+                    pos: field.pos.clone(),
+                })?;
+
+            place = Place::Field {
+                field_type: field_type.clone(),
+                object: Box::new(place.load().ok_or_else(not_assignable)?),
+                class,
+                index,
+            };
+            target_type = field_type;
+            pos = pos.to(&field.pos);
+            next = field_next;
+        }
+
+        for (index, right_square_brackets) in indexes {
+            let array = place.load().ok_or_else(not_assignable)?;
+            let (element_type, index) =
+                self.index_of(global_scopes, currently, target_type, &pos, index)?;
+
+            pos = pos.to(&right_square_brackets);
+            target_type = element_type.clone();
+            place = Place::Element {
+                element_type,
+                array: Box::new(array),
+                index: index.map(Box::new),
+                pos: pos.clone(),
+            };
+        }
+
+        Ok((target_type, pos, place))
+    }
+}
+
+// the indexed expression of `a[0][1]` and its indexes from the inside out
+// with the positions of their `]`
+fn indexed(expression: Expression) -> (Expression, Vec<(Expression, Position)>) {
+    match expression {
+        Expression::Index {
+            target,
+            index,
+            right_square_brackets,
+            next: None,
+            ..
+        } => {
+            let (target, mut indexes) = indexed(*target);
+            indexes.push((*index, right_square_brackets.pos));
+
+            (target, indexes)
+        }
+        expression => (expression, vec![]),
+    }
+}
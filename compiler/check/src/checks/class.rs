@@ -0,0 +1,472 @@
+use crate::{Checker, ZXTyped};
+use util::ast::{Expression, Statement};
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
+use util::token::{Position, Token};
+
+impl Checker {
+    /// The class declared by `statement`. The members of the base class
+    /// come first and a method marked `override` takes the place of the base
+    /// method it replaces. Methods can use `this`, `super` and the type
+    /// parameters of the class.
+    pub(crate) fn class(
+        &mut self,
+        statement: Statement,
+        scopes: &mut Scopes,
+        path: String,
+        children: &mut Vec<Scopes>,
+    ) -> Result<Scope, ZXError> {
+        let (doc, class_name, type_parameters, inherit, member) = match statement {
+            Statement::Class {
+                doc,
+                class_name,
+                type_parameters,
+                inherit,
+                member,
+                ..
+            } => (doc, class_name, type_parameters, inherit, member),
+            _ => return Err(ZXError::InternalError { message: "".into() }),
+        };
+        let name = class_name.get_symbol()?;
+        let path = format!("{}${}", path, name);
+        let (type_parameters, type_parameter_scopes) =
+            self.type_parameters(&type_parameters, &path)?;
+        let (base, mut members) = match inherit {
+            Some(inherit) => {
+                let base = self.find_scope(scopes, children, &inherit)?;
+
+                match base.scope_type {
+                    ScopeType::DefClass {
+                        type_parameters, ..
+                    } if !type_parameters.is_empty() => {
+                        return Err(ZXError::TypeError {
+                            message: format!(
+                                "cannot inherit from the generic class `{}`",
+                                base.name
+                            ),
+                            pos: inherit.pos,
+                        })
+                    }
+                    ScopeType::DefClass { members, .. } => (Some(base.name), members),
+                    _ => {
+                        return Err(ZXError::TypeError {
+                            message: format!("`{}` is not a class", base.name),
+                            pos: inherit.pos,
+                        })
+                    }
+                }
+            }
+            None => (None, Scopes::new()),
+        };
+        let inherited = members.scopes.len();
+
+        children.push(Scopes::new());
+        for member in member {
+            let (member, is_override) = strip_override(member);
+            // the class with the members declared so far
+            *children.last_mut().unwrap() = class_frame(
+                name,
+                &path,
+                &type_parameters,
+                &type_parameter_scopes,
+                base,
+                &members,
+                &class_name.pos,
+            );
+
+            let scope = match self.declaration(member, scopes, path.clone(), children) {
+                Ok(scope) => scope,
+                Err(error) => {
+                    children.pop();
+                    return Err(error);
+                }
+            };
+
+            let added = match (
+                members
+                    .scopes
+                    .iter()
+                    .position(|member| member.name == scope.name),
+                base,
+            ) {
+                (Some(index), Some(base)) if index < inherited => {
+                    override_member(&mut members.scopes[index], scope, base, is_override)
+                }
+                (Some(_), _) => Err(ZXError::NameError {
+                    message: format!("`{}` is already declared in `{}`", scope.name, name),
+                    pos: scope.pos,
+                }),
+                (None, _) if is_override => Err(ZXError::TypeError {
+                    message: match base {
+                        Some(base) => format!(
+                            "method `{}` is marked `override` but `{}` has no method `{}`",
+                            scope.name, base, scope.name
+                        ),
+                        None => format!(
+                            "method `{}` is marked `override` but `{}` has no base class",
+                            scope.name, name
+                        ),
+                    },
+                    pos: scope.pos,
+                }),
+                (None, _) => {
+                    members.add_scope(scope);
+                    Ok(())
+                }
+            };
+
+            if let Err(error) = added {
+                self.reposts.push(Report {
+                    level: Level::Error,
+                    error,
+                });
+            }
+        }
+        children.pop();
+
+        Ok(Scope {
+            name,
+            path,
+            pos: class_name.pos,
+            scope_type: ScopeType::DefClass {
+                type_parameters,
+                members,
+                base,
+            },
+            uses_num: 0,
+            doc,
+            public: false,
+        })
+    }
+
+    /// `next` after `object`, a value of the class `class`. A method called
+    /// on the object is looked up in the vtable of the object unless
+    /// `direct`, then the method of `class` is called. A field is loaded
+    /// from the object.
+    pub(crate) fn member_access(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        object: Option<BytecodeType>,
+        class: Scope,
+        next: Expression,
+        direct: bool,
+    ) -> Result<(ZXTyped, Option<BytecodeType>), ZXError> {
+        let mut members = match &class.scope_type {
+            ScopeType::DefClass { members, .. } => members.clone(),
+            _ => {
+                return Err(ZXError::UnknownError {
+                    message: String::new(),
+                })
+            }
+        };
+        let (call_name, left_parentheses, arguments, right_parentheses, next) = match next {
+            Expression::SubMember { sub_member } => match *sub_member {
+                Expression::Call {
+                    call_name,
+                    left_parentheses,
+                    arguments,
+                    right_parentheses,
+                    next,
+                } if is_method(&members, &call_name) => (
+                    call_name,
+                    left_parentheses,
+                    arguments,
+                    right_parentheses,
+                    next,
+                ),
+                Expression::Identifier { identifier, next }
+                    if field_of(&class, &identifier).is_some() =>
+                {
+                    return self.field_access(
+                        global_scopes,
+                        currently,
+                        object,
+                        class,
+                        identifier,
+                        next,
+                    );
+                }
+                sub_member => {
+                    let member =
+                        self.auto_type(global_scopes, Some(&mut members), currently, sub_member)?;
+                    return Ok((member.0, None));
+                }
+            },
+            next => {
+                let member = self.auto_type(global_scopes, Some(&mut members), currently, next)?;
+                return Ok((member.0, None));
+            }
+        };
+        let pos = call_name.pos.to(&right_parentheses.pos);
+        let name = call_name.get_string()?;
+        let (ret_type, _, call) = self.auto_type(
+            global_scopes,
+            Some(&mut members),
+            currently,
+            Expression::Call {
+                call_name,
+                left_parentheses,
+                arguments,
+                right_parentheses,
+                next: None,
+            },
+        )?;
+        // a generic method has no slot of its own
+        let method = match (object, call) {
+            (
+                Some(object),
+                Some(BytecodeType::Call {
+                    ret_type,
+                    type_arguments,
+                    arguments,
+                    ..
+                }),
+            ) if type_arguments.is_empty() => Some(BytecodeType::CallMethod {
+                object: Box::new(object),
+                class: class.path,
+                name,
+                direct,
+                ret_type,
+                arguments,
+            }),
+            _ => None,
+        };
+
+        match next {
+            Some(next) => {
+                let class = self.object_class(global_scopes, currently, &ret_type, pos)?;
+                self.member_access(global_scopes, currently, method, class, *next, false)
+            }
+            None => Ok((ret_type, method)),
+        }
+    }
+
+    // the field `name` of `object`, a value of `class`, and `next` after it
+    fn field_access(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        object: Option<BytecodeType>,
+        class: Scope,
+        name: Token,
+        next: Option<Box<Expression>>,
+    ) -> Result<(ZXTyped, Option<BytecodeType>), ZXError> {
+        let (index, field_type) = field_of(&class, &name).ok_or(ZXError::InternalError {
+            message: "not a field".into(),
+        })?;
+        let value = object.map(|object| BytecodeType::FieldLoad {
+            object: Box::new(object),
+            class: class.path,
+            index,
+            field_type: field_type.clone(),
+        });
+
+        match next {
+            Some(next) => {
+                let class = self.object_class(global_scopes, currently, &field_type, name.pos)?;
+                self.member_access(global_scopes, currently, value, class, *next, false)
+            }
+            None => Ok((field_type, value)),
+        }
+    }
+
+    /// The class of a value of `object_type` with a member used at `pos`
+    pub(crate) fn object_class(
+        &self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        object_type: &ZXTyped,
+        pos: Position,
+    ) -> Result<Scope, ZXError> {
+        self.class_scope(global_scopes, currently, object_type, pos.clone())
+            .map_err(|_| ZXError::TypeError {
+                message: format!(
+                    "member reference base type '{}' is not a structure or union",
+                    object_type
+                ),
+                pos,
+            })
+    }
+}
+
+// whether the member `name` is a method
+fn is_method(members: &Scopes, name: &Token) -> bool {
+    members.scopes.iter().any(|member| {
+        name.get_symbol().ok() == Some(member.name)
+            && matches!(member.scope_type, ScopeType::DefFunction { .. })
+    })
+}
+
+/// The index of the field `name` among the fields of `class` and its type.
+/// The vtable comes first in an object, the field is one further in it.
+pub(crate) fn field_of(class: &Scope, name: &Token) -> Option<(usize, ZXTyped)> {
+    let members = match &class.scope_type {
+        ScopeType::DefClass { members, .. } => members,
+        _ => return None,
+    };
+
+    members
+        .scopes
+        .iter()
+        .filter_map(|member| match &member.scope_type {
+            ScopeType::DefVariable { var_type, .. } => Some((member.name, var_type)),
+            _ => None,
+        })
+        .enumerate()
+        .find(|(_, (member, _))| name.get_symbol().ok() == Some(*member))
+        .map(|(index, (_, var_type))| (index, var_type.clone()))
+}
+
+// replace the inherited member `inherited` with the method `scope`
+fn override_member(
+    inherited: &mut Scope,
+    scope: Scope,
+    base: Symbol,
+    is_override: bool,
+) -> Result<(), ZXError> {
+    match (&inherited.scope_type, &scope.scope_type) {
+        (
+            ScopeType::DefFunction {
+                parameters,
+                return_type,
+                ..
+            },
+            ScopeType::DefFunction {
+                parameters: new_parameters,
+                return_type: new_return_type,
+                ..
+            },
+        ) => {
+            if !is_override {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "method `{}` overrides `{}::{}` but is not marked `override`",
+                        scope.name, base, scope.name
+                    ),
+                    pos: scope.pos,
+                });
+            }
+
+            if return_type != new_return_type
+                || parameter_types(parameters) != parameter_types(new_parameters)
+            {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "method `{}` does not match the signature of `{}::{}`",
+                        scope.name, base, scope.name
+                    ),
+                    pos: scope.pos,
+                });
+            }
+
+            *inherited = scope;
+            Ok(())
+        }
+        _ => Err(ZXError::NameError {
+            message: format!("`{}` is already declared in `{}`", scope.name, base),
+            pos: scope.pos,
+        }),
+    }
+}
+
+fn parameter_types(parameters: &[Scope]) -> Vec<Option<&ZXTyped>> {
+    parameters
+        .iter()
+        .map(|parameter| match &parameter.scope_type {
+            ScopeType::DefVariable { var_type, .. } => Some(var_type),
+            _ => None,
+        })
+        .collect()
+}
+
+// `override fn`, also behind `pub` or `static`, as the method and whether it
+// was marked `override`
+fn strip_override(statement: Statement) -> (Statement, bool) {
+    match statement {
+        Statement::Override { statement, .. } => (*statement, true),
+        Statement::Public { statement } => {
+            let (statement, is_override) = strip_override(*statement);
+            (
+                Statement::Public {
+                    statement: Box::new(statement),
+                },
+                is_override,
+            )
+        }
+        Statement::Static { statement } => {
+            let (statement, is_override) = strip_override(*statement);
+            (
+                Statement::Static {
+                    statement: Box::new(statement),
+                },
+                is_override,
+            )
+        }
+        statement => (statement, false),
+    }
+}
+
+// scopes visible to the methods of a class: the class itself, its type
+// parameters, `this` and `super`
+fn class_frame(
+    name: Symbol,
+    path: &str,
+    type_parameters: &[Symbol],
+    type_parameter_scopes: &Scopes,
+    base: Option<Symbol>,
+    members: &Scopes,
+    pos: &Position,
+) -> Scopes {
+    let scope = |scope_name: Symbol, scope_path: String, scope_type: ScopeType| Scope {
+        name: scope_name,
+        path: scope_path,
+        pos: pos.clone(),
+        scope_type,
+        uses_num: 0,
+        doc: None,
+        public: false,
+    };
+    let variable = |variable: &str, var_type: ZXTyped| {
+        scope(
+            Symbol::intern(variable),
+            format!("{}${}", path, variable),
+            ScopeType::DefVariable {
+                var_type,
+                value: None,
+            },
+        )
+    };
+    // `this` of `class List<T>` is a `List<T>`
+    let this_type = if type_parameters.is_empty() {
+        ZXTyped::Other(name.to_string())
+    } else {
+        ZXTyped::Generic {
+            name: name.to_string(),
+            arguments: type_parameters
+                .iter()
+                .map(|type_parameter| ZXTyped::Param(type_parameter.to_string()))
+                .collect(),
+        }
+    };
+    let mut frame = type_parameter_scopes.clone();
+
+    frame.add_scope(scope(
+        name,
+        path.to_string(),
+        ScopeType::DefClass {
+            type_parameters: type_parameters.to_vec(),
+            members: members.clone(),
+            base,
+        },
+    ));
+    frame.add_scope(variable("this", this_type));
+    if let Some(base) = base {
+        frame.add_scope(variable("super", ZXTyped::Other(base.to_string())));
+    }
+
+    frame
+}
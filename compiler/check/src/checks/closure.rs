@@ -0,0 +1,350 @@
+use crate::checks::generic::accepts;
+use crate::{Checker, ZXTyped};
+use util::ast::Expression;
+use util::bytecode::{BytecodeType, Variable};
+use util::error::ZXError;
+use util::scope::{Scope, ScopeType, Scopes};
+use util::token::{Position, Token};
+
+impl Checker {
+    /// `(Int, Int) -> Int`
+    pub(crate) fn function_type(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        left_parentheses: Token,
+        parameters: Vec<Expression>,
+        return_type: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let parameters = parameters
+            .into_iter()
+            .map(|parameter| Ok(self.auto_type(global_scopes, None, currently, parameter)?.0))
+            .collect::<Result<Vec<ZXTyped>, ZXError>>()?;
+        let (return_type, return_pos, _) =
+            self.auto_type(global_scopes, None, currently, return_type)?;
+
+        Ok((
+            ZXTyped::Function {
+                parameters,
+                return_type: Box::new(return_type),
+            },
+            Some(match return_pos {
+                Some(return_pos) => left_parentheses.pos.to(&return_pos),
+                None => left_parentheses.pos,
+            }),
+            None,
+        ))
+    }
+
+    /// `fn(a: Int): Int { a + n }`, an anonymous function. The local
+    /// variables around it that the block uses are captured, without a
+    /// return type it returns what its block does.
+    pub(crate) fn closure(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        expression: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (fn_keyword, parameters, return_type, block) = match expression {
+            Expression::Function {
+                fn_keyword,
+                parameters,
+                return_type,
+                block,
+                ..
+            } => (fn_keyword, parameters, return_type, block),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not an anonymous function".into(),
+                })
+            }
+        };
+        // anonymous functions are told apart by where they start
+        let path = format!("{}$closure@{}", self.path, fn_keyword.pos.start);
+        let return_type = match return_type {
+            Some(return_type) => {
+                let (return_type, return_pos, _) =
+                    self.auto_type(global_scopes, None, currently, *return_type)?;
+
+                Some((return_type, return_pos))
+            }
+            None => None,
+        };
+        let parameters = parameters
+            .iter()
+            .map(|parameter| {
+                let (var_type, ..) = self.auto_type(
+                    global_scopes,
+                    None,
+                    currently,
+                    parameter.type_expression.clone(),
+                )?;
+                let name = parameter.parameter_name.get_symbol()?;
+
+                Ok(Scope {
+                    name,
+                    path: format!("{}${}", path, name),
+                    pos: parameter.parameter_name.pos.clone(),
+                    scope_type: ScopeType::DefVariable {
+                        var_type,
+                        value: None,
+                    },
+                    uses_num: 0,
+                    doc: None,
+                    public: false,
+                })
+            })
+            .collect::<Result<Vec<Scope>, ZXError>>()?;
+
+        let uses_before = uses(currently);
+        let mut frame = Scopes::new();
+        parameters
+            .iter()
+            .for_each(|parameter| frame.add_scope(parameter.clone()));
+        currently.push(frame);
+        let body = self.declaration(*block, global_scopes, path.clone(), currently);
+        currently.pop();
+        let body = body?;
+        let captures = captures(currently, &uses_before);
+        let (ret, bytecodes) = match body.scope_type {
+            ScopeType::Block { ret, bytecodes, .. } => (ret, bytecodes),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "the body of an anonymous function is not a block".into(),
+                })
+            }
+        };
+        let return_type = match return_type {
+            Some((return_type, return_pos)) => {
+                if !accepts(&return_type, &ret.0) {
+                    return Err(ZXError::TypeError {
+                        message: format!(
+                            "mismatched types, expected `{}` found `{}`",
+                            return_type, ret.0
+                        ),
+                        pos: ret.1.or(return_pos).unwrap_or(body.pos),
+                    });
+                }
+
+                return_type
+            }
+            None => ret.0,
+        };
+        let parameters = parameters
+            .into_iter()
+            .map(|parameter| match parameter.scope_type {
+                ScopeType::DefVariable { var_type, .. } => Variable {
+                    path: parameter.path,
+                    var_type,
+                },
+                _ => unreachable!("parameters are variables"),
+            })
+            .collect::<Vec<Variable>>();
+
+        Ok((
+            ZXTyped::Function {
+                parameters: parameters
+                    .iter()
+                    .map(|parameter| parameter.var_type.clone())
+                    .collect(),
+                return_type: Box::new(return_type.clone()),
+            },
+            Some(fn_keyword.pos.to(&body.pos)),
+            Some(BytecodeType::Closure {
+                path,
+                parameters,
+                return_type,
+                captures,
+                body: Box::new(BytecodeType::Box { bytecodes }),
+            }),
+        ))
+    }
+
+    /// A declared function used as a value, `add` of `var f = add`
+    pub(crate) fn function_value(
+        &self,
+        scope: Scope,
+        identifier: Token,
+        next: Option<Box<Expression>>,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (type_parameters, parameters, return_type) = match scope.scope_type {
+            ScopeType::DefFunction {
+                type_parameters,
+                parameters,
+                return_type,
+                ..
+            } => (type_parameters, parameters, return_type),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a function".into(),
+                })
+            }
+        };
+        if !type_parameters.is_empty() {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "generic function `{}` cannot be used as a value",
+                    scope.name
+                ),
+                pos: identifier.pos,
+            });
+        }
+        let function_type = ZXTyped::Function {
+            parameters: parameter_types(&parameters)?,
+            return_type: Box::new(return_type),
+        };
+        if next.is_some() {
+            return Err(ZXError::TypeError {
+                message: format!("`{}` has no members", function_type),
+                pos: identifier.pos,
+            });
+        }
+
+        Ok((
+            function_type.clone(),
+            Some(identifier.pos),
+            Some(BytecodeType::FunctionRef {
+                path: scope.path,
+                function_type,
+            }),
+        ))
+    }
+
+    /// `f(1, 2)` where the variable `f` holds a function or a closure
+    pub(crate) fn call_value(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        scope: Scope,
+        call: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (call_name, arguments, right_parentheses, next) = match call {
+            Expression::Call {
+                call_name,
+                arguments,
+                right_parentheses,
+                next,
+                ..
+            } => (call_name, arguments, right_parentheses, next),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a call".into(),
+                })
+            }
+        };
+        let pos = call_name.pos.to(&right_parentheses.pos);
+        let function_type = match scope.scope_type {
+            ScopeType::DefVariable { var_type, .. } => var_type,
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a variable".into(),
+                })
+            }
+        };
+        let (parameters, return_type) = match &function_type {
+            ZXTyped::Function {
+                parameters,
+                return_type,
+            } => (parameters.clone(), return_type.as_ref().clone()),
+            var_type => {
+                return Err(ZXError::TypeError {
+                    message: format!("expected a function, found `{}`", var_type),
+                    pos: call_name.pos,
+                })
+            }
+        };
+        if arguments.len() != parameters.len() {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "this function takes {} argument but {} arguments were supplied",
+                    parameters.len(),
+                    arguments.len()
+                ),
+                pos,
+            });
+        }
+
+        let mut values = vec![];
+        for (parameter_type, argument) in parameters.iter().zip(arguments) {
+            let (argument_type, argument_pos, value) =
+                self.auto_type(global_scopes, None, currently, argument)?;
+
+            if !accepts(parameter_type, &argument_type) {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "mismatched types, expected `{}` found `{}`",
+                        parameter_type, argument_type
+                    ),
+                    pos: argument_pos.unwrap_or_else(|| pos.clone()),
+                });
+            }
+            values.push(value);
+        }
+        let value_type = if let Some(next) = next {
+            let class = self.class_scope(global_scopes, currently, &return_type, pos.clone())?;
+
+            if let ScopeType::DefClass { mut members, .. } = class.scope_type {
+                self.auto_type(global_scopes, Some(&mut members), currently, *next)?
+                    .0
+            } else {
+                return Err(ZXError::TypeError {
+                    message: format!("`{}` has no members", return_type),
+                    pos,
+                });
+            }
+        } else {
+            return_type
+        };
+
+        Ok((
+            value_type,
+            Some(pos),
+            values
+                .into_iter()
+                .collect::<Option<Vec<BytecodeType>>>()
+                .map(|arguments| BytecodeType::CallValue {
+                    function: Box::new(BytecodeType::Load { path: scope.path }),
+                    function_type,
+                    arguments,
+                }),
+        ))
+    }
+}
+
+fn parameter_types(parameters: &[Scope]) -> Result<Vec<ZXTyped>, ZXError> {
+    parameters
+        .iter()
+        .map(|parameter| match &parameter.scope_type {
+            ScopeType::DefVariable { var_type, .. } => Ok(var_type.clone()),
+            _ => Err(ZXError::InternalError {
+                message: "a parameter is not a variable".into(),
+            }),
+        })
+        .collect()
+}
+
+// how often every local variable was used so far, frame by frame
+fn uses(currently: &[Scopes]) -> Vec<Vec<i32>> {
+    currently
+        .iter()
+        .map(|frame| frame.scopes.iter().map(|scope| scope.uses_num).collect())
+        .collect()
+}
+
+// the local variables used since `uses_before` was taken, the ones an
+// anonymous function uses
+fn captures(currently: &[Scopes], uses_before: &[Vec<i32>]) -> Vec<Variable> {
+    currently
+        .iter()
+        .zip(uses_before)
+        .flat_map(|(frame, uses_before)| frame.scopes.iter().zip(uses_before))
+        .filter(|(scope, uses_before)| scope.uses_num > **uses_before)
+        .filter_map(|(scope, _)| match &scope.scope_type {
+            ScopeType::DefVariable { var_type, .. } => Some(Variable {
+                path: scope.path.clone(),
+                var_type: var_type.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
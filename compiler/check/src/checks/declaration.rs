@@ -1,13 +1,15 @@
-use crate::ScopeType::DefClass;
+use crate::checks::generic::{accepts, is_uninferred};
 use crate::{Checker, ZXTyped};
 use util::ast::Statement;
-use util::ast::Statement::{Block, Class, FunctionDeclaration, VariableDeclaration};
+use util::ast::Statement::{
+    Block, Class, Enum, FunctionDeclaration, Override, Public, VariableDeclaration,
+};
 use util::bytecode::BytecodeType;
 use util::error::ZXError;
 use util::report::Level::{self, Error};
 use util::report::Report;
 use util::scope::{Scope, ScopeType, Scopes};
-use util::token::Position;
+use util::symbol::Symbol;
 use util::token::Tokens::IdentifierToken;
 
 impl Checker {
@@ -20,99 +22,24 @@ impl Checker {
     ) -> Result<Scope, ZXError> {
         match statement {
             FunctionDeclaration {
-                function_name,
-                parameters,
-                block,
-                return_type,
+                ref function_name,
+                ref type_parameters,
                 ..
             } => {
-                let mut param_index = 0;
-                let return_type = if let Some(expression) = return_type {
-                    let ret = self.auto_type(scopes, None, children, expression)?;
-                    (ret.0, ret.1)
-                } else {
-                    (ZXTyped::Void, None)
-                };
+                let function_path = format!("{}${}", path, function_name.get_symbol()?);
+                let (type_parameters, frame) =
+                    self.type_parameters(type_parameters, &function_path)?;
 
-                let parameters = parameters
-                    .iter()
-                    .map(|parameter| {
-                        let scope = self.auto_type(
-                            scopes,
-                            None,
-                            children,
-                            parameter.type_expression.clone(),
-                        )?;
-                        let name = parameter.parameter_name.get_string()?;
-                        let path = format!("{}${}", path, name);
-                        let scope = Scope {
-                            name,
-                            path,
-                            pos: parameter.parameter_name.pos.clone(),
-                            scope_type: ScopeType::DefVariable {
-                                var_type: scope.0,
-                                value: Some(BytecodeType::param_value(param_index)),
-                            },
-                            uses_num: 0,
-                        };
-                        param_index = param_index + 1;
-                        Ok(scope)
-                    })
-                    .collect::<Result<Vec<Scope>, ZXError>>()?;
-                let name = if let IdentifierToken { literal } = function_name.token_type {
-                    literal
-                } else {
-                    return Err(ZXError::UnknownError {
-                        message: "".to_string(),
-                    });
-                };
-                let path = format!("{}${}", path, name);
-                let scope = Scope {
-                    name: name.clone(),
-                    path: path.clone(),
-                    scope_type: ScopeType::DefFunction {
-                        parameters: parameters.clone(),
-                        block: BytecodeType::Box { bytecodes: vec![] },
-                        return_type: return_type.0.clone(),
-                        children: Scopes::new(),
-                    },
-                    uses_num: 0,
-                    pos: function_name.pos.clone(),
-                };
-                children.last_mut().unwrap().add_scope(scope.clone());
-                let block_scope = self.declaration(*block, scopes, path.clone(), children)?;
-                let block = if let ScopeType::Block {
-                    ret,
-                    bytecodes,
-                    children,
-                } = block_scope.scope_type
-                {
-                    if return_type.0 != ret.0 {
-                        Err(ZXError::TypeError {
-                            message: "mismatched types".to_string(),
-                            pos: return_type.1.unwrap(),
-                        })
-                    } else {
-                        Ok((BytecodeType::Box { bytecodes }, children))
-                    }
-                } else {
-                    Err(ZXError::InternalError { message: "".into() })
-                }?;
+                // the type parameters and the parameters are only visible in
+                // the function
+                children.push(frame);
+                let scope = self.function(statement, type_parameters, scopes, path, children);
+                children.pop();
 
-                Ok(Scope {
-                    name,
-                    path,
-                    scope_type: ScopeType::DefFunction {
-                        parameters,
-                        block: block.0,
-                        return_type: return_type.0.clone(),
-                        children: block.1,
-                    },
-                    uses_num: 0,
-                    pos: function_name.pos,
-                })
+                scope
             }
             VariableDeclaration {
+                doc,
                 var_name,
                 type_identifier,
                 value,
@@ -120,18 +47,19 @@ impl Checker {
                 ..
             } => {
                 let auto_type = if let Some(type_expression) = type_identifier {
-                    let auto_type = self.auto_type(scopes, None, children, type_expression)?;
+                    let mut auto_type = self.auto_type(scopes, None, children, type_expression)?;
 
                     if let Some(value) = value {
                         if let Statement::Expression { expression } = *value {
                             let value_type =
                                 self.auto_type(scopes, None, children, expression.clone())?;
-                            if auto_type.0 != value_type.0 {
+                            if !accepts(&auto_type.0, &value_type.0) {
                                 return Err(ZXError::TypeError {
                                     message: "mismatched types".to_string(),
                                     pos: auto_type.1.unwrap(),
                                 });
                             }
+                            auto_type.2 = value_type.2;
                         }
                     }
 
@@ -139,7 +67,21 @@ impl Checker {
                 } else {
                     if let Some(value) = value {
                         if let Statement::Expression { expression } = *value {
-                            self.auto_type(scopes, None, children, expression.clone())?
+                            let value_type =
+                                self.auto_type(scopes, None, children, expression.clone())?;
+
+                            // `var list = List()` does not say what the list holds
+                            if is_uninferred(&value_type.0) {
+                                return Err(ZXError::TypeError {
+                                    message: format!(
+                                        "type annotations needed for `{}`",
+                                        value_type.0
+                                    ),
+                                    pos: var_name.pos.clone(),
+                                });
+                            }
+
+                            value_type
                         } else {
                             return Err(ZXError::SyntaxError {
                                 message: "this is not a expression".to_string(),
@@ -170,32 +112,12 @@ impl Checker {
                     },
                     uses_num: 0,
                     pos: var_name.pos,
+                    doc,
+                    public: false,
                 })
             }
-            Class {
-                class_name, member, ..
-            } => {
-                let mut members = Scopes::new();
-                let name = if let IdentifierToken { literal } = class_name.token_type {
-                    literal
-                } else {
-                    return Err(ZXError::UnknownError {
-                        message: "".to_string(),
-                    });
-                };
-                let path = format!("{}${}", path, name);
-
-                for member in member {
-                    members.add_scope(self.declaration(member, scopes, path.clone(), children)?);
-                }
-                Ok(Scope {
-                    name,
-                    path,
-                    pos: class_name.pos,
-                    scope_type: DefClass { members },
-                    uses_num: 0,
-                })
-            }
+            Class { .. } => self.class(statement, scopes, path, children),
+            Enum { .. } => self.enum_declaration(statement, scopes, path, children),
             Block {
                 left_curly_brackets,
                 statements,
@@ -209,9 +131,10 @@ impl Checker {
                         Ok(ret) => {
                             if ret.1.is_some() {
                                 ret_type = (ret.0, ret.1);
-                                if ret.2.is_some() {
-                                    bytecodes.push(ret.2.unwrap())
-                                }
+                            }
+                            // stores of declarations and assignments have no position
+                            if let Some(bytecode) = ret.2 {
+                                bytecodes.push(bytecode)
                             }
                         }
                         Err(error) => self.reposts.push(Report {
@@ -235,7 +158,7 @@ impl Checker {
                     });
                 children.pop();
                 Ok(Scope {
-                    name: "$".into(),
+                    name: Symbol::intern("$"),
                     path,
                     scope_type: ScopeType::Block {
                         children: children_clone,
@@ -243,12 +166,23 @@ impl Checker {
                         bytecodes,
                     },
                     uses_num: 0,
-                    pos: Position {
-                        start: left_curly_brackets.pos.start.clone(),
-                        end: right_curly_brackets.pos.end.clone(),
-                    },
+                    pos: left_curly_brackets.pos.to(&right_curly_brackets.pos),
+                    doc: None,
+                    public: false,
                 })
             }
+            Override {
+                override_keyword, ..
+            } => Err(ZXError::SyntaxError {
+                message: String::from("`override` is only allowed on methods of a class"),
+                pos: override_keyword.pos,
+            }),
+            Public { statement } => {
+                let mut scope = self.declaration(*statement, scopes, path, children)?;
+                scope.public = true;
+
+                Ok(scope)
+            }
             _ => {
                 return Err(ZXError::UnknownError {
                     message: String::from("Unknown statement."),
@@ -256,4 +190,121 @@ impl Checker {
             }
         }
     }
+
+    // a function declaration with the frame of its type parameters on top of
+    // `children`
+    fn function(
+        &mut self,
+        statement: Statement,
+        type_parameters: Vec<Symbol>,
+        scopes: &mut Scopes,
+        path: String,
+        children: &mut Vec<Scopes>,
+    ) -> Result<Scope, ZXError> {
+        let (doc, function_name, parameters, block, return_type) = match statement {
+            FunctionDeclaration {
+                doc,
+                function_name,
+                parameters,
+                block,
+                return_type,
+                ..
+            } => (doc, function_name, parameters, block, return_type),
+            _ => return Err(ZXError::InternalError { message: "".into() }),
+        };
+        let mut param_index = 0;
+        let return_type = if let Some(expression) = return_type {
+            let ret = self.auto_type(scopes, None, children, expression)?;
+            (ret.0, ret.1)
+        } else {
+            (ZXTyped::Void, None)
+        };
+
+        let parameters = parameters
+            .iter()
+            .map(|parameter| {
+                let scope =
+                    self.auto_type(scopes, None, children, parameter.type_expression.clone())?;
+                let name = parameter.parameter_name.get_symbol()?;
+                let path = format!("{}${}", path, name);
+                let scope = Scope {
+                    name,
+                    path,
+                    pos: parameter.parameter_name.pos.clone(),
+                    scope_type: ScopeType::DefVariable {
+                        var_type: scope.0,
+                        value: Some(BytecodeType::param_value(param_index)),
+                    },
+                    uses_num: 0,
+                    doc: None,
+                    public: false,
+                };
+                param_index += 1;
+                Ok(scope)
+            })
+            .collect::<Result<Vec<Scope>, ZXError>>()?;
+        parameters
+            .iter()
+            .for_each(|parameter| children.last_mut().unwrap().add_scope(parameter.clone()));
+        let name = if let IdentifierToken { literal } = function_name.token_type {
+            literal
+        } else {
+            return Err(ZXError::UnknownError {
+                message: "".to_string(),
+            });
+        };
+        let path = format!("{}${}", path, name);
+        let scope = Scope {
+            name,
+            path: path.clone(),
+            scope_type: ScopeType::DefFunction {
+                type_parameters: type_parameters.clone(),
+                parameters: parameters.clone(),
+                block: BytecodeType::Box { bytecodes: vec![] },
+                return_type: return_type.0.clone(),
+                children: Scopes::new(),
+            },
+            uses_num: 0,
+            pos: function_name.pos.clone(),
+            doc: doc.clone(),
+            public: false,
+        };
+        // declared in the scope around the function, before its body
+        let outer = children.len() - 2;
+        children[outer].add_scope(scope.clone());
+        let block_scope = self.declaration(*block, scopes, path.clone(), children)?;
+        let block = if let ScopeType::Block {
+            ret,
+            bytecodes,
+            children,
+        } = block_scope.scope_type
+        {
+            if !accepts(&return_type.0, &ret.0) {
+                Err(ZXError::TypeError {
+                    message: "mismatched types".to_string(),
+                    pos: return_type.1.unwrap_or_else(|| function_name.pos.clone()),
+                })
+            } else {
+                Ok((BytecodeType::Box { bytecodes }, children))
+            }
+        } else {
+            Err(ZXError::InternalError { message: "".into() })
+        }?;
+
+        Ok(Scope {
+            name,
+            path,
+            scope_type: ScopeType::DefFunction {
+                type_parameters,
+                parameters,
+                block: block.0,
+                return_type: return_type.0.clone(),
+                children: block.1,
+            },
+            uses_num: 0,
+            pos: function_name.pos,
+            doc,
+            public: false,
+        })
+    }
 }
@@ -0,0 +1,222 @@
+use crate::checks::generic::accepts;
+use crate::{Checker, ZXTyped};
+use util::ast::{Expression, Statement, Variant};
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{Scope, ScopeType, Scopes};
+use util::token::{Position, Token};
+
+impl Checker {
+    /// The enum declared by `statement`, a value of it has the type of the
+    /// enum. The variants can hold the enum itself.
+    pub(crate) fn enum_declaration(
+        &mut self,
+        statement: Statement,
+        scopes: &mut Scopes,
+        path: String,
+        children: &mut Vec<Scopes>,
+    ) -> Result<Scope, ZXError> {
+        let (doc, enum_name, declared) = match statement {
+            Statement::Enum {
+                doc,
+                enum_name,
+                variants,
+                ..
+            } => (doc, enum_name, variants),
+            _ => return Err(ZXError::InternalError { message: "".into() }),
+        };
+        let name = enum_name.get_symbol()?;
+        let path = format!("{}${}", path, name);
+        let scope = |variants: Scopes| Scope {
+            name,
+            path: path.clone(),
+            pos: enum_name.pos.clone(),
+            scope_type: ScopeType::DefEnum { variants },
+            uses_num: 0,
+            doc: doc.clone(),
+            public: false,
+        };
+
+        let mut frame = Scopes::new();
+        frame.add_scope(scope(Scopes::new()));
+        children.push(frame);
+        let mut variants = Scopes::new();
+        for Variant {
+            name: variant_name,
+            payload,
+        } in declared
+        {
+            let added = match variant_name.get_symbol() {
+                Ok(variant)
+                    if variants
+                        .scopes
+                        .iter()
+                        .any(|declared| declared.name == variant) =>
+                {
+                    Err(ZXError::NameError {
+                        message: format!("`{}` is already declared in `{}`", variant, name),
+                        pos: variant_name.pos,
+                    })
+                }
+                _ => self.variant(scopes, children, &path, &variants, variant_name, payload),
+            };
+
+            match added {
+                Ok(variant) => variants.add_scope(variant),
+                Err(error) => self.reposts.push(Report {
+                    level: Level::Error,
+                    error,
+                }),
+            }
+        }
+        children.pop();
+
+        Ok(scope(variants))
+    }
+
+    /// `Shape::Circle(1.0)` or `Shape::Empty`, a value of the variant of
+    /// the enum `scope`
+    pub(crate) fn variant_value(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        scope: Scope,
+        identifier: Token,
+        next: Option<Box<Expression>>,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let mut variants = match scope.scope_type {
+            ScopeType::DefEnum { variants } => variants,
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not an enum".into(),
+                })
+            }
+        };
+        let (variant_name, arguments, end, next) = match next.map(|next| *next) {
+            Some(Expression::Path { next }) => match *next {
+                Expression::Identifier {
+                    identifier: variant_name,
+                    next,
+                } => (variant_name.clone(), vec![], variant_name.pos, next),
+                Expression::Call {
+                    call_name,
+                    arguments,
+                    right_parentheses,
+                    next,
+                    ..
+                } => (call_name, arguments, right_parentheses.pos, next),
+                _ => {
+                    return Err(ZXError::TypeError {
+                        message: format!("expected a variant of `{}`", scope.name),
+                        pos: identifier.pos,
+                    })
+                }
+            },
+            _ => {
+                return Err(ZXError::TypeError {
+                    message: format!("expected a value, found enum `{}`", scope.name),
+                    pos: identifier.pos,
+                })
+            }
+        };
+        let pos = identifier.pos.to(&end);
+        let variant = variants
+            .find_scope(variant_name.get_symbol()?)
+            .ok_or_else(|| ZXError::NameError {
+                message: format!(
+                    "no variant named `{}` in `{}`",
+                    variant_name.get_string().unwrap_or_default(),
+                    scope.name
+                ),
+                pos: variant_name.pos.clone(),
+            })?;
+        let (tag, payload) = match variant.scope_type {
+            ScopeType::DefVariant { tag, payload } => (tag, payload),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a variant".into(),
+                })
+            }
+        };
+        if arguments.len() != payload.len() {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "the variant `{}::{}` takes {} fields but {} were supplied",
+                    scope.name,
+                    variant.name,
+                    payload.len(),
+                    arguments.len()
+                ),
+                pos,
+            });
+        }
+
+        let mut values = vec![];
+        for (field_type, argument) in payload.iter().zip(arguments) {
+            let (argument_type, argument_pos, value) =
+                self.auto_type(global_scopes, None, currently, argument)?;
+
+            if !accepts(field_type, &argument_type) {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "mismatched types, expected `{}` found `{}`",
+                        field_type, argument_type
+                    ),
+                    pos: argument_pos.unwrap_or_else(|| pos.clone()),
+                });
+            }
+            values.push(value);
+        }
+        if next.is_some() {
+            return Err(ZXError::TypeError {
+                message: format!("`{}` has no members", scope.name),
+                pos,
+            });
+        }
+
+        Ok((
+            ZXTyped::Other(scope.name.to_string()),
+            Some(pos),
+            values
+                .into_iter()
+                .collect::<Option<Vec<BytecodeType>>>()
+                .map(|payload| BytecodeType::EnumValue {
+                    name: scope.name.to_string(),
+                    variant: variant.name.to_string(),
+                    tag,
+                    payload,
+                }),
+        ))
+    }
+
+    // a variant of the enum at `path`, tagged with its index in `variants`
+    fn variant(
+        &mut self,
+        scopes: &mut Scopes,
+        children: &mut Vec<Scopes>,
+        path: &str,
+        variants: &Scopes,
+        variant_name: Token,
+        payload: Vec<Expression>,
+    ) -> Result<Scope, ZXError> {
+        let name = variant_name.get_symbol()?;
+        let payload = payload
+            .into_iter()
+            .map(|field| Ok(self.auto_type(scopes, None, children, field)?.0))
+            .collect::<Result<Vec<ZXTyped>, ZXError>>()?;
+
+        Ok(Scope {
+            name,
+            path: format!("{}${}", path, name),
+            pos: variant_name.pos,
+            scope_type: ScopeType::DefVariant {
+                tag: variants.scopes.len() as u32,
+                payload,
+            },
+            uses_num: 0,
+            doc: None,
+            public: false,
+        })
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{Checker, ZXTyped};
+use util::error::ZXError;
+use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
+use util::token::{Position, Token};
+
+impl Checker {
+    /// Scopes of the type parameters `<T, U>`, a name can only be used once
+    pub(crate) fn type_parameters(
+        &self,
+        type_parameters: &[Token],
+        path: &str,
+    ) -> Result<(Vec<Symbol>, Scopes), ZXError> {
+        let mut names: Vec<Symbol> = vec![];
+        let mut scopes = Scopes::new();
+
+        for type_parameter in type_parameters {
+            let name = type_parameter.get_symbol()?;
+
+            if names.contains(&name) {
+                return Err(ZXError::NameError {
+                    message: format!("the type parameter `{}` is already declared", name),
+                    pos: type_parameter.pos.clone(),
+                });
+            }
+            names.push(name);
+            scopes.add_scope(Scope {
+                name,
+                path: format!("{}${}", path, name),
+                pos: type_parameter.pos.clone(),
+                scope_type: ScopeType::DefTypeParameter,
+                // never reported as unused
+                uses_num: 1,
+                doc: None,
+                public: false,
+            });
+        }
+
+        Ok((names, scopes))
+    }
+
+    /// Type arguments of a call to a generic function, every type parameter
+    /// gets the type of the first argument passed for it
+    pub(crate) fn infer_type_arguments(
+        &self,
+        function: &Scope,
+        type_parameters: &[Symbol],
+        parameter_types: &[ZXTyped],
+        arguments: &[(ZXTyped, Option<Position>)],
+        pos: &Position,
+    ) -> Result<Vec<ZXTyped>, ZXError> {
+        let mut bindings: Vec<Option<ZXTyped>> = vec![None; type_parameters.len()];
+
+        for (parameter_type, (argument_type, argument_pos)) in parameter_types.iter().zip(arguments)
+        {
+            if !unify(
+                parameter_type,
+                argument_type,
+                type_parameters,
+                &mut bindings,
+            ) {
+                return Err(ZXError::TypeError {
+                    message: format!(
+                        "mismatched types, expected `{}` found `{}`",
+                        parameter_type.substitute(
+                            type_parameters,
+                            &bound_or_param(type_parameters, &bindings)
+                        ),
+                        argument_type
+                    ),
+                    pos: argument_pos.clone().unwrap_or_else(|| pos.clone()),
+                });
+            }
+        }
+
+        bindings
+            .into_iter()
+            .zip(type_parameters)
+            .map(|(binding, type_parameter)| {
+                binding.ok_or_else(|| ZXError::TypeError {
+                    message: format!(
+                        "cannot infer the type parameter `{}` of `{}`",
+                        type_parameter, function.name
+                    ),
+                    pos: pos.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The class of a value of `class_type`, for `List<Int>` the instance
+    /// of `List` with `Int` as its type argument
+    pub(crate) fn class_scope(
+        &self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        class_type: &ZXTyped,
+        pos: Position,
+    ) -> Result<Scope, ZXError> {
+        let (name, arguments) = match class_type {
+            ZXTyped::Generic { name, arguments } => (name.clone(), arguments.clone()),
+            class_type => (class_type.to_string(), vec![]),
+        };
+        let scope = self.find_scope_str(global_scopes, currently, Symbol::intern(&name), pos)?;
+
+        Ok(match &scope.scope_type {
+            // inside the class `this` is an instance with its own parameters
+            ScopeType::DefClass {
+                type_parameters, ..
+            } if !arguments.is_empty() && !is_own(type_parameters, &arguments) => {
+                scope.instantiate(&arguments)
+            }
+            _ => scope,
+        })
+    }
+}
+
+/// Whether a value of `found` can be used as `expected`. `List()` has no
+/// type arguments yet and can be used as any instance of `List`, `[]` as
+/// any array.
+pub(crate) fn accepts(expected: &ZXTyped, found: &ZXTyped) -> bool {
+    match (expected, found) {
+        (
+            ZXTyped::Generic { name, .. },
+            ZXTyped::Generic {
+                name: found_name,
+                arguments,
+            },
+        ) if arguments.is_empty() => name == found_name,
+        (
+            ZXTyped::Array { element, nullable },
+            ZXTyped::Array {
+                element: found_element,
+                nullable: found_nullable,
+            },
+        ) => {
+            nullable == found_nullable
+                && (**found_element == ZXTyped::Void || accepts(element, found_element))
+        }
+        _ => expected == found,
+    }
+}
+
+/// An instance of a generic class without type arguments or `[]`, its type
+/// has to be written down
+pub(crate) fn is_uninferred(value_type: &ZXTyped) -> bool {
+    match value_type {
+        ZXTyped::Generic { arguments, .. } => arguments.is_empty(),
+        ZXTyped::Array { element, .. } => **element == ZXTyped::Void || is_uninferred(element),
+        _ => false,
+    }
+}
+
+// bind the type parameters in `parameter` to the parts of `argument` at the
+// same place, false if they do not fit together
+fn unify(
+    parameter: &ZXTyped,
+    argument: &ZXTyped,
+    type_parameters: &[Symbol],
+    bindings: &mut Vec<Option<ZXTyped>>,
+) -> bool {
+    match (parameter, argument) {
+        (ZXTyped::Param(name), _) => {
+            match type_parameters
+                .iter()
+                .position(|type_parameter| type_parameter.as_str() == name)
+            {
+                Some(index) => match &bindings[index] {
+                    Some(bound) => accepts(bound, argument),
+                    None => {
+                        bindings[index] = Some(argument.clone());
+                        true
+                    }
+                },
+                // a type parameter of the enclosing declaration
+                None => parameter == argument,
+            }
+        }
+        (
+            ZXTyped::Generic { name, arguments },
+            ZXTyped::Generic {
+                name: argument_name,
+                arguments: argument_arguments,
+            },
+        ) if name == argument_name => {
+            argument_arguments.is_empty()
+                || (arguments.len() == argument_arguments.len()
+                    && arguments
+                        .iter()
+                        .zip(argument_arguments)
+                        .all(|(parameter, argument)| {
+                            unify(parameter, argument, type_parameters, bindings)
+                        }))
+        }
+        (
+            ZXTyped::Array { element, nullable },
+            ZXTyped::Array {
+                element: argument_element,
+                nullable: argument_nullable,
+            },
+        ) => {
+            nullable == argument_nullable
+                && (**argument_element == ZXTyped::Void
+                    || unify(element, argument_element, type_parameters, bindings))
+        }
+        (
+            ZXTyped::Function {
+                parameters,
+                return_type,
+            },
+            ZXTyped::Function {
+                parameters: argument_parameters,
+                return_type: argument_return_type,
+            },
+        ) => {
+            parameters.len() == argument_parameters.len()
+                && parameters
+                    .iter()
+                    .zip(argument_parameters)
+                    .all(|(parameter, argument)| {
+                        unify(parameter, argument, type_parameters, bindings)
+                    })
+                && unify(return_type, argument_return_type, type_parameters, bindings)
+        }
+        _ => accepts(parameter, argument),
+    }
+}
+
+fn bound_or_param(type_parameters: &[Symbol], bindings: &[Option<ZXTyped>]) -> Vec<ZXTyped> {
+    type_parameters
+        .iter()
+        .zip(bindings)
+        .map(|(type_parameter, binding)| {
+            binding
+                .clone()
+                .unwrap_or_else(|| ZXTyped::Param(type_parameter.to_string()))
+        })
+        .collect()
+}
+
+// `List<T>` used inside `List` itself
+fn is_own(type_parameters: &[Symbol], arguments: &[ZXTyped]) -> bool {
+    type_parameters.len() == arguments.len()
+        && type_parameters
+            .iter()
+            .zip(arguments)
+            .all(|(type_parameter, argument)| {
+                matches!(argument, ZXTyped::Param(name) if name == type_parameter.as_str())
+            })
+}
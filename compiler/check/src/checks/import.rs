@@ -0,0 +1,138 @@
+use crate::Checker;
+use util::ast::{ImportItem, ImportKind};
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
+use util::token::Token;
+
+impl Checker {
+    /// Add what an `im` statement brings into scope to `scopes`, every
+    /// import that can not be resolved is reported
+    pub(crate) fn import(&mut self, scopes: &mut Scopes, path: Vec<Token>, kind: ImportKind) {
+        let imported = match kind {
+            ImportKind::Single { alias } => {
+                let last = path.last().unwrap().clone();
+                self.import_single(&path, last, alias)
+                    .map(|scope| vec![scope])
+            }
+            ImportKind::Group { items } => {
+                let mut imported = vec![];
+                for ImportItem { name, alias } in items {
+                    match self.import_item(&path, &name, alias) {
+                        Ok(scope) => imported.push(scope),
+                        Err(error) => self.reposts.push(Report {
+                            level: Level::Error,
+                            error,
+                        }),
+                    }
+                }
+                Ok(imported)
+            }
+            ImportKind::Glob => self
+                .module_members(&path)
+                .map(|members| members.public_scopes().scopes),
+        };
+
+        match imported {
+            Ok(imported) => imported
+                .into_iter()
+                .for_each(|scope| scopes.add_scope(scope)),
+            Err(error) => self.reposts.push(Report {
+                level: Level::Error,
+                error,
+            }),
+        }
+    }
+
+    // `im a::b` imports the module `a::b` if there is one, else the item `b`
+    // of the module `a`
+    fn import_single(
+        &self,
+        path: &[Token],
+        last: Token,
+        alias: Option<Token>,
+    ) -> Result<Scope, ZXError> {
+        let names = symbols(path)?;
+
+        if let Some(members) = self.modules.get(&names) {
+            let name = alias.as_ref().unwrap_or(&last).get_symbol()?;
+
+            return Ok(Scope {
+                name,
+                path: names.iter().map(|name| format!("${}", name)).collect(),
+                pos: path[0].pos.to(&last.pos),
+                scope_type: ScopeType::DefModule {
+                    members: members.public_scopes(),
+                },
+                uses_num: 0,
+                doc: None,
+                public: false,
+            });
+        }
+
+        match path.split_last() {
+            Some((_, module)) if !module.is_empty() => self.import_item(module, &last, alias),
+            _ => Err(unresolved_module(path, &names)),
+        }
+    }
+
+    fn import_item(
+        &self,
+        module: &[Token],
+        name: &Token,
+        alias: Option<Token>,
+    ) -> Result<Scope, ZXError> {
+        let mut members = self.module_members(module)?;
+        let module_name = module_name(&symbols(module)?);
+        let symbol = name.get_symbol()?;
+        let mut scope = members.find_scope(symbol).ok_or(ZXError::NameError {
+            message: format!("cannot find `{}` in module `{}`", symbol, module_name),
+            pos: name.pos.clone(),
+        })?;
+
+        if !scope.public {
+            return Err(ZXError::NameError {
+                message: format!("`{}` is private to module `{}`", symbol, module_name),
+                pos: name.pos.clone(),
+            });
+        }
+
+        // the imported item keeps the path of its module
+        scope.name = alias.as_ref().unwrap_or(name).get_symbol()?;
+        scope.pos = name.pos.clone();
+        scope.uses_num = 0;
+        scope.public = false;
+
+        Ok(scope)
+    }
+
+    fn module_members(&self, module: &[Token]) -> Result<Scopes, ZXError> {
+        let names = symbols(module)?;
+
+        match self.modules.get(&names) {
+            Some(members) => Ok(members.clone()),
+            None => Err(unresolved_module(module, &names)),
+        }
+    }
+}
+
+fn symbols(path: &[Token]) -> Result<Vec<Symbol>, ZXError> {
+    path.iter().map(|name| name.get_symbol()).collect()
+}
+
+// `a::b`
+fn module_name(names: &[Symbol]) -> String {
+    names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>()
+        .join("::")
+}
+
+fn unresolved_module(module: &[Token], names: &[Symbol]) -> ZXError {
+    ZXError::NameError {
+        message: format!("cannot find module `{}`", module_name(names)),
+        pos: module[0].pos.to(&module[module.len() - 1].pos),
+    }
+}
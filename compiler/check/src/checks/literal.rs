@@ -0,0 +1,159 @@
+use crate::{Checker, ZXTyped};
+use util::ast::Expression;
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::Scopes;
+use util::token::{Literal, Position, Token};
+
+impl Checker {
+    /// Type and value of a number literal, the lexer has already validated the
+    /// digits so only the range is checked here.
+    pub(crate) fn number_value(
+        &self,
+        kid: &Literal,
+        content: &Token,
+    ) -> Result<(ZXTyped, BytecodeType), ZXError> {
+        let literal = content.get_string()?;
+        let (radix, digits, suffix) = split_number(&literal);
+        let negative = matches!(kid, Literal::NegativeInteger);
+
+        match (kid, suffix) {
+            (Literal::Float, "f64") => {
+                let value = digits.parse::<f64>().unwrap();
+
+                if value.is_infinite() {
+                    return Err(out_of_range(content, "Double"));
+                }
+                Ok((
+                    ZXTyped::Double { nullable: false },
+                    BytecodeType::double_value(value),
+                ))
+            }
+            (Literal::Float, _) => {
+                let value = digits.parse::<f64>().unwrap();
+
+                if value.is_infinite() || value > f32::MAX as f64 {
+                    return Err(out_of_range(content, "Float"));
+                }
+                Ok((
+                    ZXTyped::Float { nullable: false },
+                    BytecodeType::float_value(value as f32),
+                ))
+            }
+            (_, "i64") => {
+                let value = integer_value(radix, digits, negative, i64::MAX as u64)
+                    .ok_or_else(|| out_of_range(content, "Long"))?;
+
+                Ok((
+                    ZXTyped::Long { nullable: false },
+                    BytecodeType::long_value(value),
+                ))
+            }
+            _ => {
+                let value = integer_value(radix, digits, negative, i32::MAX as u64)
+                    .ok_or_else(|| out_of_range(content, "Int"))?;
+
+                Ok((
+                    ZXTyped::Integer { nullable: false },
+                    BytecodeType::int_value(value as i32),
+                ))
+            }
+        }
+    }
+
+    /// Type check every part of `"a ${b} c"` and lower it to string
+    /// conversions concatenated from left to right
+    pub(crate) fn string_template(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        left_quote: Token,
+        parts: Vec<Expression>,
+        right_quote: Token,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let pos = left_quote.pos.to(&right_quote.pos);
+        let mut bytecode: Option<BytecodeType> = None;
+
+        for part in parts {
+            let (part_type, part_pos, value) =
+                self.auto_type(global_scopes, None, currently, part)?;
+            let value = value.ok_or(ZXError::InternalError {
+                message: "string template part has no value".into(),
+            })?;
+            let value = match part_type {
+                ZXTyped::String { .. } => value,
+                ZXTyped::Integer { .. }
+                | ZXTyped::Long { .. }
+                | ZXTyped::Float { .. }
+                | ZXTyped::Double { .. }
+                | ZXTyped::Char { .. }
+                | ZXTyped::Bool { .. } => BytecodeType::ToStr {
+                    value: Box::new(value),
+                    value_type: part_type,
+                },
+                _ => {
+                    return Err(ZXError::TypeError {
+                        message: format!("`{}` cannot be converted to `Str`", part_type),
+                        pos: part_pos.unwrap_or(pos),
+                    })
+                }
+            };
+
+            bytecode = Some(match bytecode {
+                Some(left) => BytecodeType::Concat {
+                    left: Box::new(left),
+                    right: Box::new(value),
+                },
+                None => value,
+            });
+        }
+
+        Ok((ZXTyped::String { nullable: false }, Some(pos), bytecode))
+    }
+}
+
+// split `0xffi64` into its radix, digits and suffix
+fn split_number(literal: &str) -> (u32, &str, &str) {
+    let (radix, body) = match literal.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        _ => (10, literal),
+    };
+    // `f` is a hexadecimal digit, so only decimal literals take float suffixes
+    let suffix_start = body
+        .find(|c: char| c == 'i' || (radix == 10 && c == 'f'))
+        .unwrap_or(body.len());
+
+    (radix, &body[..suffix_start], &body[suffix_start..])
+}
+
+// value of an integer literal, negative literals may reach `max + 1`
+fn integer_value(radix: u32, digits: &str, negative: bool, max: u64) -> Option<i64> {
+    let value = u64::from_str_radix(digits, radix).ok()?;
+
+    if negative && value <= max + 1 {
+        Some(-(value as i128) as i64)
+    } else if !negative && value <= max {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+fn out_of_range(content: &Token, type_name: &str) -> ZXError {
+    let range = match type_name {
+        "Int" => format!("{}..={}", i32::MIN, i32::MAX),
+        "Long" => format!("{}..={}", i64::MIN, i64::MAX),
+        "Float" => format!("{:e}..={:e}", f32::MIN, f32::MAX),
+        _ => format!("{:e}..={:e}", f64::MIN, f64::MAX),
+    };
+
+    ZXError::TypeError {
+        message: format!(
+            "literal out of range for `{}`, the range is `{}`",
+            type_name, range
+        ),
+        pos: content.pos.clone(),
+    }
+}
@@ -0,0 +1,523 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::checks::generic::accepts;
+use crate::{Checker, ZXTyped};
+use util::ast::{Expression, MatchArm, Pattern, Statement};
+use util::bytecode::{self, BytecodeType, BytecodeValue};
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{Scope, ScopeType, Scopes};
+use util::token::{Position, Token};
+
+impl Checker {
+    /// `match value { .. }`. Every arm has the type of the first one, the
+    /// patterns have to cover every value and an arm no value can reach is
+    /// reported.
+    pub(crate) fn match_expression(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        expression: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (match_keyword, value, arms, right_curly_brackets) = match expression {
+            Expression::Match {
+                match_keyword,
+                value,
+                arms,
+                right_curly_brackets,
+            } => (match_keyword, value, arms, right_curly_brackets),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a match".into(),
+                })
+            }
+        };
+        let (value_type, value_pos, value) =
+            self.auto_type(global_scopes, None, currently, *value)?;
+        let mut result_type: Option<ZXTyped> = None;
+        let mut rows: Vec<Vec<Space>> = vec![];
+        let mut checked = vec![];
+
+        for MatchArm {
+            pattern,
+            fat_arrow,
+            body,
+        } in arms
+        {
+            let pattern_pos = pattern_pos(&pattern);
+            // arms are told apart by where their pattern starts
+            let path = format!("{}$match@{}", self.path, pattern_pos.start);
+            let mut bindings = Scopes::new();
+            let (pattern, space) = self.pattern(
+                global_scopes,
+                currently,
+                &mut bindings,
+                &path,
+                pattern,
+                &value_type,
+            )?;
+            if useful(&rows, std::slice::from_ref(&space)).is_none() {
+                self.reposts.push(Report {
+                    level: Level::Warning,
+                    error: ZXError::Warning {
+                        message: String::from("unreachable pattern"),
+                        pos: pattern_pos,
+                    },
+                });
+            }
+            rows.push(vec![space]);
+
+            currently.push(bindings);
+            let body = self.arm_body(global_scopes, currently, path, *body, fat_arrow);
+            currently.pop();
+            let (arm_type, arm_pos, body) = body?;
+            match &result_type {
+                Some(result_type) if !accepts(result_type, &arm_type) => {
+                    return Err(ZXError::TypeError {
+                        message: format!(
+                            "`match` arms have incompatible types, expected `{}` found `{}`",
+                            result_type, arm_type
+                        ),
+                        pos: arm_pos,
+                    })
+                }
+                Some(_) => {}
+                None => result_type = Some(arm_type),
+            }
+            checked.push(bytecode::MatchArm { pattern, body });
+        }
+
+        // reported here so that the match still has the type of its arms
+        if let Some(missing) = useful(&rows, &[Space::Any]) {
+            self.reposts.push(Report {
+                level: Level::Error,
+                error: ZXError::TypeError {
+                    message: format!("non-exhaustive patterns: `{}` not covered", missing[0]),
+                    pos: value_pos.unwrap_or_else(|| match_keyword.pos.clone()),
+                },
+            });
+        }
+        let result_type = result_type.unwrap_or(ZXTyped::Void);
+
+        Ok((
+            result_type.clone(),
+            Some(match_keyword.pos.to(&right_curly_brackets.pos)),
+            value.map(|value| BytecodeType::Match {
+                value: Box::new(value),
+                value_type,
+                result_type,
+                arms: checked,
+            }),
+        ))
+    }
+
+    // `pattern` against a value of `value_type`, the names it binds are
+    // added to `bindings`
+    fn pattern(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        bindings: &mut Scopes,
+        path: &str,
+        pattern: Pattern,
+        value_type: &ZXTyped,
+    ) -> Result<(bytecode::Pattern, Space), ZXError> {
+        match pattern {
+            Pattern::Literal { kid, content } => {
+                let pos = content.pos.clone();
+                let value = Expression::Value {
+                    kid,
+                    content,
+                    next: Box::new(None),
+                };
+                let (literal_type, _, value) =
+                    self.auto_type(global_scopes, None, currently, value)?;
+                let value = value.ok_or_else(|| ZXError::InternalError {
+                    message: "a literal without a value".into(),
+                })?;
+                expect_type(value_type, &literal_type, pos)?;
+
+                Ok((
+                    bytecode::Pattern::Value {
+                        value: Box::new(value.clone()),
+                        value_type: literal_type,
+                    },
+                    Space::Constructor {
+                        name: literal_name(&value),
+                        siblings: vec![],
+                        fields: vec![],
+                    },
+                ))
+            }
+            Pattern::Bool { identifier } => {
+                let name = identifier.get_string()?;
+                expect_type(
+                    value_type,
+                    &ZXTyped::Bool { nullable: false },
+                    identifier.pos,
+                )?;
+
+                Ok((
+                    bytecode::Pattern::Value {
+                        value: Box::new(BytecodeType::bool_value(name == "true")),
+                        value_type: ZXTyped::Bool { nullable: false },
+                    },
+                    Space::Constructor {
+                        name,
+                        siblings: vec![(String::from("true"), 0), (String::from("false"), 0)],
+                        fields: vec![],
+                    },
+                ))
+            }
+            Pattern::Variant {
+                enum_name,
+                variant_name,
+                fields,
+            } => {
+                let pos = enum_name.pos.to(&variant_name.pos);
+                let scope = self.find_scope(global_scopes, currently, &enum_name)?;
+                let variants = match scope.scope_type {
+                    ScopeType::DefEnum { variants } => variants,
+                    _ => {
+                        return Err(ZXError::TypeError {
+                            message: format!("`{}` is not an enum", scope.name),
+                            pos: enum_name.pos,
+                        })
+                    }
+                };
+                expect_type(
+                    value_type,
+                    &ZXTyped::Other(scope.name.to_string()),
+                    pos.clone(),
+                )?;
+                let (variant, tag, payload) =
+                    find_variant(&scope.name.to_string(), &variants, &variant_name)?;
+                if fields.len() != payload.len() {
+                    return Err(ZXError::TypeError {
+                        message: format!(
+                            "this pattern has {} fields, but the variant `{}` has {} fields",
+                            fields.len(),
+                            variant,
+                            payload.len()
+                        ),
+                        pos,
+                    });
+                }
+
+                let mut field_patterns = vec![];
+                let mut field_spaces = vec![];
+                for (field, field_type) in fields.into_iter().zip(&payload) {
+                    let (pattern, space) =
+                        self.pattern(global_scopes, currently, bindings, path, field, field_type)?;
+                    field_patterns.push(pattern);
+                    field_spaces.push(space);
+                }
+
+                Ok((
+                    bytecode::Pattern::Variant {
+                        name: scope.name.to_string(),
+                        variant: variant_name.get_string()?,
+                        tag,
+                        fields: field_patterns,
+                    },
+                    Space::Constructor {
+                        name: variant,
+                        siblings: siblings(&scope.name.to_string(), &variants),
+                        fields: field_spaces,
+                    },
+                ))
+            }
+            Pattern::Binding { name } => {
+                let symbol = name.get_symbol()?;
+                if bindings.scopes.iter().any(|binding| binding.name == symbol) {
+                    return Err(ZXError::NameError {
+                        message: format!(
+                            "identifier `{}` is bound more than once in the same pattern",
+                            symbol
+                        ),
+                        pos: name.pos,
+                    });
+                }
+                let path = format!("{}${}", path, symbol);
+                bindings.add_scope(Scope {
+                    name: symbol,
+                    path: path.clone(),
+                    pos: name.pos,
+                    scope_type: ScopeType::DefVariable {
+                        var_type: value_type.clone(),
+                        value: None,
+                    },
+                    uses_num: 0,
+                    doc: None,
+                    public: false,
+                });
+
+                Ok((bytecode::Pattern::Binding { path: Some(path) }, Space::Any))
+            }
+            Pattern::Wildcard { .. } => Ok((bytecode::Pattern::Binding { path: None }, Space::Any)),
+        }
+    }
+
+    // the type, the position and the bytecodes of an arm, a block gives the
+    // value of its last statement
+    fn arm_body(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        path: String,
+        body: Statement,
+        fat_arrow: Token,
+    ) -> Result<(ZXTyped, Position, Vec<BytecodeType>), ZXError> {
+        match body {
+            Statement::Expression { expression } => {
+                let (arm_type, arm_pos, value) =
+                    self.auto_type(global_scopes, None, currently, expression)?;
+
+                Ok((
+                    arm_type,
+                    arm_pos.unwrap_or(fat_arrow.pos),
+                    value.into_iter().collect(),
+                ))
+            }
+            body => {
+                let block = self.declaration(body, global_scopes, path, currently)?;
+
+                match block.scope_type {
+                    ScopeType::Block { ret, bytecodes, .. } => {
+                        Ok((ret.0, ret.1.unwrap_or(block.pos), bytecodes))
+                    }
+                    _ => Err(ZXError::InternalError {
+                        message: "the body of a match arm is not a block".into(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+fn expect_type(expected: &ZXTyped, found: &ZXTyped, pos: Position) -> Result<(), ZXError> {
+    if accepts(expected, found) {
+        Ok(())
+    } else {
+        Err(ZXError::TypeError {
+            message: format!(
+                "mismatched types, expected `{}` found `{}`",
+                expected, found
+            ),
+            pos,
+        })
+    }
+}
+
+// `Shape::Circle` with its tag and the types of its fields
+fn find_variant(
+    enum_name: &str,
+    variants: &Scopes,
+    variant_name: &Token,
+) -> Result<(String, u32, Vec<ZXTyped>), ZXError> {
+    let name = variant_name.get_symbol()?;
+
+    match variants.scopes.iter().find(|variant| variant.name == name) {
+        Some(Scope {
+            scope_type: ScopeType::DefVariant { tag, payload },
+            ..
+        }) => Ok((format!("{}::{}", enum_name, name), *tag, payload.clone())),
+        _ => Err(ZXError::NameError {
+            message: format!("no variant named `{}` in `{}`", name, enum_name),
+            pos: variant_name.pos.clone(),
+        }),
+    }
+}
+
+// every variant of an enum with the number of its fields
+fn siblings(enum_name: &str, variants: &Scopes) -> Vec<(String, usize)> {
+    variants
+        .scopes
+        .iter()
+        .map(|variant| {
+            let fields = match &variant.scope_type {
+                ScopeType::DefVariant { payload, .. } => payload.len(),
+                _ => 0,
+            };
+
+            (format!("{}::{}", enum_name, variant.name), fields)
+        })
+        .collect()
+}
+
+// literals are told apart by their value, `0x10` is the same as `16`
+fn literal_name(value: &BytecodeType) -> String {
+    match value {
+        BytecodeType::Value { value } => match value {
+            BytecodeValue::Int { value } => value.to_string(),
+            BytecodeValue::Long { value } => value.to_string(),
+            BytecodeValue::Float { value } => value.to_string(),
+            BytecodeValue::Double { value } => value.to_string(),
+            BytecodeValue::Bool { value } => value.to_string(),
+            BytecodeValue::Char { value } => format!("{:?}", value),
+            BytecodeValue::String { value } => format!("{:?}", value),
+            value => format!("{:?}", value),
+        },
+        value => format!("{:?}", value),
+    }
+}
+
+fn pattern_pos(pattern: &Pattern) -> Position {
+    match pattern {
+        Pattern::Literal { content, .. } => content.pos.clone(),
+        Pattern::Bool { identifier } => identifier.pos.clone(),
+        Pattern::Variant {
+            enum_name,
+            variant_name,
+            fields,
+        } => enum_name.pos.to(&fields
+            .last()
+            .map_or_else(|| variant_name.pos.clone(), pattern_pos)),
+        Pattern::Binding { name } => name.pos.clone(),
+        Pattern::Wildcard { underscore } => underscore.pos.clone(),
+    }
+}
+
+/// The values a pattern matches as far as exhaustiveness goes, names and
+/// literal values do not matter
+#[derive(Clone, Debug)]
+enum Space {
+    /// Every value
+    Any,
+    /// A variant, `true`, `false` or a literal. `siblings` are every
+    /// constructor of the type with the number of their fields, empty when
+    /// there are too many to list like the values of an `Int`.
+    Constructor {
+        name: String,
+        siblings: Vec<(String, usize)>,
+        fields: Vec<Space>,
+    },
+}
+
+impl fmt::Display for Space {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Space::Any => write!(f, "_"),
+            Space::Constructor { name, fields, .. } if fields.is_empty() => write!(f, "{}", name),
+            Space::Constructor { name, fields, .. } => write!(
+                f,
+                "{}({})",
+                name,
+                fields
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+// A value `row` matches but none of `rows` do, `None` when every value of
+// `row` is matched already. The rows are the patterns of the arms before,
+// a pattern with fields is flattened into a row of them.
+fn useful(rows: &[Vec<Space>], row: &[Space]) -> Option<Vec<Space>> {
+    let (head, rest) = match row.split_first() {
+        Some(split) => split,
+        None if rows.is_empty() => return Some(vec![]),
+        None => return None,
+    };
+
+    match head {
+        Space::Constructor {
+            name,
+            siblings,
+            fields,
+        } => {
+            let row = fields.iter().chain(rest).cloned().collect::<Vec<Space>>();
+            let witness = useful(&specialize(rows, name, fields.len()), &row)?;
+
+            Some(rebuild(name, siblings, witness, fields.len()))
+        }
+        Space::Any => {
+            let seen = rows
+                .iter()
+                .filter_map(|row| match &row[0] {
+                    Space::Constructor { name, siblings, .. } => Some((name, siblings)),
+                    Space::Any => None,
+                })
+                .collect::<Vec<(&String, &Vec<(String, usize)>)>>();
+            let siblings = seen
+                .first()
+                .map(|(_, siblings)| (*siblings).clone())
+                .unwrap_or_default();
+            let is_seen = |sibling: &String| seen.iter().any(|(name, _)| *name == sibling);
+
+            // every constructor is in the column, the value has to be one of them
+            if !siblings.is_empty() && siblings.iter().all(|(name, _)| is_seen(name)) {
+                return siblings.iter().find_map(|(name, arity)| {
+                    let row = vec![Space::Any; *arity]
+                        .into_iter()
+                        .chain(rest.iter().cloned())
+                        .collect::<Vec<Space>>();
+                    let witness = useful(&specialize(rows, name, *arity), &row)?;
+
+                    Some(rebuild(name, &siblings, witness, *arity))
+                });
+            }
+
+            let default = rows
+                .iter()
+                .filter(|row| matches!(row[0], Space::Any))
+                .map(|row| row[1..].to_vec())
+                .collect::<Vec<Vec<Space>>>();
+            let witness = useful(&default, rest)?;
+            let missing = siblings.iter().find(|(name, _)| !is_seen(name)).map_or(
+                Space::Any,
+                |(name, arity)| Space::Constructor {
+                    name: name.clone(),
+                    siblings: siblings.clone(),
+                    fields: vec![Space::Any; *arity],
+                },
+            );
+
+            Some(std::iter::once(missing).chain(witness).collect())
+        }
+    }
+}
+
+// the rows that can match the constructor `name`, with its fields in place
+// of it
+fn specialize(rows: &[Vec<Space>], name: &str, arity: usize) -> Vec<Vec<Space>> {
+    rows.iter()
+        .filter_map(|row| match &row[0] {
+            Space::Constructor {
+                name: row_name,
+                fields,
+                ..
+            } if row_name == name => Some(fields.iter().chain(&row[1..]).cloned().collect()),
+            Space::Constructor { .. } => None,
+            Space::Any => Some(
+                vec![Space::Any; arity]
+                    .into_iter()
+                    .chain(row[1..].iter().cloned())
+                    .collect(),
+            ),
+        })
+        .collect()
+}
+
+// the first `arity` spaces of `witness` back into the constructor `name`
+fn rebuild(
+    name: &str,
+    siblings: &[(String, usize)],
+    witness: Vec<Space>,
+    arity: usize,
+) -> Vec<Space> {
+    let mut witness = witness;
+    let fields = witness.drain(..arity).collect();
+
+    std::iter::once(Space::Constructor {
+        name: name.to_string(),
+        siblings: siblings.to_vec(),
+        fields,
+    })
+    .chain(witness)
+    .collect()
+}
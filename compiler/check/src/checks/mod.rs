@@ -1,2 +1,13 @@
+mod array;
+mod assignment;
+mod class;
+mod closure;
 mod declaration;
+mod enums;
+pub(crate) mod generic;
+mod import;
+mod literal;
+mod matching;
+mod operator;
+mod range;
 mod statements;
@@ -0,0 +1,187 @@
+use crate::checks::generic::accepts;
+use crate::{Checker, ZXTyped};
+use std::mem::discriminant;
+use util::ast::{Expression, Operator, UnaryOperator};
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::Scopes;
+use util::token::{Position, Token};
+
+impl Checker {
+    /// Type check `left operator right`, both sides must have the same type
+    pub(crate) fn operator(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        operator: Operator,
+        left: Expression,
+        right: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let left = self.auto_type(global_scopes, None, currently, left)?;
+        let right = self.auto_type(global_scopes, None, currently, right)?;
+
+        self.binary(operator, left, right)
+    }
+
+    /// Type check `operator` on two operands that were already checked
+    pub(crate) fn binary(
+        &mut self,
+        operator: Operator,
+        (left_type, left_pos, left_value): (ZXTyped, Option<Position>, Option<BytecodeType>),
+        (right_type, right_pos, right_value): (ZXTyped, Option<Position>, Option<BytecodeType>),
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let pos = match (&left_pos, &right_pos) {
+            (Some(left), Some(right)) => Some(left.to(right)),
+            _ => left_pos.or(right_pos),
+        };
+        let type_error = |message: String| match &pos {
+            Some(pos) => ZXError::TypeError {
+                message,
+                pos: pos.clone(),
+            },
+            None => ZXError::UnknownError { message },
+        };
+        let value = |value: Option<BytecodeType>| {
+            value.map(Box::new).ok_or(ZXError::InternalError {
+                message: "operand has no value".into(),
+            })
+        };
+
+        if !same_type(&left_type, &right_type) {
+            return Err(type_error(format!(
+                "cannot apply `{}` to `{}` and `{}`",
+                operator, left_type, right_type
+            )));
+        }
+
+        let result_type = match operator {
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Mod
+                if is_number(&left_type) =>
+            {
+                left_type.clone()
+            }
+            Operator::Equal | Operator::NotEqual if left_type != ZXTyped::Void => {
+                ZXTyped::Bool { nullable: false }
+            }
+            Operator::Less | Operator::LessEqual | Operator::More | Operator::MoreEqual
+                if is_number(&left_type) || matches!(left_type, ZXTyped::Char { .. }) =>
+            {
+                ZXTyped::Bool { nullable: false }
+            }
+            Operator::And | Operator::Or if matches!(left_type, ZXTyped::Bool { .. }) => {
+                return Ok((
+                    left_type,
+                    pos,
+                    Some(BytecodeType::Logical {
+                        operator,
+                        left: value(left_value)?,
+                        right: value(right_value)?,
+                    }),
+                ));
+            }
+            _ => {
+                return Err(type_error(format!(
+                    "cannot apply `{}` to `{}`",
+                    operator, left_type
+                )))
+            }
+        };
+
+        Ok((
+            result_type,
+            pos,
+            Some(BytecodeType::Binary {
+                operator,
+                operand_type: left_type,
+                left: value(left_value)?,
+                right: value(right_value)?,
+            }),
+        ))
+    }
+
+    /// `-` takes a number, `!` a `Bool` and `~` an integer
+    pub(crate) fn unary(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        operator_type: UnaryOperator,
+        operator: Token,
+        expression: Expression,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (value_type, value_pos, value) =
+            self.auto_type(global_scopes, None, currently, expression)?;
+        let pos = match &value_pos {
+            Some(value_pos) => operator.pos.to(value_pos),
+            None => operator.pos,
+        };
+        let accepted = match operator_type {
+            UnaryOperator::Neg => is_number(&value_type),
+            UnaryOperator::Not => matches!(value_type, ZXTyped::Bool { .. }),
+            UnaryOperator::BitNot => {
+                matches!(value_type, ZXTyped::Integer { .. } | ZXTyped::Long { .. })
+            }
+        };
+
+        if !accepted {
+            return Err(ZXError::TypeError {
+                message: format!("cannot apply `{}` to `{}`", operator_type, value_type),
+                pos,
+            });
+        }
+
+        let value = value.ok_or(ZXError::InternalError {
+            message: "operand has no value".into(),
+        })?;
+
+        Ok((
+            value_type.clone(),
+            Some(pos),
+            Some(BytecodeType::Unary {
+                operator: operator_type,
+                operand_type: value_type,
+                value: Box::new(value),
+            }),
+        ))
+    }
+}
+
+// nullability does not change what an operator accepts, the elements of `[]`
+// go with any other array
+pub(super) fn same_type(left: &ZXTyped, right: &ZXTyped) -> bool {
+    match (left, right) {
+        (ZXTyped::Other(left), ZXTyped::Other(right)) => left == right,
+        (ZXTyped::Param(left), ZXTyped::Param(right)) => left == right,
+        (ZXTyped::Generic { .. }, ZXTyped::Generic { .. }) => accepts(left, right),
+        (ZXTyped::Array { element: left, .. }, ZXTyped::Array { element: right, .. }) => {
+            **left == ZXTyped::Void || **right == ZXTyped::Void || same_type(left, right)
+        }
+        (
+            ZXTyped::Function {
+                parameters,
+                return_type,
+            },
+            ZXTyped::Function {
+                parameters: right_parameters,
+                return_type: right_return_type,
+            },
+        ) => {
+            parameters.len() == right_parameters.len()
+                && parameters
+                    .iter()
+                    .zip(right_parameters)
+                    .all(|(left, right)| same_type(left, right))
+                && same_type(return_type, right_return_type)
+        }
+        _ => discriminant(left) == discriminant(right),
+    }
+}
+
+fn is_number(value_type: &ZXTyped) -> bool {
+    matches!(
+        value_type,
+        ZXTyped::Integer { .. }
+            | ZXTyped::Long { .. }
+            | ZXTyped::Float { .. }
+            | ZXTyped::Double { .. }
+    )
+}
@@ -0,0 +1,62 @@
+use crate::checks::operator::same_type;
+use crate::{Checker, ZXTyped};
+use util::ast::Expression;
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::Scopes;
+use util::token::{Position, Token};
+
+impl Checker {
+    /// `start..end` or `start..=end`, both ends are an `Int` or both a
+    /// `Long`
+    pub(crate) fn range(
+        &mut self,
+        global_scopes: &mut Scopes,
+        currently: &mut Vec<Scopes>,
+        start: Expression,
+        range_operator: Token,
+        end: Expression,
+        inclusive: bool,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (start_type, start_pos, start) =
+            self.auto_type(global_scopes, None, currently, start)?;
+        let (end_type, end_pos, end) = self.auto_type(global_scopes, None, currently, end)?;
+        let start_pos = start_pos.unwrap_or_else(|| range_operator.pos.clone());
+        let end_pos = end_pos.unwrap_or_else(|| range_operator.pos.clone());
+
+        if !matches!(start_type, ZXTyped::Integer { .. } | ZXTyped::Long { .. }) {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "range bounds must be `Int` or `Long`, found `{}`",
+                    start_type
+                ),
+                pos: start_pos,
+            });
+        }
+        if !same_type(&start_type, &end_type) {
+            return Err(ZXError::TypeError {
+                message: format!(
+                    "mismatched types in the range, expected `{}` found `{}`",
+                    start_type, end_type
+                ),
+                pos: end_pos,
+            });
+        }
+
+        Ok((
+            ZXTyped::Range {
+                element: Box::new(start_type.clone()),
+            },
+            Some(start_pos.to(&end_pos)),
+            match (start, end) {
+                (Some(start), Some(end)) => Some(BytecodeType::Range {
+                    element_type: start_type,
+                    start: Box::new(start),
+                    end: Box::new(end),
+                    inclusive,
+                }),
+                _ => None,
+            },
+        ))
+    }
+}
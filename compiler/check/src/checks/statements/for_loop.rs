@@ -0,0 +1,107 @@
+use crate::checks::generic::is_uninferred;
+use crate::{Checker, ZXTyped};
+use util::ast::Statement;
+use util::bytecode::BytecodeType;
+use util::error::ZXError;
+use util::scope::{Scope, ScopeType, Scopes};
+use util::token::Position;
+
+impl Checker {
+    /// `for x in iterable { .. }`, `x` is an `Int` or a `Long` of a range,
+    /// an element of an array or a `Char` of a `Str` and is only visible in
+    /// the block
+    pub(crate) fn for_loop(
+        &mut self,
+        statement: Statement,
+        scopes: &mut Scopes,
+        children: &mut Vec<Scopes>,
+        path: String,
+    ) -> Result<(ZXTyped, Option<Position>, Option<BytecodeType>), ZXError> {
+        let (for_var_name, for_in_keyword, iter, block) = match statement {
+            Statement::ForLoop {
+                for_var_name,
+                for_in_keyword,
+                iter,
+                block,
+                ..
+            } => (for_var_name, for_in_keyword, iter, block),
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "not a for loop".into(),
+                })
+            }
+        };
+        let expression = match *iter {
+            Statement::Expression { expression } => expression,
+            _ => {
+                return Err(ZXError::SyntaxError {
+                    message: String::from("expected an iterable after `in`"),
+                    pos: for_in_keyword.pos,
+                })
+            }
+        };
+        let (iterable_type, iterable_pos, iterable) =
+            self.auto_type(scopes, None, children, expression)?;
+        let iterable_pos = iterable_pos.unwrap_or(for_in_keyword.pos);
+        let element_type = match &iterable_type {
+            ZXTyped::Range { element } => element.as_ref().clone(),
+            // `for x in []`
+            array @ ZXTyped::Array { .. } if is_uninferred(array) => {
+                return Err(ZXError::TypeError {
+                    message: format!("type annotations needed for `{}`", array),
+                    pos: iterable_pos,
+                })
+            }
+            ZXTyped::Array { element, .. } => element.as_ref().clone(),
+            ZXTyped::String { .. } => ZXTyped::Char { nullable: false },
+            iterable_type => {
+                return Err(ZXError::TypeError {
+                    message: format!("cannot iterate over a value of type `{}`", iterable_type),
+                    pos: iterable_pos,
+                })
+            }
+        };
+
+        let name = for_var_name.get_symbol()?;
+        let variable = Scope {
+            name,
+            path: format!("{}${}", path, name),
+            pos: for_var_name.pos,
+            scope_type: ScopeType::DefVariable {
+                var_type: element_type.clone(),
+                value: None,
+            },
+            uses_num: 0,
+            doc: None,
+            public: false,
+        };
+        let mut frame = Scopes::new();
+        frame.add_scope(variable.clone());
+        children.push(frame);
+        let body = self.declaration(*block, scopes, path, children);
+        children.pop();
+        let body = match body?.scope_type {
+            ScopeType::Block { bytecodes, .. } => bytecodes,
+            _ => {
+                return Err(ZXError::InternalError {
+                    message: "the body of a for loop is not a block".into(),
+                })
+            }
+        };
+
+        Ok((
+            ZXTyped::Void,
+            None,
+            iterable.map(|iterable| BytecodeType::ForLoop {
+                variable: variable.path,
+                element_type,
+                iterable: Box::new(iterable),
+                iterable_type,
+                body: Box::new(BytecodeType::Block {
+                    name: String::from("for_body"),
+                    bytecodes: body,
+                }),
+            }),
+        ))
+    }
+}
@@ -1 +1 @@
-
+mod for_loop;
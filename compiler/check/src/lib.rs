@@ -2,6 +2,7 @@ mod checks;
 mod test;
 
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 
 use util::ast::Expression::*;
 use util::ast::Statement::*;
@@ -11,13 +12,20 @@ use util::error::ZXError;
 use util::report::Level::Error;
 use util::report::{Level, Report};
 use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
 use util::token::Tokens::IdentifierToken;
 use util::token::{Literal, Position, Token};
 use util::zx_type::ZXTyped;
 
+use crate::checks::generic::accepts;
+
 pub struct Checker {
     ast: Vec<Statement>,
     pub reposts: Vec<Report>,
+    // path of the module being checked, every declaration path starts with it
+    path: String,
+    // top level scopes of the modules this file can import
+    modules: HashMap<Vec<Symbol>, Scopes>,
 }
 
 impl Checker {
@@ -25,16 +33,37 @@ impl Checker {
         Checker {
             ast,
             reposts: vec![],
+            path: String::new(),
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Check the module `math::vector`, declarations are put under
+    /// `$math$vector`
+    pub fn module(ast: Vec<Statement>, path: &[Symbol]) -> Checker {
+        Checker {
+            path: path.iter().map(|name| format!("${}", name)).collect(),
+            ..Checker::new(ast)
         }
     }
 
+    /// Make the top level scopes of a checked module importable as `path`
+    pub fn add_module(&mut self, path: Vec<Symbol>, scopes: Scopes) {
+        self.modules.insert(path, scopes);
+    }
+
     pub fn check(&mut self) -> Scopes {
         let mut scopes = Scopes::new();
         for statement in self.ast.clone() {
+            if let Import { path, kind, .. } = statement {
+                self.import(&mut scopes, path, kind);
+                continue;
+            }
+
             match self.declaration(
                 statement,
                 &mut scopes,
-                String::new(),
+                self.path.clone(),
                 vec![Scopes::new()].borrow_mut(),
             ) {
                 Ok(declaration) => scopes.add_scope(declaration),
@@ -69,27 +98,38 @@ impl Checker {
             Statement::Expression { expression } => {
                 self.auto_type(scopes, None, children, expression)?
             }
+            // already reported by the parser
+            Statement::Error { .. } => (ZXTyped::Void, None, None),
+            Import { im_keyword, .. } => {
+                return Err(ZXError::SyntaxError {
+                    message: String::from("`im` is only allowed at the top level of a file"),
+                    pos: im_keyword.pos,
+                })
+            }
             Statement::VariableDeclaration { .. } => {
-                let scope = self.declaration(statement, scopes, path, vec![].borrow_mut())?;
+                let scope = self.declaration(statement, scopes, path, children)?;
                 children.last_mut().unwrap().add_scope(scope.clone());
                 let value = if let ScopeType::DefVariable { value, .. } = scope.scope_type {
-                    if let BytecodeType::Value { value } = value.unwrap() {
-                        Some(value)
-                    } else {
-                        None
-                    }
+                    value
                 } else {
                     None
                 };
                 (
                     ZXTyped::Void,
                     None,
-                    Some(BytecodeType::Store {
+                    value.map(|value| BytecodeType::Store {
                         path: scope.path,
-                        value: value.unwrap(),
+                        value: Box::new(value),
                     }),
                 )
             }
+            Assignment {
+                target,
+                operator_type,
+                value,
+                ..
+            } => self.assignment(scopes, children, target, operator_type, value)?,
+            ForLoop { .. } => self.for_loop(statement, scopes, children, path)?,
             _ => {
                 self.declaration(statement, scopes, path, children)?;
                 (ZXTyped::Void, None, None)
@@ -98,7 +138,7 @@ impl Checker {
     }
 
     fn auto_type(
-        &self,
+        &mut self,
         global_scopes: &mut Scopes,
         sub_scopes: Option<&mut Scopes>,
         currently: &mut Vec<Scopes>,
@@ -107,34 +147,19 @@ impl Checker {
         match expression {
             Value { kid, content, .. } => {
                 // value type
-                Ok((
-                    match kid {
-                        Literal::String => ZXTyped::String { nullable: false },
-                        Literal::Char => ZXTyped::Char { nullable: false },
-                        Literal::PositiveInteger | Literal::NegativeInteger => {
-                            ZXTyped::Integer { nullable: false }
-                        }
-                        Literal::Float => ZXTyped::Float { nullable: false },
-                    },
-                    Some(content.pos.clone()),
-                    Some(match kid {
-                        Literal::String => BytecodeType::string_value(
-                            content.get_string()?.parse::<String>().unwrap(),
-                        ),
-                        Literal::PositiveInteger => {
-                            BytecodeType::int_value(content.get_string()?.parse::<i32>().unwrap())
-                        }
-                        Literal::Float => {
-                            BytecodeType::float_value(content.get_string()?.parse::<f32>().unwrap())
-                        }
-                        Literal::Char => {
-                            BytecodeType::char_value(content.get_string()?.parse::<char>().unwrap())
-                        }
-                        Literal::NegativeInteger => {
-                            BytecodeType::int_value(content.get_string()?.parse::<i32>().unwrap())
-                        }
-                    }),
-                ))
+                let (value_type, value) = match kid {
+                    Literal::String => (
+                        ZXTyped::String { nullable: false },
+                        BytecodeType::string_value(content.get_string()?),
+                    ),
+                    Literal::Char => (
+                        ZXTyped::Char { nullable: false },
+                        BytecodeType::char_value(content.get_string()?.parse::<char>().unwrap()),
+                    ),
+                    _ => self.number_value(&kid, &content)?,
+                };
+
+                Ok((value_type, Some(content.pos.clone()), Some(value)))
             }
             Call {
                 call_name,
@@ -144,135 +169,183 @@ impl Checker {
                 arguments,
                 ..
             } => {
-                let scope = self.find_scope(
-                    if let Some(scopes) = sub_scopes {
-                        scopes
-                    } else {
-                        global_scopes
+                let scope = match sub_scopes {
+                    Some(members) => self.find_member(members, &call_name)?,
+                    None => match self.find_scope(global_scopes, currently, &call_name) {
+                        Err(ZXError::NameError { .. }) if call_name.get_string()? == "len" => {
+                            return self.len(
+                                global_scopes,
+                                currently,
+                                call_name,
+                                arguments,
+                                right_parentheses,
+                            )
+                        }
+                        scope => scope?,
                     },
-                    currently,
-                    &call_name,
-                )?;
+                };
 
-                match scope.scope_type {
+                match scope.scope_type.clone() {
                     ScopeType::DefFunction {
+                        type_parameters,
                         parameters,
                         return_type,
                         ..
                     } => {
-                        if arguments.len() == parameters.len() {
-                            for index in 0..arguments.len() {
-                                let arg_scope = self.auto_type(
-                                    global_scopes,
-                                    None,
-                                    currently,
-                                    arguments[index].clone(),
-                                )?;
-                                let parameter_type =
-                                    if let ScopeType::DefVariable { var_type, .. } =
-                                        &parameters[index].scope_type
-                                    {
-                                        Ok(var_type)
-                                    } else {
-                                        Err(ZXError::InternalError { message: "".into() })
-                                    }?;
-
-                                if !arg_scope.0.eq(parameter_type) {
-                                    return Err(ZXError::TypeError {
-                                        message: format!("mismatched types"),
-                                        pos: arg_scope.1.unwrap(),
-                                    });
-                                }
-                            }
-                        } else {
+                        if arguments.len() != parameters.len() {
                             return Err(ZXError::TypeError {
                                 message: format!(
                                     "this function takes {} argument but {} arguments were supplied",
                                     parameters.len(),
                                     arguments.len()),
-                                pos: Position {
-                                    start: left_parentheses.pos.start,
-                                    end: right_parentheses.pos.end + 1,
-                                },
+                                pos: left_parentheses.pos.to(&right_parentheses.pos),
                             });
                         }
-                        Ok((
-                            if let Some(next) = next {
-                                let scope = match self.find_scope_str(
-                                    global_scopes,
-                                    currently,
-                                    &return_type.to_string(),
-                                    call_name.pos.clone(),
-                                ) {
-                                    Ok(scope) => Ok(scope),
-                                    _ => Err(ZXError::TypeError { message: format!(
-                                        "member reference base type '{}' is not a structure or union",
-                                        return_type.to_string()
-                                    ), pos: call_name.pos.clone() })
-                                }?;
-
-                                if let ScopeType::DefClass { members } = scope.scope_type {
-                                    self.auto_type(
-                                        global_scopes,
-                                        Some(&mut members.clone()),
-                                        currently,
-                                        *next,
-                                    )?
-                                    .0
+                        let parameter_types = parameters
+                            .iter()
+                            .map(|parameter| {
+                                if let ScopeType::DefVariable { var_type, .. } =
+                                    &parameter.scope_type
+                                {
+                                    Ok(var_type.clone())
                                 } else {
-                                    return Err(ZXError::UnknownError {
-                                        message: String::new(),
+                                    Err(ZXError::InternalError { message: "".into() })
+                                }
+                            })
+                            .collect::<Result<Vec<ZXTyped>, ZXError>>()?;
+                        let arguments = arguments
+                            .into_iter()
+                            .map(|argument| {
+                                let arg_scope =
+                                    self.auto_type(global_scopes, None, currently, argument)?;
+                                let value = arg_scope.2.ok_or(ZXError::InternalError {
+                                    message: "argument has no value".into(),
+                                })?;
+                                Ok(((arg_scope.0, arg_scope.1), value))
+                            })
+                            .collect::<Result<Vec<_>, ZXError>>()?;
+                        let (argument_types, argument_values): (
+                            Vec<(ZXTyped, Option<Position>)>,
+                            Vec<BytecodeType>,
+                        ) = arguments.into_iter().unzip();
+                        let type_arguments = if type_parameters.is_empty() {
+                            for (parameter_type, arg_scope) in
+                                parameter_types.iter().zip(&argument_types)
+                            {
+                                if !accepts(parameter_type, &arg_scope.0) {
+                                    return Err(ZXError::TypeError {
+                                        message: format!(
+                                            "mismatched types, expected `{}` found `{}`",
+                                            parameter_type, arg_scope.0
+                                        ),
+                                        pos: arg_scope.1.clone().unwrap_or_else(|| {
+                                            left_parentheses.pos.to(&right_parentheses.pos)
+                                        }),
                                     });
                                 }
-                            } else {
-                                return_type.clone()
-                            },
-                            Some(Position {
-                                start: call_name.pos.start,
-                                end: right_parentheses.pos.end,
-                            }),
-                            Some(BytecodeType::Call {
-                                path: scope.path,
-                                ret_type: return_type,
-                                argument_types: parameters
-                                    .iter()
-                                    .map(|parameter| {
-                                        if let ScopeType::DefVariable { var_type, .. } =
-                                            &parameter.scope_type
-                                        {
-                                            Ok(var_type.clone())
-                                        } else {
-                                            Err(())
-                                        }
-                                    })
-                                    .collect::<Result<Vec<ZXTyped>, ()>>()
-                                    .unwrap(),
-                            }),
-                        ))
-                    }
-                    ScopeType::DefClass { members } => {
-                        let return_type = if let Some(next) = next {
-                            let ret = self
-                                .auto_type(
+                            }
+                            vec![]
+                        } else {
+                            // `max(1, 2)` calls `max<Int>`
+                            self.infer_type_arguments(
+                                &scope,
+                                &type_parameters,
+                                &parameter_types,
+                                &argument_types,
+                                &call_name.pos.to(&right_parentheses.pos),
+                            )?
+                        };
+                        let parameter_types = parameter_types
+                            .iter()
+                            .map(|parameter_type| {
+                                parameter_type.substitute(&type_parameters, &type_arguments)
+                            })
+                            .collect::<Vec<ZXTyped>>();
+                        let return_type = return_type.substitute(&type_parameters, &type_arguments);
+                        let pos = Some(call_name.pos.to(&right_parentheses.pos));
+                        let call = BytecodeType::Call {
+                            path: scope.path,
+                            ret_type: return_type.clone(),
+                            argument_types: parameter_types,
+                            type_arguments,
+                            arguments: argument_values,
+                        };
+
+                        match next {
+                            Some(next) => {
+                                let class = self.object_class(
                                     global_scopes,
-                                    Some(&mut members.clone()),
                                     currently,
+                                    &return_type,
+                                    call_name.pos.clone(),
+                                )?;
+                                let (member_type, value) = self.member_access(
+                                    global_scopes,
+                                    currently,
+                                    Some(call),
+                                    class,
                                     *next,
-                                )?
-                                .0;
-                            ret
+                                    false,
+                                )?;
+
+                                Ok((member_type, pos, value))
+                            }
+                            None => Ok((return_type, pos, Some(call))),
+                        }
+                    }
+                    ScopeType::DefClass {
+                        ref type_parameters,
+                        ..
+                    } => {
+                        let pos = Some(call_name.pos.to(&right_parentheses.pos));
+                        // the type arguments of a generic class come from the
+                        // type it is used as, only its instances are built
+                        let (class_type, object) = if type_parameters.is_empty() {
+                            (
+                                ZXTyped::Other(scope.name.to_string()),
+                                Some(BytecodeType::New {
+                                    name: scope.name.to_string(),
+                                    path: scope.path.clone(),
+                                }),
+                            )
                         } else {
-                            ZXTyped::Other(scope.name)
+                            (
+                                ZXTyped::Generic {
+                                    name: scope.name.to_string(),
+                                    arguments: vec![],
+                                },
+                                None,
+                            )
                         };
-                        Ok((
-                            return_type,
-                            Option::from(Position {
-                                start: call_name.pos.start,
-                                end: right_parentheses.pos.end,
-                            }),
-                            None,
-                        ))
+
+                        match next {
+                            Some(next) => {
+                                let (member_type, value) = self.member_access(
+                                    global_scopes,
+                                    currently,
+                                    object,
+                                    scope,
+                                    *next,
+                                    false,
+                                )?;
+
+                                Ok((member_type, pos, value))
+                            }
+                            None => Ok((class_type, pos, object)),
+                        }
                     }
+                    ScopeType::DefVariable { .. } => self.call_value(
+                        global_scopes,
+                        currently,
+                        scope,
+                        Call {
+                            call_name,
+                            left_parentheses,
+                            arguments,
+                            right_parentheses,
+                            next,
+                        },
+                    ),
                     _ => Err(ZXError::NameError {
                         message: format!("NameError: name '{}' is not defined", scope.name),
                         pos: call_name.pos,
@@ -281,79 +354,217 @@ impl Checker {
             }
             Type {
                 identifier,
+                arguments,
                 nullable,
             } => {
                 if let IdentifierToken { literal } = &identifier.token_type {
-                    Ok((
-                        match literal.as_ref() {
-                            "Int" => ZXTyped::Integer { nullable },
-                            "Float" => ZXTyped::Float { nullable },
-                            "Str" => ZXTyped::String { nullable },
-                            "Char" => ZXTyped::Char { nullable },
-                            "Void" => ZXTyped::Void,
-                            _ => {
-                                let scope =
-                                    self.find_scope(global_scopes, currently, &identifier)?;
+                    let type_arguments = arguments
+                        .into_iter()
+                        .map(|argument| {
+                            Ok(self.auto_type(global_scopes, None, currently, argument)?.0)
+                        })
+                        .collect::<Result<Vec<ZXTyped>, ZXError>>()?;
+                    let zx_type = match literal.as_str() {
+                        "Int" => ZXTyped::Integer { nullable },
+                        "Long" => ZXTyped::Long { nullable },
+                        "Float" => ZXTyped::Float { nullable },
+                        "Double" => ZXTyped::Double { nullable },
+                        "Str" => ZXTyped::String { nullable },
+                        "Char" => ZXTyped::Char { nullable },
+                        "Bool" => ZXTyped::Bool { nullable },
+                        "Void" => ZXTyped::Void,
+                        _ => {
+                            let scope = self.find_scope(global_scopes, currently, &identifier)?;
 
-                                if let ScopeType::DefClass { .. } = &scope.scope_type {
-                                    ZXTyped::Other(scope.name)
-                                } else {
+                            match &scope.scope_type {
+                                ScopeType::DefClass {
+                                    type_parameters, ..
+                                } => {
+                                    if type_parameters.len() != type_arguments.len() {
+                                        return Err(ZXError::TypeError {
+                                            message: format!(
+                                                "class `{}` takes {} type arguments but {} were supplied",
+                                                literal,
+                                                type_parameters.len(),
+                                                type_arguments.len()
+                                            ),
+                                            pos: identifier.pos.clone(),
+                                        });
+                                    }
+
+                                    if type_parameters.is_empty() {
+                                        ZXTyped::Other(scope.name.to_string())
+                                    } else {
+                                        ZXTyped::Generic {
+                                            name: scope.name.to_string(),
+                                            arguments: type_arguments.clone(),
+                                        }
+                                    }
+                                }
+                                ScopeType::DefEnum { .. } => ZXTyped::Other(scope.name.to_string()),
+                                ScopeType::DefTypeParameter => {
+                                    ZXTyped::Param(scope.name.to_string())
+                                }
+                                _ => {
                                     return Err(ZXError::TypeError {
                                         message: format!("type `{}` not found", literal),
                                         pos: identifier.pos.clone(),
                                     });
                                 }
                             }
-                        },
-                        Some(identifier.pos),
-                        None,
-                    ))
+                        }
+                    };
+
+                    if !type_arguments.is_empty() && !matches!(zx_type, ZXTyped::Generic { .. }) {
+                        return Err(ZXError::TypeError {
+                            message: format!("type `{}` takes no type arguments", literal),
+                            pos: identifier.pos,
+                        });
+                    }
+
+                    Ok((zx_type, Some(identifier.pos), None))
                 } else {
                     Err(ZXError::UnknownError {
                         message: "".to_string(),
                     })
                 }
             }
+            StringTemplate {
+                left_quote,
+                parts,
+                right_quote,
+            } => self.string_template(global_scopes, currently, left_quote, parts, right_quote),
             // Path { identifier, next } => {
             // TODO: path end type
             // },
             SubMember { sub_member } => {
                 self.auto_type(global_scopes, sub_scopes, currently, *sub_member)
             }
+            Bool { identifier } => Ok((
+                ZXTyped::Bool { nullable: false },
+                Some(identifier.pos.clone()),
+                Some(BytecodeType::bool_value(identifier.get_string()? == "true")),
+            )),
+            Brackets { content } => self.auto_type(global_scopes, sub_scopes, currently, *content),
+            ArrayType {
+                element, nullable, ..
+            } => {
+                let (element_type, element_pos, _) =
+                    self.auto_type(global_scopes, None, currently, *element)?;
+
+                Ok((
+                    ZXTyped::Array {
+                        element: Box::new(element_type),
+                        nullable,
+                    },
+                    element_pos,
+                    None,
+                ))
+            }
+            Array {
+                left_square_brackets,
+                elements,
+                right_square_brackets,
+            } => self.array(
+                global_scopes,
+                currently,
+                left_square_brackets,
+                elements,
+                right_square_brackets,
+            ),
+            Index { .. } => self.index(global_scopes, sub_scopes, currently, expression),
+            Range {
+                start,
+                range_operator,
+                end,
+                inclusive,
+            } => self.range(
+                global_scopes,
+                currently,
+                *start,
+                range_operator,
+                *end,
+                inclusive,
+            ),
+            FunctionType {
+                left_parentheses,
+                parameters,
+                return_type,
+                ..
+            } => self.function_type(
+                global_scopes,
+                currently,
+                left_parentheses,
+                parameters,
+                *return_type,
+            ),
+            Function { .. } => self.closure(global_scopes, currently, expression),
+            Match { .. } => self.match_expression(global_scopes, currently, expression),
+            Operator {
+                operator_type,
+                left,
+                right,
+            } => self.operator(global_scopes, currently, operator_type, *left, *right),
+            Unary {
+                operator_type,
+                operator,
+                expression,
+            } => self.unary(
+                global_scopes,
+                currently,
+                operator_type,
+                operator,
+                *expression,
+            ),
             Identifier { identifier, next } => {
-                let scope = self.find_scope(global_scopes, currently, &identifier)?;
+                let scope = match sub_scopes {
+                    Some(members) => self.find_member(members, &identifier)?,
+                    None => self.find_scope(global_scopes, currently, &identifier)?,
+                };
 
                 match scope.scope_type {
                     ScopeType::DefVariable { var_type, .. } => {
-                        let var_type = if let Some(next) = next {
-                            let scope = self.find_scope_str(
-                                global_scopes,
-                                currently,
-                                &var_type.to_string(),
-                                identifier.pos.clone(),
-                            )?;
+                        let object = BytecodeType::Load { path: scope.path };
 
-                            if let ScopeType::DefClass { .. } = scope.scope_type {
-                                self.auto_type(global_scopes, sub_scopes, currently, *next)?
-                                    .0
-                            } else {
-                                return Err(ZXError::UnknownError {
-                                    message: String::new(),
-                                });
+                        match next {
+                            Some(next) => {
+                                let class = self.class_scope(
+                                    global_scopes,
+                                    currently,
+                                    &var_type,
+                                    identifier.pos.clone(),
+                                )?;
+                                // `super.speak()` is the method of the base class
+                                let direct = scope.name.as_str() == "super";
+                                let (member_type, value) = self.member_access(
+                                    global_scopes,
+                                    currently,
+                                    Some(object),
+                                    class,
+                                    *next,
+                                    direct,
+                                )?;
+
+                                Ok((member_type, Some(identifier.pos), value))
                             }
-                        } else {
-                            var_type
-                        };
-                        Ok((
-                            var_type,
-                            Some(identifier.pos),
-                            Some(BytecodeType::Load { path: scope.path }),
-                        ))
+                            None => Ok((var_type, Some(identifier.pos), Some(object))),
+                        }
                     }
-                    ScopeType::DefFunction { .. } => Err(ZXError::TypeError {
-                        message: format!("`{}` is Function not a variable", scope.name),
-                        pos: identifier.pos,
-                    }),
+                    ScopeType::DefFunction { .. } => self.function_value(scope, identifier, next),
+                    // `Shape::Circle(1.0)`
+                    ScopeType::DefEnum { .. } => {
+                        self.variant_value(global_scopes, currently, scope, identifier, next)
+                    }
+                    // `math::PI` or `math::pow(2, 3)`
+                    ScopeType::DefModule { mut members } => match next.map(|next| *next) {
+                        Some(Path { next }) => {
+                            self.auto_type(global_scopes, Some(&mut members), currently, *next)
+                        }
+                        _ => Err(ZXError::TypeError {
+                            message: format!("expected a value, found module `{}`", scope.name),
+                            pos: identifier.pos,
+                        }),
+                    },
                     _ => Err(ZXError::UnknownError {
                         message: "".to_string(),
                     }),
@@ -371,14 +582,24 @@ impl Checker {
         currently: &mut Vec<Scopes>,
         name: &Token,
     ) -> Result<Scope, ZXError> {
-        self.find_scope_str(scopes, currently, &name.get_string()?, name.pos.clone())
+        self.find_scope_str(scopes, currently, name.get_symbol()?, name.pos.clone())
+    }
+
+    // members of a class or a module are never shadowed by local variables
+    fn find_member(&self, members: &mut Scopes, name: &Token) -> Result<Scope, ZXError> {
+        let symbol = name.get_symbol()?;
+
+        members.find_scope(symbol).ok_or(ZXError::NameError {
+            message: format!("no member named `{}`", symbol),
+            pos: name.pos.clone(),
+        })
     }
 
     fn find_scope_str(
         &self,
         scopes: &mut Scopes,
         currently: &mut Vec<Scopes>,
-        name: &String,
+        name: Symbol,
         pos: Position,
     ) -> Result<Scope, ZXError> {
         for currently_scopes in currently.iter_mut().rev() {
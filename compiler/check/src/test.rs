@@ -1,7 +1,903 @@
 #[cfg(test)]
 mod test {
     use crate::Checker;
+    use lexer::Lexer;
+    use parser::Parser;
+    use util::ast::Statement;
+    use util::bytecode::{self, BytecodeType};
+    use util::error::ZXError;
+    use util::scope::{ScopeType, Scopes};
+    use util::source_map::{FileId, SourceMap};
+    use util::symbol::Symbol;
+    use util::zx_type::ZXTyped;
+
+    // the statements of `source`, it has to parse without errors
+    fn parse(source: &str) -> Vec<Statement> {
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        parser.parse(&SourceMap::new());
+        assert!(!parser.has_errors(), "`{}` does not parse", source);
+
+        parser.asts
+    }
+
+    // parse and check `source`
+    fn check(source: &str) -> (Checker, Scopes) {
+        let mut checker = Checker::new(parse(source));
+        let scopes = checker.check();
+
+        (checker, scopes)
+    }
+
+    // the messages of the type and name errors, in the order they were found
+    fn errors(checker: &Checker) -> Vec<String> {
+        checker
+            .reposts
+            .iter()
+            .filter_map(|report| match &report.error {
+                ZXError::TypeError { message, .. } | ZXError::NameError { message, .. } => {
+                    Some(message.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn warnings(checker: &Checker) -> Vec<String> {
+        checker
+            .reposts
+            .iter()
+            .filter_map(|report| match &report.error {
+                ZXError::Warning { message, .. } => Some(message.clone()),
+                _ => None,
+            })
+            .filter(|message| !message.starts_with("field is never read"))
+            .collect()
+    }
+
+    // the name and the type of every top level variable
+    fn variable_types(scopes: &Scopes) -> Vec<(String, String)> {
+        scopes
+            .scopes
+            .iter()
+            .filter_map(|scope| match &scope.scope_type {
+                ScopeType::DefVariable { var_type, .. } => {
+                    Some((scope.name.to_string(), var_type.to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn variable_value<'a>(scopes: &'a Scopes, name: &str) -> &'a BytecodeType {
+        match scopes
+            .scopes
+            .iter()
+            .find(|scope| scope.name.to_string() == name)
+            .map(|scope| &scope.scope_type)
+        {
+            Some(ScopeType::DefVariable {
+                value: Some(value), ..
+            }) => value,
+            scope_type => panic!("`{}` declared as {:?}", name, scope_type),
+        }
+    }
+
+    // the bytecodes of the body of the top level function `name`
+    fn function_body<'a>(scopes: &'a Scopes, name: &str) -> &'a Vec<BytecodeType> {
+        match scopes
+            .scopes
+            .iter()
+            .find(|scope| scope.name.to_string() == name)
+            .map(|scope| &scope.scope_type)
+        {
+            Some(ScopeType::DefFunction {
+                block: BytecodeType::Box { bytecodes },
+                ..
+            }) => bytecodes,
+            scope_type => panic!("`{}` declared as {:?}", name, scope_type),
+        }
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
 
     #[test]
-    fn test() {}
+    fn test_number_literal_range() {
+        let (checker, scopes) = check(
+            "var a = 99999999999\nvar b = -2147483648\nvar c = 0xffffffffffffi64\nvar d = 1e39\nvar e = 1e39f64",
+        );
+
+        let errors = errors(&checker);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with("literal out of range for `Int`"));
+        assert!(errors[1].starts_with("literal out of range for `Float`"));
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[("b", "Int"), ("c", "Long"), ("e", "Double")])
+        );
+    }
+
+    #[test]
+    fn test_doc_comment_scope() {
+        let (_, scopes) = check("/// the answer\nvar a = 1");
+
+        assert_eq!(scopes.scopes[0].doc.as_deref(), Some("the answer"));
+    }
+
+    #[test]
+    fn test_operator_types() {
+        let (checker, scopes) = check(
+            "var a = 1 + 2 * 3\nvar b = 1 < 2 && 3 % 2 == 1\nvar c = 1 / 2.0\nvar d = 1 || 2",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "cannot apply `Div` to `Int` and `Float`",
+                "cannot apply `Or` to `Int`"
+            ]
+        );
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[("a", "Int"), ("b", "Bool")])
+        );
+    }
+
+    #[test]
+    fn test_unary_types() {
+        let (checker, scopes) =
+            check("var a = -1.5\nvar b = !(1 < 2)\nvar c = ~1.5\nvar d = !\"a\"");
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "cannot apply `BitNot` to `Float`",
+                "cannot apply `Not` to `Str`"
+            ]
+        );
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[("a", "Float"), ("b", "Bool")])
+        );
+    }
+
+    #[test]
+    fn test_field_assignment() {
+        let (checker, scopes) = check(
+            "class A {\n    var x: Int\n}\nfn f() {\n    var o = A()\n    o.x = 1\n    o.x = \"s\"\n    o.y = 1\n    o.x += 2\n}",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "mismatched types, expected `Int` found `Str`",
+                "no field `y` on type `A`",
+            ]
+        );
+        let stores = function_body(&scopes, "f")
+            .iter()
+            .filter_map(|bytecode| match bytecode {
+                BytecodeType::FieldStore {
+                    object,
+                    class,
+                    index,
+                    value,
+                } => Some((object.as_ref(), class.as_str(), *index, value.as_ref())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            &stores[..],
+            [
+                (BytecodeType::Load { path }, "$A", 0, BytecodeType::Value { .. }),
+                (_, "$A", 0, BytecodeType::Binary { left, .. }),
+            ] if path == "$f$o" && matches!(left.as_ref(), BytecodeType::FieldLoad { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let (checker, scopes) = check("fn f() {\n    var n = 1\n    n += 1.5\n    n *= 2\n}");
+
+        assert_eq!(
+            errors(&checker),
+            vec!["cannot apply `Add` to `Int` and `Float`"]
+        );
+        assert!(matches!(
+            &function_body(&scopes, "f")[..],
+            [
+                BytecodeType::Store { path, .. },
+                BytecodeType::Store { value, .. },
+            ] if path == "$f$n" && matches!(value.as_ref(), BytecodeType::Binary { .. })
+        ));
+    }
+
+    #[test]
+    fn test_assignment_target() {
+        let (checker, _) = check("fn f() {\n    m = 1\n    f = 1\n}");
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "NameError: name 'm' is not defined",
+                "cannot assign to `f`, it is not a variable",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imports() {
+        let math = Symbol::intern("math");
+        let mut module = Checker::module(parse("pub var PI = 3.14\nvar secret = 1"), &[math]);
+        let module_scopes = module.check();
+        let mut checker = Checker::new(parse(
+            "im math::{ PI as pi, secret, missing }\nim math\nim nothing::x\nim math::*\nvar x = math::PI",
+        ));
+        checker.add_module(vec![math], module_scopes);
+        let scopes = checker.check();
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "`secret` is private to module `math`",
+                "cannot find `missing` in module `math`",
+                "cannot find module `nothing`",
+            ]
+        );
+        assert_eq!(
+            scopes
+                .scopes
+                .iter()
+                .map(|scope| (scope.name.to_string(), scope.path.clone()))
+                .collect::<Vec<(String, String)>>(),
+            pairs(&[
+                ("pi", "$math$PI"),
+                ("math", "$math"),
+                ("PI", "$math$PI"),
+                ("x", "$x"),
+            ])
+        );
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[("pi", "Float"), ("PI", "Float"), ("x", "Float")])
+        );
+    }
+
+    const ANIMAL: &str = "class Animal {\n    var legs = 4\n    fn speak(): Int { 1 }\n    fn name(a: Int): Int { 2 }\n}\n";
+
+    #[test]
+    fn test_override() {
+        let (checker, scopes) = check(&format!(
+            "{}class Dog : Animal {{\n    override fn speak(): Int {{ super.speak() }}\n    var tail = 1\n}}",
+            ANIMAL
+        ));
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let (members, base) = match &scopes.scopes[1].scope_type {
+            ScopeType::DefClass { members, base, .. } => (members, base),
+            scope_type => panic!("declared {:?}", scope_type),
+        };
+        assert_eq!(base.map(|base| base.to_string()).as_deref(), Some("Animal"));
+        assert_eq!(
+            members
+                .scopes
+                .iter()
+                .map(|member| member.path.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["$Animal$legs", "$Dog$speak", "$Animal$name", "$Dog$tail"]
+        );
+    }
+
+    #[test]
+    fn test_override_errors() {
+        let (checker, _) = check(&format!(
+            "{}class Dog : Animal {{\n    override fn name(): Int {{ 3 }}\n    override fn fly(): Int {{ 4 }}\n    var legs = 3\n    fn bark() {{ super.bark() }}\n}}\nclass Cat : Animal {{\n    fn speak(): Int {{ 5 }}\n}}\nclass Fish : Water {{}}",
+            ANIMAL
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "method `name` does not match the signature of `Animal::name`",
+                "method `fly` is marked `override` but `Animal` has no method `fly`",
+                "`legs` is already declared in `Animal`",
+                "no member named `bark`",
+                "method `speak` overrides `Animal::speak` but is not marked `override`",
+                "NameError: name 'Water' is not defined",
+            ]
+        );
+    }
+
+    // the object, the class, the method and whether the call is direct
+    fn method_call(bytecode: &BytecodeType) -> (String, &str, &str, bool) {
+        match bytecode {
+            BytecodeType::CallMethod {
+                object,
+                class,
+                name,
+                direct,
+                ..
+            } => (
+                match object.as_ref() {
+                    BytecodeType::Load { path } => path.clone(),
+                    BytecodeType::New { path, .. } => format!("new {}", path),
+                    object => format!("{:?}", object),
+                },
+                class,
+                name,
+                *direct,
+            ),
+            bytecode => panic!("not a method call: {:?}", bytecode),
+        }
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let (checker, scopes) = check(&format!(
+            "{}class Dog : Animal {{\n    override fn speak(): Int {{ super.speak() }}\n}}\nvar dog = Dog()\nvar b = dog.name(1)\nfn main(): Int {{\n    Dog().speak()\n}}",
+            ANIMAL
+        ));
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let speak = match &scopes.scopes[1].scope_type {
+            ScopeType::DefClass { members, .. } => match &members.scopes[1].scope_type {
+                ScopeType::DefFunction {
+                    block: BytecodeType::Box { bytecodes },
+                    ..
+                } => &bytecodes[0],
+                scope_type => panic!("declared {:?}", scope_type),
+            },
+            scope_type => panic!("declared {:?}", scope_type),
+        };
+        assert_eq!(
+            method_call(speak),
+            ("$Dog$super".to_string(), "$Animal", "speak", true)
+        );
+
+        assert!(matches!(
+            variable_value(&scopes, "dog"),
+            BytecodeType::New { name, path } if name == "Dog" && path == "$Dog"
+        ));
+        let call = variable_value(&scopes, "b");
+        assert_eq!(
+            method_call(call),
+            ("$dog".to_string(), "$Dog", "name", false)
+        );
+        assert!(matches!(
+            call,
+            BytecodeType::CallMethod { arguments, .. } if arguments.len() == 1
+        ));
+        assert_eq!(
+            method_call(&function_body(&scopes, "main")[0]),
+            ("new $Dog".to_string(), "$Dog", "speak", false)
+        );
+    }
+
+    const BOX: &str = "class Box<T> {\n    var value: T\n    fn get(): T { this.value }\n}\nfn first<T>(a: T, b: T): T { a }\nfn wrap<T>(value: T): Box<T> { Box() }\n";
+
+    // object, class and index of a `FieldLoad`
+    fn field_load(bytecode: &BytecodeType) -> (String, &str, usize) {
+        match bytecode {
+            BytecodeType::FieldLoad {
+                object,
+                class,
+                index,
+                ..
+            } => (
+                match object.as_ref() {
+                    BytecodeType::Load { path } => path.clone(),
+                    object => format!("{:?}", object),
+                },
+                class,
+                *index,
+            ),
+            bytecode => panic!("not a field load: {:?}", bytecode),
+        }
+    }
+
+    #[test]
+    fn test_field_access() {
+        let (checker, scopes) = check(&format!(
+            "{}class Dog : Animal {{\n    var age = 1\n    fn years(): Int {{ this.age }}\n}}\nfn main(): Int {{\n    var dog = Dog()\n    var legs = dog.legs\n    dog.age\n}}",
+            ANIMAL
+        ));
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let years = match &scopes.scopes[1].scope_type {
+            ScopeType::DefClass { members, .. } => match &members.scopes[4].scope_type {
+                ScopeType::DefFunction {
+                    block: BytecodeType::Box { bytecodes },
+                    ..
+                } => &bytecodes[0],
+                scope_type => panic!("declared {:?}", scope_type),
+            },
+            scope_type => panic!("declared {:?}", scope_type),
+        };
+        assert_eq!(field_load(years), ("$Dog$this".to_string(), "$Dog", 1));
+
+        let body = function_body(&scopes, "main");
+        match &body[1] {
+            // inherited fields come first
+            BytecodeType::Store { value, .. } => {
+                assert_eq!(field_load(value), ("$main$dog".to_string(), "$Dog", 0))
+            }
+            bytecode => panic!("not a store: {:?}", bytecode),
+        }
+        assert_eq!(field_load(&body[2]), ("$main$dog".to_string(), "$Dog", 1));
+    }
+
+    #[test]
+    fn test_generic_inference() {
+        let (checker, scopes) = check(&format!(
+            "{}var a = first(1, 2)\nvar s = first(\"x\", \"y\")\nvar b: Box<Int> = Box()\nvar v = b.get()\nvar w = wrap(\"x\")",
+            BOX
+        ));
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[
+                ("a", "Int"),
+                ("s", "Str"),
+                ("b", "Box<Int>"),
+                ("v", "Int"),
+                ("w", "Box<Str>")
+            ])
+        );
+        assert!(matches!(
+            variable_value(&scopes, "a"),
+            BytecodeType::Call { path, type_arguments, arguments, .. }
+                if path == "$first"
+                    && type_arguments == &vec![ZXTyped::Integer { nullable: false }]
+                    && arguments.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_generic_errors() {
+        let (checker, _) = check(&format!(
+            "{}var mixed = first(1, \"x\")\nvar e = Box()\nvar c: Box<Int, Str>\nvar d: Int<Str>\nfn pair<T, T>() {{}}",
+            BOX
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "mismatched types, expected `Int` found `Str`",
+                "type annotations needed for `Box<_>`",
+                "class `Box` takes 1 type arguments but 2 were supplied",
+                "type `Int` takes no type arguments",
+                "the type parameter `T` is already declared",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_types() {
+        let (checker, scopes) = check(
+            "var a = [1, 2]\nvar grid = [a, []]\nvar empty: [Int] = []\nvar first = a[0]\nvar cell = grid[0][1]\nvar n = len(a)",
+        );
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[
+                ("a", "[Int]"),
+                ("grid", "[[Int]]"),
+                ("empty", "[Int]"),
+                ("first", "Int"),
+                ("cell", "Int"),
+                ("n", "Int"),
+            ])
+        );
+        assert!(matches!(
+            variable_value(&scopes, "n"),
+            BytecodeType::Len { .. }
+        ));
+    }
+
+    #[test]
+    fn test_array_errors() {
+        let (checker, _) = check(
+            "var n = 1\nvar a = [1]\nvar mixed = [1, \"x\"]\nvar unknown = []\nvar not_array = n[0]\nvar text_index = a[\"x\"]\nvar no_array = len(n)\nvar two = len(a, a)",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "mismatched types in the array, expected `Int` found `Str`",
+                "type annotations needed for `[_]`",
+                "cannot index into a value of type `Int`",
+                "array index must be `Int` or `Long`, found `Str`",
+                "`len` expects an array, found `Int`",
+                "`len` takes 1 argument but 2 arguments were supplied",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_comparison() {
+        let (checker, scopes) = check(
+            "var same = [1] == [2]\nvar empty = [[1]] != [[]]\nvar c: Bool = [1] == [\"x\"]\nvar d = [[1]] == [[1.5]]",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "cannot apply `Equal` to `[Int]` and `[Str]`",
+                "cannot apply `Equal` to `[[Int]]` and `[[Float]]`",
+            ]
+        );
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[("same", "Bool"), ("empty", "Bool")])
+        );
+    }
+
+    #[test]
+    fn test_index_assignment() {
+        let (checker, scopes) = check(
+            "fn set(values: [Int]) {\n    values[0] = 3\n    values[1] += 1\n    values[0] = \"x\"\n}",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec!["mismatched types, expected `Int` found `Str`"]
+        );
+        assert!(matches!(
+            &function_body(&scopes, "set")[..],
+            [
+                BytecodeType::IndexStore { array, value: first, .. },
+                BytecodeType::IndexStore { value: second, .. },
+            ] if matches!(array.as_ref(), BytecodeType::Load { path } if path == "$values")
+                && matches!(first.as_ref(), BytecodeType::Value { .. })
+                && matches!(
+                    second.as_ref(),
+                    BytecodeType::Binary { left, .. }
+                        if matches!(left.as_ref(), BytecodeType::Index { .. })
+                )
+        ));
+    }
+
+    // the variables and element types of the loops in the function `name`
+    fn loops<'a>(scopes: &'a Scopes, name: &str) -> Vec<(&'a str, String, &'a BytecodeType)> {
+        function_body(scopes, name)
+            .iter()
+            .filter_map(|bytecode| match bytecode {
+                BytecodeType::ForLoop {
+                    variable,
+                    element_type,
+                    iterable,
+                    ..
+                } => Some((
+                    variable.as_str(),
+                    element_type.to_string(),
+                    iterable.as_ref(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_for_loops() {
+        let (checker, scopes) = check(
+            "var r = 0..3\nfn walk(values: [Int]) {\n    for i in 0..10 { i }\n    for x in values { x }\n    for c in \"abc\" { c }\n}",
+        );
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        assert_eq!(variable_types(&scopes), pairs(&[("r", "Range<Int>")]));
+        let loops = loops(&scopes, "walk");
+        assert_eq!(
+            loops
+                .iter()
+                .map(|(variable, element_type, _)| (*variable, element_type.as_str()))
+                .collect::<Vec<(&str, &str)>>(),
+            [("$walk$i", "Int"), ("$walk$x", "Int"), ("$walk$c", "Char")]
+        );
+        assert!(matches!(
+            loops[0].2,
+            BytecodeType::Range {
+                inclusive: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &function_body(&scopes, "walk")[0],
+            BytecodeType::ForLoop { body, .. }
+                if matches!(body.as_ref(), BytecodeType::Block { bytecodes, .. } if bytecodes.len() == 1)
+        ));
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let (checker, scopes) = check("fn walk(n: Int) {\n    for i in 0..n - 1 { i }\n}");
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        assert!(matches!(
+            loops(&scopes, "walk")[..],
+            [(_, _, BytecodeType::Range { start, end, .. })]
+                if matches!(start.as_ref(), BytecodeType::Value { .. })
+                    && matches!(end.as_ref(), BytecodeType::Binary { .. })
+        ));
+    }
+
+    #[test]
+    fn test_for_loop_errors() {
+        let (checker, scopes) = check(
+            "fn walk() {\n    for f in 1.5..2 { f }\n    for m in 0..'c' { m }\n    for n in 5 { n }\n    for e in [] { e }\n    i\n}",
+        );
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "range bounds must be `Int` or `Long`, found `Float`",
+                "mismatched types in the range, expected `Int` found `Char`",
+                "cannot iterate over a value of type `Int`",
+                "type annotations needed for `[_]`",
+                "NameError: name 'i' is not defined",
+            ]
+        );
+        assert!(loops(&scopes, "walk").is_empty());
+    }
+
+    const ADD: &str = "fn add(a: Int, b: Int): Int { a + b }\nfn first<T>(a: T): T { a }\n";
+
+    #[test]
+    fn test_function_values() {
+        let source = format!(
+            "{}fn main(n: Int): Int {{\n    var offset = 10\n    var plus: (Int, Int) -> Int = add\n    var shift = fn(x: Int) {{ x + offset }}\n    shift(n)\n    plus(1, n)\n}}",
+            ADD
+        );
+        let (checker, scopes) = check(&source);
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let bytecodes = function_body(&scopes, "main");
+        assert!(matches!(
+            &bytecodes[1],
+            BytecodeType::Store { path, value } if path == "$main$plus"
+                && matches!(
+                    value.as_ref(),
+                    BytecodeType::FunctionRef { path, function_type }
+                        if path == "$add" && function_type.to_string() == "(Int, Int) -> Int"
+                )
+        ));
+        assert!(matches!(
+            &bytecodes[3],
+            BytecodeType::CallValue { function, arguments, .. }
+                if matches!(function.as_ref(), BytecodeType::Load { path } if path == "$main$shift")
+                    && arguments.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_closure_captures() {
+        let source = "fn main(): Int {\n    var offset = 10\n    var shift = fn(x: Int) { x + offset }\n    shift(1)\n}";
+        let (checker, scopes) = check(source);
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let path = format!("$closure@{}", source.find("fn(").unwrap());
+        match &function_body(&scopes, "main")[1] {
+            BytecodeType::Store { value, .. } => match value.as_ref() {
+                BytecodeType::Closure {
+                    path: closure_path,
+                    parameters,
+                    return_type,
+                    captures,
+                    ..
+                } => {
+                    assert_eq!(closure_path, &path);
+                    assert_eq!(parameters[0].path, format!("{}$x", path));
+                    assert_eq!(return_type.to_string(), "Int");
+                    assert_eq!(
+                        captures
+                            .iter()
+                            .map(|capture| capture.path.as_str())
+                            .collect::<Vec<&str>>(),
+                        ["$main$offset"]
+                    );
+                }
+                value => panic!("stored {:?}", value),
+            },
+            bytecode => panic!("built {:?}", bytecode),
+        }
+    }
+
+    #[test]
+    fn test_function_comparison() {
+        let (checker, scopes) = check(&format!(
+            "{}fn name(s: Str): Str {{ s }}\nfn twice(a: Int): Int {{ a }}\nvar f: (Int) -> Int = twice\nvar g: (Str) -> Str = name\nvar same = f == twice\nvar other = f == g\nvar h = add == twice",
+            ADD
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "cannot apply `Equal` to `(Int) -> Int` and `(Str) -> Str`",
+                "cannot apply `Equal` to `(Int, Int) -> Int` and `(Int) -> Int`",
+            ]
+        );
+        assert_eq!(
+            variable_types(&scopes),
+            pairs(&[
+                ("f", "(Int) -> Int"),
+                ("g", "(Str) -> Str"),
+                ("same", "Bool")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_function_value_errors() {
+        let (checker, _) = check(&format!(
+            "{}fn main() {{\n    var offset = 10\n    var plus: (Int, Int) -> Int = add\n    plus(1)\n    plus(1, \"a\")\n    offset(1)\n    first\n    fn(x: Int): Str {{ x }}\n}}",
+            ADD
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "this function takes 2 argument but 1 arguments were supplied",
+                "mismatched types, expected `Int` found `Str`",
+                "expected a function, found `Int`",
+                "generic function `first` cannot be used as a value",
+                "mismatched types, expected `Str` found `Int`",
+            ]
+        );
+    }
+
+    const SHAPE: &str = "enum Shape {\n    Circle(Float), Rect(Float, Float), Empty\n}\n";
+
+    #[test]
+    fn test_enum_declaration() {
+        let (checker, scopes) =
+            check("enum Shape {\n    Circle(Float), Rect(Float, Float)\n    Empty, Circle\n}");
+
+        assert_eq!(
+            errors(&checker),
+            vec!["`Circle` is already declared in `Shape`"]
+        );
+        match &scopes.scopes[0].scope_type {
+            ScopeType::DefEnum { variants } => assert_eq!(
+                variants
+                    .scopes
+                    .iter()
+                    .map(|variant| match &variant.scope_type {
+                        ScopeType::DefVariant { tag, payload } => {
+                            (variant.name.to_string(), *tag, payload.len())
+                        }
+                        scope_type => panic!("declared {:?}", scope_type),
+                    })
+                    .collect::<Vec<(String, u32, usize)>>(),
+                vec![
+                    ("Circle".to_string(), 0, 1),
+                    ("Rect".to_string(), 1, 2),
+                    ("Empty".to_string(), 2, 0)
+                ]
+            ),
+            scope_type => panic!("declared {:?}", scope_type),
+        }
+    }
+
+    #[test]
+    fn test_enum_values() {
+        let (checker, _) = check(&format!(
+            "{}fn main() {{\n    var circle = Shape::Circle(1.0)\n    var empty = Shape::Empty\n    var square = Shape::Square\n    var rect = Shape::Rect(1.0)\n    var wrong = Shape::Circle(1)\n}}",
+            SHAPE
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "no variant named `Square` in `Shape`",
+                "the variant `Shape::Rect` takes 2 fields but 1 were supplied",
+                "mismatched types, expected `Float` found `Int`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_bytecode() {
+        let source = format!(
+            "{}fn area(shape: Shape): Float {{\n    match shape {{\n        Shape::Circle(r) => r * r\n        Shape::Rect(w, _) => w\n        Shape::Empty => 0.0\n    }}\n}}",
+            SHAPE
+        );
+        let (checker, scopes) = check(&source);
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        let circle = format!("$match@{}$r", source.find("Shape::Circle(r)").unwrap());
+        match &function_body(&scopes, "area")[..] {
+            [BytecodeType::Match {
+                value_type,
+                result_type,
+                arms,
+                ..
+            }] => {
+                assert_eq!(value_type.to_string(), "Shape");
+                assert_eq!(result_type.to_string(), "Float");
+                assert!(matches!(
+                    &arms[0].pattern,
+                    bytecode::Pattern::Variant { tag: 0, fields, .. }
+                        if matches!(
+                            &fields[..],
+                            [bytecode::Pattern::Binding { path: Some(path) }] if path == &circle
+                        )
+                ));
+                assert!(matches!(
+                    &arms[2].pattern,
+                    bytecode::Pattern::Variant { tag: 2, fields, .. } if fields.is_empty()
+                ));
+            }
+            bytecodes => panic!("built {:?}", bytecodes),
+        }
+    }
+
+    #[test]
+    fn test_match_exhaustiveness() {
+        let (checker, _) = check(&format!(
+            "{}fn main(shape: Shape, n: Int, b: Bool) {{\n    var a = match shape {{\n        Shape::Circle(_) => 1\n        Shape::Empty => 2\n    }}\n    var m = match n {{\n        1 => 10\n        -1 => 20\n    }}\n    var c = match b {{\n        true => 1\n        false => 0\n    }}\n}}",
+            SHAPE
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "non-exhaustive patterns: `Shape::Rect(_, _)` not covered",
+                "non-exhaustive patterns: `_` not covered",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_result() {
+        let (checker, _) = check(&format!(
+            "{}fn area(shape: Shape): Int {{\n    match shape {{\n        Shape::Circle(_) => 1\n    }}\n}}\nfn size(n: Int): Int {{\n    match n {{\n        1 => 1\n        _ => 2\n    }}\n}}",
+            SHAPE
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec!["non-exhaustive patterns: `Shape::Rect(_, _)` not covered"]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_patterns() {
+        let (checker, _) = check(&format!(
+            "{}fn main(shape: Shape, n: Int) {{\n    var a = match shape {{\n        Shape::Rect(_, _) => 1\n        Shape::Rect(_, 1.0) => 2\n        Shape::Circle(r) => 3\n        Shape::Empty => 4\n        _ => 5\n    }}\n    var b = match n {{\n        0x1 => 1\n        1 => 2\n        other => 3\n    }}\n}}",
+            SHAPE
+        ));
+
+        assert!(errors(&checker).is_empty(), "{:?}", errors(&checker));
+        assert_eq!(
+            warnings(&checker),
+            vec![
+                "unreachable pattern",
+                "unreachable pattern",
+                "unreachable pattern"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pattern_errors() {
+        let (checker, _) = check(&format!(
+            "{}fn main(shape: Shape, n: Int) {{\n    match n {{\n        \"a\" => 1\n        _ => 2\n    }}\n    match n {{\n        1 => 1\n        other => \"a\"\n    }}\n    match shape {{\n        Shape::Rect(a, a) => 1\n        _ => 2\n    }}\n    var d = match shape {{\n        Shape::Rect(_) => 1\n    }}\n}}",
+            SHAPE
+        ));
+
+        assert_eq!(
+            errors(&checker),
+            vec![
+                "mismatched types, expected `Int` found `Str`",
+                "`match` arms have incompatible types, expected `Int` found `Str`",
+                "identifier `a` is bound more than once in the same pattern",
+                "this pattern has 1 fields, but the variant `Shape::Rect` has 2 fields",
+            ]
+        );
+    }
 }
@@ -1,47 +1,33 @@
-use check::Checker;
-use lexer::Lexer;
-use parser::Parser;
-use std::fs;
-use std::path::Path;
-use util::report::{Level, Report};
+mod module;
+
+pub use module::ModuleLoader;
+use std::path::{Path, PathBuf};
+use util::source_map::SourceMap;
 
 pub struct Compiler {
     pub path: String,
+    /// directories searched for imported modules that are not next to the
+    /// importing file
+    pub search_paths: Vec<PathBuf>,
 }
 
 impl Compiler {
     pub fn compile(&self) -> Result<(), ()> {
-        let source = fs::read_to_string(&self.path).expect("Something went wrong reading the file");
-        let mut lexer = Lexer::new(&source);
-
-        let mut check = match lexer.lexer() {
-            Ok(()) => {
-                let mut parser = Parser::new(&lexer.tokens);
-                parser.parse(&self.path, &source);
-                Checker::new(parser.asts)
-            }
-            Err(error) => {
-                // report lexer error
-                Report {
-                    level: Level::Error,
-                    error,
-                }
-                .print(&source, &self.path);
-                return Err(());
-            }
-        };
-
-        let scopes = check.check();
-
-        for repost in check.reposts.iter() {
-            if let Level::Debug { .. } = repost.level {
-            } else {
-                repost.print(&source, &self.path)
-            }
-        }
+        let mut source_map = SourceMap::new();
+        let file = source_map
+            .load_file(&self.path)
+            .expect("Something went wrong reading the file");
+        // imported modules are parsed and checked before the file using them
+        let scopes =
+            ModuleLoader::new(self.search_paths.clone()).check_file(&mut source_map, file, &[])?;
         let context = llvm::Context::create();
-        let builder = llvm::Builder::new(scopes, &context);
+        let builder = llvm::Builder::new(scopes, &source_map, &context);
         builder.build();
+        let reports = builder.take_reports();
+        if !reports.is_empty() {
+            reports.iter().for_each(|report| report.print(&source_map));
+            return Err(());
+        }
         builder.compile(&Path::new("./test.bc"));
         Ok(())
     }
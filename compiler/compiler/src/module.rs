@@ -0,0 +1,171 @@
+use check::Checker;
+use lexer::Lexer;
+use parser::Parser;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use util::ast::{ImportKind, Statement};
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::Scopes;
+use util::source_map::{FileId, SourceMap};
+use util::symbol::Symbol;
+use util::token::Position;
+
+/// Finds, parses and checks the modules imported by a file.
+///
+/// `im a::b` is looked up as `a/b.zx` next to the importing file, then in
+/// every search path. Every module is checked once, its top level scopes
+/// are given to the checkers of the files importing it.
+pub struct ModuleLoader {
+    search_paths: Vec<PathBuf>,
+    // top level scopes of every module by file, `None` while it is checked
+    modules: HashMap<PathBuf, Option<Scopes>>,
+}
+
+impl ModuleLoader {
+    pub fn new(search_paths: Vec<PathBuf>) -> ModuleLoader {
+        ModuleLoader {
+            search_paths,
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Parse and check the module `module_path` in `file`, the modules it
+    /// imports are checked first. Reports are printed, `Err` means the file
+    /// or one of its imports has errors.
+    pub fn check_file(
+        &mut self,
+        source_map: &mut SourceMap,
+        file: FileId,
+        module_path: &[Symbol],
+    ) -> Result<Scopes, ()> {
+        let asts = {
+            let mut parser = Parser::new(Lexer::new(source_map.source(file), file));
+            parser.parse(source_map);
+
+            if parser.has_errors() {
+                return Err(());
+            }
+
+            parser.asts
+        };
+        let path = Path::new(&source_map.file(file).path);
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let canonical_path = fs::canonicalize(path).ok();
+        if let Some(canonical_path) = &canonical_path {
+            self.modules.insert(canonical_path.clone(), None);
+        }
+        let mut checker = Checker::module(asts.clone(), module_path);
+        let mut has_errors = false;
+
+        for (candidates, pos) in asts.iter().filter_map(module_candidates) {
+            let resolved = candidates.into_iter().find_map(|path| {
+                self.resolve(&directory, &path)
+                    .map(|file_path| (path, file_path))
+            });
+            let (path, file_path) = match resolved {
+                Some(resolved) => resolved,
+                // the checker reports the import it can not resolve
+                None => continue,
+            };
+
+            match self.modules.get(&file_path) {
+                Some(Some(scopes)) => checker.add_module(path, scopes.clone()),
+                Some(None) => {
+                    Report {
+                        level: Level::Error,
+                        error: ZXError::NameError {
+                            message: format!("module `{}` imports itself", file_path.display()),
+                            pos,
+                        },
+                    }
+                    .print(source_map);
+                    has_errors = true;
+                }
+                None => {
+                    let file = source_map.load_file(&file_path.display().to_string());
+                    let file = match file {
+                        Ok(file) => file,
+                        Err(error) => {
+                            Report {
+                                level: Level::Error,
+                                error: ZXError::NameError {
+                                    message: format!(
+                                        "cannot read `{}`: {}",
+                                        file_path.display(),
+                                        error
+                                    ),
+                                    pos,
+                                },
+                            }
+                            .print(source_map);
+                            has_errors = true;
+                            continue;
+                        }
+                    };
+
+                    let scopes = self.check_file(source_map, file, &path)?;
+                    checker.add_module(path, scopes);
+                }
+            }
+        }
+
+        let scopes = checker.check();
+        if let Some(canonical_path) = canonical_path {
+            self.modules.insert(canonical_path, Some(scopes.clone()));
+        }
+
+        for repost in checker.reposts.iter() {
+            if !matches!(repost.level, Level::Debug) {
+                repost.print(source_map)
+            }
+            has_errors |= matches!(repost.level, Level::Error);
+        }
+
+        if has_errors {
+            return Err(());
+        }
+
+        Ok(scopes)
+    }
+
+    // `a/b.zx` for the module `a::b`
+    fn resolve(&self, directory: &Path, path: &[Symbol]) -> Option<PathBuf> {
+        let mut relative = path
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<PathBuf>();
+        relative.set_extension("zx");
+
+        std::iter::once(directory)
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|directory| directory.join(&relative))
+            .find(|file_path| file_path.is_file())
+            .and_then(|file_path| fs::canonicalize(file_path).ok())
+    }
+}
+
+// modules an import can refer to in the order they are tried, `im a::b` is
+// the module `a::b` or an item of `a`
+fn module_candidates(statement: &Statement) -> Option<(Vec<Vec<Symbol>>, Position)> {
+    let (path, kind) = match statement {
+        Statement::Import { path, kind, .. } => (path, kind),
+        _ => return None,
+    };
+    let names = path
+        .iter()
+        .map(|name| name.get_symbol())
+        .collect::<Result<Vec<Symbol>, ZXError>>()
+        .ok()?;
+    let pos = path.first()?.pos.to(&path.last()?.pos);
+    let mut candidates = vec![names.clone()];
+
+    if let ImportKind::Single { .. } = kind {
+        if names.len() > 1 {
+            candidates.push(names[..names.len() - 1].to_vec());
+        }
+    }
+
+    Some((candidates, pos))
+}
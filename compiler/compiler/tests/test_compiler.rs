@@ -1,13 +1,59 @@
 #[cfg(test)]
 mod test_compiler {
-    use compiler::Compiler;
+    use compiler::{Compiler, ModuleLoader};
+    use util::source_map::SourceMap;
 
     #[test]
     fn test_compiler() {
         Compiler {
             path: "./test_data/test.zx".to_string(),
+            search_paths: vec![],
         }
         .compile()
         .expect("sad");
     }
+
+    #[test]
+    fn test_module_loader() {
+        let mut source_map = SourceMap::new();
+        let file = source_map
+            .load_file(&"./test_data/modules/main.zx".to_string())
+            .unwrap();
+        let scopes = ModuleLoader::new(vec![])
+            .check_file(&mut source_map, file, &[])
+            .expect("modules");
+
+        let names = scopes
+            .scopes
+            .iter()
+            .map(|scope| (scope.name.to_string(), scope.path.clone()))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(
+            names,
+            [
+                ("pow", "$math$pow"),
+                ("pi", "$math$PI"),
+                ("double", "$math$double"),
+                ("shapes", "$geometry$shapes"),
+                ("shape_area", "$geometry$shapes$area"),
+                ("main", "$main"),
+            ]
+            .map(|(name, path)| (name.to_string(), path.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_module_errors() {
+        for path in [
+            "./test_data/modules/missing.zx",
+            "./test_data/modules/cycle.zx",
+        ] {
+            let mut source_map = SourceMap::new();
+            let file = source_map.load_file(&path.to_string()).unwrap();
+
+            assert!(ModuleLoader::new(vec![])
+                .check_file(&mut source_map, file, &[])
+                .is_err());
+        }
+    }
 }
@@ -1,13 +1,108 @@
+use crate::file_stream::StringStream;
 use crate::Lexer;
+use util::error::ZXError;
 
-impl Lexer {
-    pub(crate) fn escapes(&self, now_char: char) -> char {
-        match now_char {
+impl Lexer<'_> {
+    /// Decode the escape sequence whose `\` is at `start`, the stream is on the
+    /// character after the backslash and is left on the last character of the
+    /// sequence.
+    pub(crate) fn escapes(
+        &self,
+        string_stream: &mut StringStream,
+        start: usize,
+    ) -> Result<char, ZXError> {
+        Ok(match string_stream.get_currently() {
             'n' => '\n',
             'r' => '\r',
             't' => '\t',
             '0' => '\0',
-            _ => now_char,
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '$' => '$',
+            'x' => self.hex_escape(string_stream, start)?,
+            'u' => self.unicode_escape(string_stream, start)?,
+            c => {
+                return Err(ZXError::SyntaxError {
+                    message: format!("unknown character escape: `{}`", c.escape_default()),
+                    pos: self.pos(start, string_stream.index + c.len_utf8()),
+                })
+            }
+        })
+    }
+
+    // `\x7f`, exactly two hex digits of an ASCII character
+    fn hex_escape(&self, string_stream: &mut StringStream, start: usize) -> Result<char, ZXError> {
+        let mut digits = String::new();
+
+        while digits.len() < 2 && string_stream.first().is_ascii_hexdigit() {
+            string_stream.next();
+            digits.push(string_stream.get_currently());
+        }
+
+        let pos = self.pos(start, string_stream.index + 1);
+        if digits.len() < 2 {
+            return Err(ZXError::SyntaxError {
+                message: "numeric character escape is too short".to_string(),
+                pos,
+            });
         }
+
+        let value = u32::from_str_radix(&digits, 16).unwrap();
+        if value > 0x7f {
+            return Err(ZXError::SyntaxError {
+                message: "out of range hex escape, must be a character in the range [\\x00-\\x7f]"
+                    .to_string(),
+                pos,
+            });
+        }
+
+        Ok(char::from_u32(value).unwrap())
+    }
+
+    // `\u{4e2d}`, one to six hex digits of a Unicode scalar value
+    fn unicode_escape(
+        &self,
+        string_stream: &mut StringStream,
+        start: usize,
+    ) -> Result<char, ZXError> {
+        if string_stream.first() != '{' {
+            return Err(ZXError::SyntaxError {
+                message: "incorrect unicode escape sequence, expected `\\u{...}`".to_string(),
+                pos: self.pos(start, string_stream.index + 1),
+            });
+        }
+        string_stream.next();
+
+        let mut digits = String::new();
+        while string_stream.first().is_ascii_hexdigit() {
+            string_stream.next();
+            digits.push(string_stream.get_currently());
+        }
+
+        if string_stream.first() != '}' {
+            return Err(ZXError::SyntaxError {
+                message: "unterminated unicode escape, expected `}`".to_string(),
+                pos: self.pos(start, string_stream.index + 1),
+            });
+        }
+        string_stream.next();
+
+        let pos = self.pos(start, string_stream.index + 1);
+        let message = if digits.is_empty() {
+            "empty unicode escape"
+        } else if digits.len() > 6 {
+            "overlong unicode escape, must have at most 6 hex digits"
+        } else {
+            match char::from_u32(u32::from_str_radix(&digits, 16).unwrap()) {
+                Some(c) => return Ok(c),
+                None => "invalid unicode character escape, must not be a surrogate or above 10FFFF",
+            }
+        };
+
+        Err(ZXError::SyntaxError {
+            message: message.to_string(),
+            pos,
+        })
     }
 }
@@ -5,6 +5,7 @@ pub struct StringStream<'a> {
     code_string_iterators: Chars<'a>,
     currently: char,
     prev: char,
+    /// byte offset of the current char
     pub(crate) index: usize,
     pub is_eof: bool,
 }
@@ -29,8 +30,12 @@ impl StringStream<'_> {
 
     pub fn next(&mut self) {
         let next_char = self.code_string_iterators.next();
+        self.index += if self.is_eof {
+            0
+        } else {
+            self.currently.len_utf8()
+        };
         self.is_eof = next_char == None;
-        self.index += 1;
 
         if !self.is_eof {
             self.prev = next_char.unwrap();
@@ -41,4 +46,13 @@ impl StringStream<'_> {
     pub fn first(&self) -> char {
         self.code_string_iterators.clone().next().unwrap_or('\0')
     }
+
+    pub fn second(&self) -> char {
+        self.code_string_iterators.clone().nth(1).unwrap_or('\0')
+    }
+
+    /// The source text after the current char
+    pub(crate) fn rest(&self) -> &str {
+        self.code_string_iterators.as_str()
+    }
 }
@@ -1,8 +1,13 @@
 use crate::file_stream;
 use crate::Lexer;
 use file_stream::StringStream;
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::mixed_script::AugmentedScriptSet;
+use unicode_security::{is_potential_mixed_script_confusable_char, MixedScript};
+use unicode_xid::UnicodeXID;
 use util::error::ZXError;
-use util::token::{Literal, Position, Token, Tokens};
+use util::symbol::Symbol;
+use util::token::{Literal, Token, Tokens};
 
 pub fn is_whitespace(c: char) -> bool {
     // This is Pattern_White_Space.
@@ -33,39 +38,122 @@ pub fn is_whitespace(c: char) -> bool {
     )
 }
 
-impl Lexer {
+/// Identifiers follow UAX #31: `XID_Start` or `_`, then `XID_Continue`
+pub fn is_identifier_start(c: char) -> bool {
+    c == '_' || c.is_xid_start()
+}
+
+// UAX #31 only allows the zero-width joiners in a few scripts, they are
+// rejected everywhere instead
+pub fn is_identifier_continue(c: char) -> bool {
+    c.is_xid_continue() && !matches!(c, '\u{200C}' | '\u{200D}')
+}
+
+// UTS #39: a name that is not single-script and whose non-Latin letters all
+// look like Latin ones, e.g. a Cyrillic `а` in `pаypal`. Chinese mixed with
+// Latin is fine since Han letters cannot be mistaken for Latin.
+fn is_mixed_script_confusable(ident: &str) -> bool {
+    let latin = AugmentedScriptSet::for_char('a');
+
+    !ident.is_single_script()
+        && ident
+            .chars()
+            .filter(|&c| {
+                let mut script = AugmentedScriptSet::for_char(c);
+                script.intersect_with(latin);
+                script.is_empty()
+            })
+            .all(is_potential_mixed_script_confusable_char)
+}
+
+/// Operators made of two characters, `/=` is handled by `lex_slash`
+pub fn double_char_token(first: char, second: char) -> Option<Tokens> {
+    Some(match (first, second) {
+        (':', ':') => Tokens::DoubleColonToken,
+        ('=', '=') => Tokens::DoubleEqualToken,
+        ('!', '=') => Tokens::NotEqualToken,
+        ('<', '=') => Tokens::LessEqualToken,
+        ('>', '=') => Tokens::MoreEqualToken,
+        ('&', '&') => Tokens::AndToken,
+        ('|', '|') => Tokens::OrToken,
+        ('-', '>') => Tokens::ArrowToken,
+        ('=', '>') => Tokens::FatArrowToken,
+        ('+', '=') => Tokens::PlusEqualToken,
+        ('-', '=') => Tokens::MinusEqualToken,
+        ('*', '=') => Tokens::MultiplyEqualToken,
+        ('%', '=') => Tokens::PercentEqualToken,
+        ('<', '<') => Tokens::LeftShiftToken,
+        ('>', '>') => Tokens::RightShiftToken,
+        ('.', '.') => Tokens::DoubleDotToken,
+        _ => return None,
+    })
+}
+
+// text of a `/** */` doc comment without the leading `*` of every line
+fn block_doc(content: &str) -> String {
+    let lines = content
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect::<Vec<&str>>();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
+impl Lexer<'_> {
+    // Identifiers are NFC normalized, so names that only differ in how their
+    // accents are encoded are the same name.
     pub fn lex_identifier(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
-        let mut ident = String::from(string_stream.get_currently());
         let start = string_stream.index;
+        let mut ident = String::from(string_stream.get_currently());
 
-        while !string_stream.is_eof {
-            match string_stream.first() {
-                c if is_whitespace(c) => break,
-                ' '..='/' | ':'..='@' | '['..='^' | '{'..='~' | '`' => break,
-                c @ _ => {
-                    string_stream.next();
-                    ident.push(c)
-                }
-            }
+        while is_identifier_continue(string_stream.first()) {
+            string_stream.next();
+            ident.push(string_stream.get_currently());
+        }
+
+        let pos = self.pos(start, start + ident.len());
+        let ident = ident.nfc().collect::<String>();
+
+        if is_mixed_script_confusable(&ident) {
+            self.report(ZXError::Warning {
+                message: format!(
+                    "identifier `{}` mixes scripts and contains confusable characters",
+                    ident
+                ),
+                pos: pos.clone(),
+            });
         }
 
-        self.tokens.push(Token {
-            token_type: Tokens::IdentifierToken {
-                literal: ident.clone(),
-            },
-            pos: Position {
-                start,
-                end: start + ident.len(),
-            },
+        self.push(Token {
+            token_type: Tokens::keyword(&ident).unwrap_or_else(|| Tokens::IdentifierToken {
+                literal: Symbol::intern(&ident),
+            }),
+            pos,
         });
         Ok(())
     }
 
     // lex string `"abc\"\n"`
+    //
+    // A string containing `${...}` is emitted as a template instead:
+    // `TemplateStartToken`, string segments and `InterpolationStartToken`
+    // ... `InterpolationEndToken` around the embedded expression tokens,
+    // then `TemplateEndToken`.
     pub fn lex_string(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
         let mut string_content = String::new();
         let mut end_double_quotes = false;
+        let mut is_template = false;
         let start = string_stream.index.clone();
+        let mut segment_start = start + 1;
         string_stream.next();
 
         while !string_stream.is_eof {
@@ -75,13 +163,36 @@ impl Lexer {
                     end_double_quotes = true;
                     break;
                 }
+                // unterminated string, resume lexing on the next line
+                '\n' => break,
+                // interpolation `${expression}`
+                '$' if string_stream.first() == '{' => {
+                    if !is_template {
+                        is_template = true;
+                        self.push(Token {
+                            token_type: Tokens::TemplateStartToken,
+                            pos: self.pos(start, start + 1),
+                        });
+                    }
+                    self.push_string_segment(
+                        &mut string_content,
+                        segment_start,
+                        string_stream.index,
+                    );
+
+                    if let Err(error) = self.lex_interpolation(string_stream) {
+                        self.push_template_end(string_stream.index);
+                        return Err(error);
+                    }
+                    segment_start = string_stream.index + 1;
+                }
                 // string escapes
                 '\\' => {
+                    let escape_start = string_stream.index;
                     string_stream.next();
-                    let currently = string_stream.get_currently();
 
-                    if !string_stream.is_eof {
-                        string_content.push(self.escapes(currently));
+                    if !string_stream.is_eof && string_stream.get_currently() != '\n' {
+                        string_content.push(self.escape_or_report(string_stream, escape_start));
                     } else {
                         break;
                     }
@@ -95,26 +206,89 @@ impl Lexer {
             string_stream.next();
         }
 
+        if is_template {
+            self.push_string_segment(&mut string_content, segment_start, string_stream.index);
+            self.push_template_end(string_stream.index);
+        }
+
         if end_double_quotes {
-            self.tokens.push(Token {
-                token_type: Tokens::LiteralToken {
-                    kid: Literal::String,
-                    literal: string_content,
-                },
-                pos: Position {
-                    start,
-                    end: string_stream.index,
-                },
-            });
+            if !is_template {
+                self.push(Token {
+                    token_type: Tokens::LiteralToken {
+                        kid: Literal::String,
+                        literal: string_content,
+                    },
+                    pos: self.pos(start, string_stream.index + 1),
+                });
+            }
 
             Ok(())
         } else {
             Err(ZXError::SyntaxError {
                 message: "EOL while scanning string literal".to_string(),
-                pos: Position { start, end: start },
+                pos: self.pos(start, start + 1),
             })
         }
     }
+
+    // lex the expression of `${...}`, the stream starts on `$` and is left on
+    // the closing `}`
+    fn lex_interpolation(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
+        let start = string_stream.index;
+        let mut depth = 0;
+        string_stream.next();
+        self.push(Token {
+            token_type: Tokens::InterpolationStartToken,
+            pos: self.pos(start, start + 2),
+        });
+        string_stream.next();
+
+        while !string_stream.is_eof {
+            match string_stream.get_currently() {
+                '}' if depth == 0 => {
+                    self.push(Token {
+                        token_type: Tokens::InterpolationEndToken,
+                        pos: self.pos(string_stream.index, string_stream.index + 1),
+                    });
+                    return Ok(());
+                }
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+
+            if let Err(error) = self.lex_token(string_stream) {
+                self.recover(error);
+            }
+            string_stream.next();
+        }
+
+        Err(ZXError::SyntaxError {
+            message: "unclosed `${` in string literal".to_string(),
+            pos: self.pos(start, start + 2),
+        })
+    }
+
+    fn push_string_segment(&mut self, content: &mut String, start: usize, end: usize) {
+        if !content.is_empty() {
+            self.push(Token {
+                token_type: Tokens::LiteralToken {
+                    kid: Literal::String,
+                    literal: std::mem::take(content),
+                },
+                pos: self.pos(start, end),
+            });
+        }
+    }
+
+    // also closes unterminated templates so the parser sees balanced tokens
+    fn push_template_end(&mut self, index: usize) {
+        self.push(Token {
+            token_type: Tokens::TemplateEndToken,
+            pos: self.pos(index, index + 1),
+        });
+    }
+
     // lex char `'a'`
     pub fn lex_char(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
         let start = string_stream.index.clone();
@@ -130,11 +304,18 @@ impl Lexer {
                     end_apostrophe = true;
                     break;
                 }
+                // unterminated char, resume lexing on the next line
+                '\n' => break,
                 // char escapes
                 '\\' => {
+                    let escape_start = string_stream.index;
                     string_stream.next();
-                    let next_char = string_stream.get_currently();
-                    c.push(self.escapes(next_char));
+
+                    if !string_stream.is_eof && string_stream.get_currently() != '\n' {
+                        c.push(self.escape_or_report(string_stream, escape_start));
+                    } else {
+                        break;
+                    }
                 }
                 // char content
                 _ => {
@@ -147,7 +328,7 @@ impl Lexer {
 
         let error_message = if !end_apostrophe {
             "EOL while scanning char literal"
-        } else if c.len() > 1 {
+        } else if c.chars().count() > 1 {
             "character literal may only contain one codepoint"
         } else if c.is_empty() {
             "empty character literal"
@@ -155,79 +336,115 @@ impl Lexer {
             ""
         };
 
+        let end = if end_apostrophe {
+            string_stream.index + 1
+        } else {
+            string_stream.index
+        };
+
         if !error_message.is_empty() {
             Err(ZXError::SyntaxError {
                 message: error_message.to_string(),
-                pos: Position {
-                    start,
-                    end: start + c.len() + 1,
-                },
+                pos: self.pos(start, end),
             })
         } else {
-            self.tokens.push(Token {
+            self.push(Token {
                 token_type: Tokens::LiteralToken {
                     kid: Literal::Char,
                     literal: c.clone(),
                 },
-                pos: Position {
-                    start,
-                    end: start + c.len() + 1,
-                },
+                pos: self.pos(start, end),
             });
             Ok(())
         }
     }
 
+    // a bad escape is reported but does not end the literal, the literal keeps
+    // a replacement character in its place
+    pub(crate) fn escape_or_report(
+        &mut self,
+        string_stream: &mut StringStream,
+        start: usize,
+    ) -> char {
+        match self.escapes(string_stream, start) {
+            Ok(c) => c,
+            Err(error) => {
+                self.report(error);
+                char::REPLACEMENT_CHARACTER
+            }
+        }
+    }
+
     pub fn lex_slash(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
         let start = string_stream.index;
 
         match string_stream.first() {
-            // single line comment
+            // doc comment `/// example`, `////` is a plain comment
+            '/' if string_stream.rest().starts_with("//")
+                && !string_stream.rest().starts_with("///") =>
+            {
+                let line = string_stream.rest()[2..]
+                    .split('\n')
+                    .next()
+                    .unwrap()
+                    .to_string();
+
+                for _ in 0..line.chars().count() + 2 {
+                    string_stream.next();
+                }
+                let text = line.trim_end_matches('\r');
+                self.push(Token {
+                    token_type: Tokens::DocCommentToken {
+                        literal: text.strip_prefix(' ').unwrap_or(text).to_string(),
+                    },
+                    pos: self.pos(start, start + 3 + line.len()),
+                });
+
+                Ok(())
+            }
+            // doc comment `/** example */`, `/**/` and `/***` are plain comments
+            '*' if string_stream.rest().starts_with("**")
+                && !string_stream.rest()[2..].starts_with(['*', '/']) =>
+            {
+                let content = self.block_comment(string_stream, start)?;
+
+                self.push(Token {
+                    token_type: Tokens::DocCommentToken {
+                        literal: block_doc(&content[1..]),
+                    },
+                    pos: self.pos(start, string_stream.index + 1),
+                });
+
+                Ok(())
+            }
+            // single line comment, the line break is left to end the line
             '/' => {
                 string_stream.next();
-                while !string_stream.is_eof && string_stream.get_currently() != '\n' {
+                while !string_stream.rest().is_empty() && string_stream.first() != '\n' {
                     string_stream.next();
                 }
                 Ok(())
             }
             // multi line comment
             '*' => {
+                self.block_comment(string_stream, start)?;
+                Ok(())
+            }
+            // slash equal
+            '=' => {
                 string_stream.next();
-                string_stream.next();
-                let mut end_comment = false;
-
-                // search close chars `*/`
-                while !string_stream.is_eof {
-                    if string_stream.get_currently() == '*' {
-                        if string_stream.first() == '/' {
-                            end_comment = true;
-                            string_stream.next();
-                            break;
-                        }
-                    }
-                    string_stream.next();
-                }
+                self.push(Token {
+                    token_type: Tokens::SlashEqualToken,
+                    pos: self.pos(start, start + 2),
+                });
 
-                if end_comment {
-                    Ok(())
-                } else {
-                    Err(ZXError::SyntaxError {
-                        message: "EOL while scanning comment".to_string(),
-                        pos: Position {
-                            start,
-                            end: start + 1,
-                        },
-                    })
-                }
+                Ok(())
             }
             // slash
             _ => {
-                self.tokens.push(Token {
+                self.push(Token {
                     token_type: Tokens::SlashToken,
-                    pos: Position {
-                        start: string_stream.index,
-                        end: string_stream.index,
-                    },
+                    pos: self.pos(string_stream.index, string_stream.index + 1),
                 });
 
                 Ok(())
@@ -235,70 +452,46 @@ impl Lexer {
         }
     }
 
-    pub fn lex_number(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
-        let mut is_float = false;
-        let mut number_string = String::new();
-        let currently = string_stream.get_currently();
-        let start = string_stream.index;
+    // skip a `/* */` comment and the comments nested in it, the stream starts
+    // on the opening `/` and is left on the closing one. Returns the text
+    // between the outermost markers.
+    fn block_comment(
+        &mut self,
+        string_stream: &mut StringStream,
+        start: usize,
+    ) -> Result<String, ZXError> {
+        // start of every comment that is still open, innermost last
+        let mut openers = vec![start];
+        let mut content = String::new();
+        string_stream.next();
+
+        while let Some(&innermost) = openers.last() {
+            string_stream.next();
 
-        number_string.push(currently);
+            if string_stream.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "EOL while scanning comment".to_string(),
+                    pos: self.pos(innermost, innermost + 2),
+                });
+            }
 
-        while !string_stream.is_eof {
-            match string_stream.first() {
-                '.' => {
+            match (string_stream.get_currently(), string_stream.first()) {
+                ('/', '*') => {
+                    openers.push(string_stream.index);
                     string_stream.next();
-                    match string_stream.first() {
-                        '0'..='9' => {
-                            is_float = true;
-                            number_string.push(string_stream.get_currently());
-                        }
-                        _ => break,
-                    }
+                    content.push_str("/*");
                 }
-                '0'..='9' => {
+                ('*', '/') => {
+                    openers.pop();
                     string_stream.next();
-                    // println!("{}", string_stream.get_currently());
-                    number_string.push(string_stream.get_currently());
+                    if !openers.is_empty() {
+                        content.push_str("*/");
+                    }
                 }
-                _ => break,
+                (c, _) => content.push(c),
             }
         }
 
-        let pos = Position {
-            start,
-            end: start + number_string.len(),
-        };
-
-        let token_type = Tokens::LiteralToken {
-            kid: if is_float {
-                Literal::Float
-            } else {
-                Literal::PositiveInteger
-            },
-            literal: number_string.clone(),
-        };
-
-        if (is_float && number_string.len() > 1) || (!is_float && number_string.len() > 0) {
-            self.tokens.push(Token { token_type, pos });
-            match string_stream.get_currently() {
-                '.' => self.tokens.push(Token {
-                    token_type: Tokens::DotToken,
-                    pos: Position {
-                        start: string_stream.index,
-                        end: string_stream.index,
-                    },
-                }),
-                _ => {}
-            }
-            Ok(())
-        } else {
-            Err(ZXError::SyntaxError {
-                message: "".to_string(),
-                pos: Position {
-                    start,
-                    end: start + number_string.len(),
-                },
-            })
-        }
+        Ok(content)
     }
 }
@@ -1,101 +1,260 @@
 mod escapes;
 pub mod file_stream;
 mod lex;
+mod number;
+mod raw_string;
 
-use crate::lex::is_whitespace;
+use crate::lex::{double_char_token, is_identifier_start, is_whitespace};
 use file_stream::StringStream;
+use std::collections::VecDeque;
 use util::error::ZXError;
-use util::token::{Position, Token, Tokens};
+use util::source_map::FileId;
+use util::token::{Position, Token, TokenKind, Tokens};
 
-pub struct Lexer {
-    source: String,
+/// Tokens are lexed on demand, either by iterating over the lexer or all at
+/// once with [`Lexer::lexer`].
+pub struct Lexer<'a> {
+    file_stream: StringStream<'a>,
+    file: FileId,
+    // tokens and errors already scanned but not yet taken by the consumer
+    lookahead: VecDeque<Result<Token, ZXError>>,
+    // kind of the last scanned token
+    last: Option<TokenKind>,
+    is_finished: bool,
     pub tokens: Vec<Token>,
+    pub errors: Vec<ZXError>,
+    pub warnings: Vec<ZXError>,
 }
 
-impl Lexer {
-    pub fn new(source: &String) -> Lexer {
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str, file: FileId) -> Lexer<'a> {
         Lexer {
-            source: source.to_string(),
+            file_stream: StringStream::new(source),
+            file,
+            lookahead: VecDeque::new(),
+            last: None,
+            is_finished: false,
             tokens: vec![],
+            errors: vec![],
+            warnings: vec![],
         }
     }
 
-    pub fn lexer(&mut self) -> Result<(), ZXError> {
-        let source = self.source.clone();
-        let mut file_stream = StringStream::new(&source);
-
-        while !file_stream.is_eof {
-            let currently = file_stream.get_currently();
-
-            match currently {
-                '"' | '/' | '\'' | '0'..='9' => {
-                    match currently {
-                        '"' => self.lex_string(&mut file_stream)?,
-                        '/' => self.lex_slash(&mut file_stream)?,
-                        '\'' => self.lex_char(&mut file_stream)?,
-                        '0'..='9' => self.lex_number(&mut file_stream)?,
-                        _ => {}
-                    };
+    /// Tokenize the whole source.
+    ///
+    /// Lexing does not stop at the first bad character: each lexical error is
+    /// recorded, an `ErrorToken` is pushed in place of the bad text and scanning
+    /// resumes after it. The returned errors are also kept in `self.errors`,
+    /// warnings go to `self.warnings`, and `self.tokens` always ends with `EOF`
+    /// so the parser can still run.
+    pub fn lexer(&mut self) -> Vec<ZXError> {
+        while let Some(item) = self.next() {
+            match item {
+                Ok(token) => self.tokens.push(token),
+                Err(warning @ ZXError::Warning { .. }) => self.warnings.push(warning),
+                Err(error) => self.errors.push(error),
+            }
+        }
+
+        self.errors.clone()
+    }
+
+    /// The next token or error without consuming it
+    pub fn peek(&mut self) -> Option<&Result<Token, ZXError>> {
+        self.peek_nth(0)
+    }
+
+    /// Look `n` items ahead, `peek_nth(0)` is the same as `peek()`
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Result<Token, ZXError>> {
+        while self.lookahead.len() <= n && !self.is_finished {
+            self.scan();
+        }
+
+        self.lookahead.get(n)
+    }
+
+    // Lex from the current char until at least one token or error is
+    // buffered, the last token is always `EOF`.
+    fn scan(&mut self) {
+        // the sub-lexers borrow the stream and the lexer at the same time,
+        // cloning the stream only copies its position
+        let mut file_stream = self.file_stream.clone();
+        let buffered = self.lookahead.len();
+
+        while self.lookahead.len() == buffered {
+            if file_stream.is_eof {
+                self.push(Token {
+                    token_type: Tokens::EOF,
+                    pos: self.pos(file_stream.index, file_stream.index),
+                });
+                self.is_finished = true;
+                break;
+            }
+
+            if let Err(error) = self.lex_token(&mut file_stream) {
+                self.recover(error);
+                // a sub-lexer that gave up at the end of a line still ends it
+                if file_stream.get_currently() == '\n' {
+                    self.line_separator(file_stream.index);
                 }
-                '!'..='.' | ':'..='@' | '['..='^' | '{'..='~' | '`' => {
-                    let kid = match currently {
-                        '*' => Tokens::MultiplyToken,
-                        '+' => Tokens::PlusToken,
-                        '-' => Tokens::MinusToken,
-                        '>' => Tokens::MoreToken,
-                        '<' => Tokens::LessToken,
-                        '=' => Tokens::EqualToken,
-                        '{' => Tokens::LeftCurlyBracketsToken,
-                        '}' => Tokens::RightCurlyBracketsToken,
-                        '[' => Tokens::LeftSquareBracketsToken,
-                        ']' => Tokens::RightSquareBracketsToken,
-                        '(' => Tokens::LeftParenthesesToken,
-                        ')' => Tokens::RightParenthesesToken,
-                        '.' => Tokens::DotToken,
-                        ';' => Tokens::SemicolonToken,
-                        ':' => Tokens::ColonToken,
-                        '!' => Tokens::ExclamationToken,
-                        '?' => Tokens::QuestionMarkToken,
-                        '&' => Tokens::AmpersandToken,
-                        ',' => Tokens::CommaToken,
-                        '%' => Tokens::PercentToken,
-                        '$' => Tokens::StdToken,
-                        _ => {
-                            return Err(ZXError::SyntaxError {
-                                message: "invalid syntax".to_string(),
-                                pos: Position {
-                                    start: file_stream.index,
-                                    end: file_stream.index,
-                                },
-                            });
-                        }
-                    };
-
-                    self.tokens.push(Token {
+            }
+
+            file_stream.next();
+        }
+
+        self.file_stream = file_stream;
+    }
+
+    fn lex_token(&mut self, file_stream: &mut StringStream) -> Result<(), ZXError> {
+        let currently = file_stream.get_currently();
+
+        match currently {
+            // multi-line string `"""..."""`
+            '"' if file_stream.rest().starts_with("\"\"") => {
+                self.lex_multiline_string(file_stream)?
+            }
+            // raw string `r"..."` `r#"..."#`
+            'r' if file_stream.rest().trim_start_matches('#').starts_with('"') => {
+                self.lex_raw_string(file_stream)?
+            }
+            '"' | '/' | '\'' | '0'..='9' => {
+                match currently {
+                    '"' => self.lex_string(file_stream)?,
+                    '/' => self.lex_slash(file_stream)?,
+                    '\'' => self.lex_char(file_stream)?,
+                    '0'..='9' => self.lex_number(file_stream)?,
+                    _ => {}
+                };
+            }
+            // leading dot float `.5`
+            '.' if file_stream.first().is_ascii_digit() => self.lex_number(file_stream)?,
+            // `..=` is the only operator of three characters
+            '.' if file_stream.rest().starts_with(".=") => {
+                let start = file_stream.index;
+                file_stream.next();
+                file_stream.next();
+                self.push(Token {
+                    token_type: Tokens::DoubleDotEqualToken,
+                    pos: self.pos(start, start + 3),
+                });
+            }
+            '!'..='.' | ':'..='@' | '['..='^' | '{'..='~' | '`' => {
+                if let Some(kid) = double_char_token(currently, file_stream.first()) {
+                    let start = file_stream.index;
+                    file_stream.next();
+                    self.push(Token {
                         token_type: kid,
-                        pos: Position {
-                            start: file_stream.index,
-                            end: file_stream.index,
-                        },
+                        pos: self.pos(start, start + 2),
                     });
+
+                    return Ok(());
                 }
-                c if !is_whitespace(c) => {
-                    self.lex_identifier(&mut file_stream)?;
-                }
-                _ => {}
+
+                let kid = match currently {
+                    '*' => Tokens::MultiplyToken,
+                    '+' => Tokens::PlusToken,
+                    '-' => Tokens::MinusToken,
+                    '>' => Tokens::MoreToken,
+                    '<' => Tokens::LessToken,
+                    '=' => Tokens::EqualToken,
+                    '{' => Tokens::LeftCurlyBracketsToken,
+                    '}' => Tokens::RightCurlyBracketsToken,
+                    '[' => Tokens::LeftSquareBracketsToken,
+                    ']' => Tokens::RightSquareBracketsToken,
+                    '(' => Tokens::LeftParenthesesToken,
+                    ')' => Tokens::RightParenthesesToken,
+                    '.' => Tokens::DotToken,
+                    ';' => Tokens::SemicolonToken,
+                    ':' => Tokens::ColonToken,
+                    '!' => Tokens::ExclamationToken,
+                    '?' => Tokens::QuestionMarkToken,
+                    '&' => Tokens::AmpersandToken,
+                    ',' => Tokens::CommaToken,
+                    '%' => Tokens::PercentToken,
+                    '~' => Tokens::TildeToken,
+                    '$' => Tokens::StdToken,
+                    _ => {
+                        return Err(ZXError::SyntaxError {
+                            message: "invalid syntax".to_string(),
+                            pos: self
+                                .pos(file_stream.index, file_stream.index + currently.len_utf8()),
+                        });
+                    }
+                };
+
+                self.push(Token {
+                    token_type: kid,
+                    pos: self.pos(file_stream.index, file_stream.index + 1),
+                });
+            }
+            '\n' => self.line_separator(file_stream.index),
+            c if is_identifier_start(c) => self.lex_identifier(file_stream)?,
+            c if !is_whitespace(c) => {
+                return Err(ZXError::SyntaxError {
+                    message: format!("unexpected character `{}` ({})", c, c.escape_unicode()),
+                    pos: self.pos(file_stream.index, file_stream.index + c.len_utf8()),
+                });
             }
+            _ => {}
+        }
 
-            file_stream.next();
+        Ok(())
+    }
+
+    // Line breaks end statements, blank lines and a line break at the start
+    // of the file are collapsed into the previous separator.
+    fn line_separator(&mut self, index: usize) {
+        match self.last {
+            None | Some(TokenKind::LineSeparatorToken) => {}
+            Some(_) => self.push(Token {
+                token_type: Tokens::LineSeparatorToken,
+                pos: self.pos(index, index + 1),
+            }),
         }
+    }
 
-        self.tokens.push(Token {
-            token_type: Tokens::EOF,
-            pos: Position {
-                start: file_stream.index,
-                end: file_stream.index,
-            },
-        });
+    pub(crate) fn pos(&self, start: usize, end: usize) -> Position {
+        Position {
+            file: self.file,
+            start,
+            end,
+        }
+    }
 
-        Ok(())
+    // Record a lexical error and leave an `ErrorToken` where the bad text was.
+    // Every sub-lexer stops on the last character it consumed, so the main
+    // loop resynchronizes simply by moving on to the next character.
+    fn recover(&mut self, error: ZXError) {
+        let pos = if let ZXError::SyntaxError { pos, .. } = &error {
+            Some(pos.clone())
+        } else {
+            None
+        };
+
+        self.report(error);
+        if let Some(pos) = pos {
+            self.push(Token {
+                token_type: Tokens::ErrorToken,
+                pos,
+            });
+        }
+    }
+
+    pub(crate) fn push(&mut self, token: Token) {
+        self.last = Some(token.kind());
+        self.lookahead.push_back(Ok(token));
+    }
+
+    pub(crate) fn report(&mut self, error: ZXError) {
+        self.lookahead.push_back(Err(error));
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, ZXError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.peek();
+        self.lookahead.pop_front()
     }
 }
@@ -0,0 +1,179 @@
+use crate::file_stream::StringStream;
+use crate::Lexer;
+use util::error::ZXError;
+use util::token::{Literal, Token, Tokens};
+
+/// Suffixes allowed after integer literals
+const INTEGER_SUFFIXES: [&str; 2] = ["i32", "i64"];
+/// Suffixes allowed after float literals, they also turn an integer into a float
+const FLOAT_SUFFIXES: [&str; 2] = ["f32", "f64"];
+
+impl Lexer<'_> {
+    // lex number `123` `0xff` `1_000` `.5` `1e-9` `10i64` `2.0f64`
+    //
+    // The token literal keeps the radix prefix and the suffix but drops the
+    // `_` separators, the value itself is range checked by the checker.
+    pub fn lex_number(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
+        let start = string_stream.index;
+        let mut number_string = String::new();
+        let mut is_float = false;
+
+        let radix = match (string_stream.get_currently(), string_stream.first()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'o' | 'O') => 8,
+            ('0', 'b' | 'B') => 2,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            string_stream.next();
+            number_string.push('0');
+            number_string.push(string_stream.get_currently().to_ascii_lowercase());
+
+            // digits and suffix of `0x1f_i64` are lexed as one word
+            let (word_start, word) = self.take_word(string_stream);
+            let digits_len = word
+                .find(|c: char| !(c.is_ascii_hexdigit() || c == '_'))
+                .unwrap_or(word.len());
+            let (digits, suffix) = word.split_at(digits_len);
+            let digits = self.radix_digits(digits, word_start, radix)?;
+
+            if digits.is_empty() {
+                return Err(ZXError::SyntaxError {
+                    message: "no valid digits found for number".to_string(),
+                    pos: self.pos(start, word_start + word.len()),
+                });
+            }
+            number_string.push_str(&digits);
+
+            if !suffix.is_empty() && !INTEGER_SUFFIXES.contains(&suffix) {
+                return Err(ZXError::SyntaxError {
+                    message: format!("invalid suffix `{}` for number literal", suffix),
+                    pos: self.pos(word_start + digits_len, word_start + word.len()),
+                });
+            }
+            number_string.push_str(suffix);
+        } else {
+            // integer part, empty for a leading dot float like `.5`
+            if string_stream.get_currently() != '.' {
+                number_string.push(string_stream.get_currently());
+                self.take_digits(string_stream, &mut number_string);
+            }
+
+            // fraction part, `1.` followed by something else is `1` and a dot
+            if string_stream.get_currently() == '.'
+                || (string_stream.first() == '.' && string_stream.second().is_ascii_digit())
+            {
+                if string_stream.get_currently() != '.' {
+                    string_stream.next();
+                }
+                is_float = true;
+                number_string.push('.');
+                self.take_digits(string_stream, &mut number_string);
+            }
+
+            // exponent part
+            if matches!(string_stream.first(), 'e' | 'E')
+                && (string_stream.second().is_ascii_digit()
+                    || matches!(string_stream.second(), '+' | '-'))
+            {
+                string_stream.next();
+                is_float = true;
+                number_string.push('e');
+
+                if matches!(string_stream.first(), '+' | '-') {
+                    string_stream.next();
+                    number_string.push(string_stream.get_currently());
+                }
+
+                if !string_stream.first().is_ascii_digit() {
+                    return Err(ZXError::SyntaxError {
+                        message: "expected at least one digit in exponent".to_string(),
+                        pos: self.pos(start, string_stream.index + 1),
+                    });
+                }
+                self.take_digits(string_stream, &mut number_string);
+            }
+
+            let (suffix_start, suffix) = self.take_word(string_stream);
+
+            if FLOAT_SUFFIXES.contains(&suffix.as_str()) {
+                is_float = true;
+            } else if !suffix.is_empty()
+                && (is_float || !INTEGER_SUFFIXES.contains(&suffix.as_str()))
+            {
+                return Err(ZXError::SyntaxError {
+                    message: format!(
+                        "invalid suffix `{}` for {} literal",
+                        suffix,
+                        if is_float { "float" } else { "number" }
+                    ),
+                    pos: self.pos(suffix_start, suffix_start + suffix.len()),
+                });
+            }
+            number_string.push_str(&suffix);
+        }
+
+        self.push(Token {
+            token_type: Tokens::LiteralToken {
+                kid: if is_float {
+                    Literal::Float
+                } else {
+                    Literal::PositiveInteger
+                },
+                literal: number_string,
+            },
+            pos: self.pos(start, string_stream.index + 1),
+        });
+
+        Ok(())
+    }
+
+    // consume the following decimal digits, skipping `_` separators
+    fn take_digits(&self, string_stream: &mut StringStream, number_string: &mut String) {
+        while matches!(string_stream.first(), '0'..='9' | '_') {
+            string_stream.next();
+            if string_stream.get_currently() != '_' {
+                number_string.push(string_stream.get_currently());
+            }
+        }
+    }
+
+    // consume the following `[0-9A-Za-z_]` characters, returns their start offset
+    fn take_word(&self, string_stream: &mut StringStream) -> (usize, String) {
+        let start = string_stream.index + string_stream.get_currently().len_utf8();
+        let mut word = String::new();
+
+        while string_stream.first().is_ascii_alphanumeric() || string_stream.first() == '_' {
+            string_stream.next();
+            word.push(string_stream.get_currently());
+        }
+
+        (start, word)
+    }
+
+    // check every digit against the radix and drop `_` separators
+    fn radix_digits(&self, digits: &str, start: usize, radix: u32) -> Result<String, ZXError> {
+        let radix_name = match radix {
+            16 => "hexadecimal",
+            8 => "octal",
+            _ => "binary",
+        };
+        let mut clean = String::new();
+
+        for (offset, c) in digits.char_indices() {
+            match c {
+                '_' => {}
+                c if c.is_digit(radix) => clean.push(c),
+                _ => {
+                    return Err(ZXError::SyntaxError {
+                        message: format!("invalid digit `{}` for a {} literal", c, radix_name),
+                        pos: self.pos(start + offset, start + offset + 1),
+                    })
+                }
+            }
+        }
+
+        Ok(clean)
+    }
+}
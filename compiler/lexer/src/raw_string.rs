@@ -0,0 +1,158 @@
+use crate::file_stream::StringStream;
+use crate::Lexer;
+use util::error::ZXError;
+use util::token::{Literal, Token, Tokens};
+
+impl Lexer<'_> {
+    // lex raw string `r"C:\path"` `r#"say "hi""#`
+    //
+    // Nothing is escaped, the string ends at the first `"` followed by as many
+    // `#` as the opening one and may span several lines.
+    pub fn lex_raw_string(&mut self, string_stream: &mut StringStream) -> Result<(), ZXError> {
+        let start = string_stream.index;
+        let mut hashes = 0;
+
+        while string_stream.first() == '#' {
+            string_stream.next();
+            hashes += 1;
+        }
+        // skip the opening quote
+        string_stream.next();
+        string_stream.next();
+
+        let closing = "#".repeat(hashes);
+        let mut string_content = String::new();
+
+        while !string_stream.is_eof {
+            if string_stream.get_currently() == '"' && string_stream.rest().starts_with(&closing) {
+                for _ in 0..hashes {
+                    string_stream.next();
+                }
+                self.push(Token {
+                    token_type: Tokens::LiteralToken {
+                        kid: Literal::String,
+                        literal: string_content,
+                    },
+                    pos: self.pos(start, string_stream.index + 1),
+                });
+
+                return Ok(());
+            }
+
+            string_content.push(string_stream.get_currently());
+            string_stream.next();
+        }
+
+        Err(ZXError::SyntaxError {
+            message: "EOF while scanning raw string literal".to_string(),
+            pos: self.pos(start, start + hashes + 2),
+        })
+    }
+
+    // lex multi-line string
+    // ```
+    // """
+    //     SELECT *
+    //     FROM users
+    //     """
+    // ```
+    //
+    // Escapes work as in `"..."`. A line break right after the opening `"""`
+    // and a blank line before the closing one are dropped, then the common
+    // indentation of the lines, closing line included, is removed.
+    pub fn lex_multiline_string(
+        &mut self,
+        string_stream: &mut StringStream,
+    ) -> Result<(), ZXError> {
+        let start = string_stream.index;
+        // characters with whether they came from an escape, an escaped `\n`
+        // or space is content and never a line break or indentation
+        let mut content: Vec<(char, bool)> = vec![];
+
+        for _ in 0..3 {
+            string_stream.next();
+        }
+
+        while !string_stream.is_eof {
+            match string_stream.get_currently() {
+                // close string
+                '"' if string_stream.rest().starts_with("\"\"") => {
+                    string_stream.next();
+                    string_stream.next();
+                    self.push(Token {
+                        token_type: Tokens::LiteralToken {
+                            kid: Literal::String,
+                            literal: dedent(&content),
+                        },
+                        pos: self.pos(start, string_stream.index + 1),
+                    });
+
+                    return Ok(());
+                }
+                // `\r\n` line endings
+                '\r' if string_stream.first() == '\n' => {}
+                // string escapes
+                '\\' => {
+                    let escape_start = string_stream.index;
+                    string_stream.next();
+
+                    if string_stream.is_eof {
+                        break;
+                    }
+                    content.push((self.escape_or_report(string_stream, escape_start), true));
+                }
+                // string content
+                c => content.push((c, false)),
+            }
+
+            string_stream.next();
+        }
+
+        Err(ZXError::SyntaxError {
+            message: "EOF while scanning multi-line string literal".to_string(),
+            pos: self.pos(start, start + 3),
+        })
+    }
+}
+
+// remove the layout of a multi-line string, see `lex_multiline_string`
+fn dedent(content: &[(char, bool)]) -> String {
+    let is_indent = |&(c, escaped): &(char, bool)| !escaped && (c == ' ' || c == '\t');
+    let mut lines = content
+        .split(|&(c, escaped)| !escaped && c == '\n')
+        .collect::<Vec<&[(char, bool)]>>();
+    let is_blank = |line: &[(char, bool)]| line.iter().all(is_indent);
+
+    // text on the opening line keeps its place and is not indented
+    let first_line = if lines.len() > 1 && is_blank(lines[0]) {
+        lines.remove(0);
+        None
+    } else {
+        Some(lines.remove(0))
+    };
+    let closing_line = match lines.last() {
+        Some(line) if is_blank(line) => lines.pop(),
+        _ => None,
+    };
+
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .chain(closing_line.iter())
+        .map(|line| line.iter().take_while(|c| is_indent(c)).count())
+        .min()
+        .unwrap_or(0);
+
+    first_line
+        .into_iter()
+        .chain(lines.into_iter().map(|line| {
+            if is_blank(line) {
+                &[][..]
+            } else {
+                &line[indent..]
+            }
+        }))
+        .map(|line| line.iter().map(|&(c, _)| c).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
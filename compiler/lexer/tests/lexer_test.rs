@@ -1,25 +1,25 @@
 #[cfg(test)]
 mod lexer_test {
     use lexer::Lexer;
-    use std::fs;
     use util::report::{Level, Report};
+    use util::source_map::SourceMap;
     use util::token::{Literal, Tokens};
 
     #[test]
     fn test_lexer() {
         let path = "./test_data/test_data.zx".to_string();
-        let source = fs::read_to_string(&path).expect("Something went wrong reading the file");
-        let mut lexer = Lexer::new(&source);
-        match lexer.lexer() {
-            Err(error) => {
-                Report {
-                    level: Level::Error,
-                    error,
-                }
-                .print(&source, &path);
+        let mut source_map = SourceMap::new();
+        let file = source_map
+            .load_file(&path)
+            .expect("Something went wrong reading the file");
+        let mut lexer = Lexer::new(source_map.source(file), file);
+        for error in lexer.lexer() {
+            Report {
+                level: Level::Error,
+                error,
             }
-            Ok(()) => {}
-        };
+            .print(&source_map);
+        }
         for token in lexer.tokens {
             match token.token_type {
                 Tokens::LiteralToken { kid, literal } => match kid {
@@ -40,6 +40,630 @@ mod lexer_test {
     }
 }
 
+#[cfg(test)]
+mod lexer_recovery_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+    use util::token::{TokenKind, Tokens};
+
+    #[test]
+    fn test_lexer_reports_every_error() {
+        let source = "var a = #\nvar b = 'ab'\nvar c = \"abc\nvar d = 1".to_string();
+        let mut lexer = Lexer::new(&source, FileId(0));
+        let errors = lexer.lexer();
+
+        // emoji, zero-width joiner and a leading combining mark
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            lexer
+                .tokens
+                .iter()
+                .filter(|token| token.is_token_type(&Tokens::ErrorToken))
+                .count(),
+            3
+        );
+        // lexing resumed after the unterminated string
+        let last_identifier = lexer
+            .tokens
+            .iter()
+            .rev()
+            .find(|token| token.is_kind(TokenKind::IdentifierToken))
+            .unwrap();
+        assert_eq!(last_identifier.get_string().unwrap(), "d");
+        assert!(lexer.tokens.last().unwrap().is_token_type(&Tokens::EOF));
+    }
+}
+
+#[cfg(test)]
+mod operator_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+
+    #[test]
+    fn test_double_char_operators() {
+        let source = "a::b == != <= >= && || -> += -= *= /= %= << >> < = / ~ =>".to_string();
+        let mut lexer = Lexer::new(&source, FileId(0));
+
+        assert!(lexer.lexer().is_empty());
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "IdentifierToken",
+                "DoubleColonToken",
+                "IdentifierToken",
+                "DoubleEqualToken",
+                "NotEqualToken",
+                "LessEqualToken",
+                "MoreEqualToken",
+                "AndToken",
+                "OrToken",
+                "ArrowToken",
+                "PlusEqualToken",
+                "MinusEqualToken",
+                "MultiplyEqualToken",
+                "SlashEqualToken",
+                "PercentEqualToken",
+                "LeftShiftToken",
+                "RightShiftToken",
+                "LessToken",
+                "EqualToken",
+                "SlashToken",
+                "TildeToken",
+                "FatArrowToken",
+                "EOF",
+            ]
+        );
+        let arrow = &lexer.tokens[9].pos;
+        assert_eq!((arrow.start, arrow.end), (23, 25));
+    }
+
+    #[test]
+    fn test_range_operators() {
+        let source = "0..10 a..=b .5..1".to_string();
+        let mut lexer = Lexer::new(&source, FileId(0));
+
+        assert!(lexer.lexer().is_empty());
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "LiteralToken",
+                "DoubleDotToken",
+                "LiteralToken",
+                "IdentifierToken",
+                "DoubleDotEqualToken",
+                "IdentifierToken",
+                "LiteralToken",
+                "DoubleDotToken",
+                "LiteralToken",
+                "EOF",
+            ]
+        );
+        let inclusive = &lexer.tokens[4].pos;
+        assert_eq!((inclusive.start, inclusive.end), (7, 10));
+    }
+}
+
+#[cfg(test)]
+mod keyword_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+    use util::token::KEYWORDS;
+
+    #[test]
+    fn test_keywords() {
+        let source = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| *keyword)
+            .chain(["fns", "variable", "_if", "iM"])
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let mut lexer = Lexer::new(&source, FileId(0));
+
+        assert!(lexer.lexer().is_empty());
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.to_string())
+            .collect::<Vec<String>>();
+        let mut expected = KEYWORDS
+            .iter()
+            .map(|(_, token)| token.to_string())
+            .collect::<Vec<String>>();
+        expected.extend(vec!["IdentifierToken".to_string(); 4]);
+        expected.push("EOF".to_string());
+        assert_eq!(kinds, expected);
+        assert_eq!((lexer.tokens[1].pos.start, lexer.tokens[1].pos.end), (3, 6));
+    }
+}
+
+#[cfg(test)]
+mod number_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+    use util::token::{Literal, Tokens};
+
+    fn lex(source: &str) -> Lexer<'_> {
+        let mut lexer = Lexer::new(source, FileId(0));
+        lexer.lexer();
+        lexer
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let lexer = lex("0xFF_ff 0b1010 0o17i64 1_000_000 .5 1.25e-9 1E3 10i64 2.0f64 3f32 1.abs");
+        assert!(lexer.errors.is_empty());
+
+        let literals = lexer
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.token_type {
+                Tokens::LiteralToken { kid, literal } => Some((kid.to_string(), literal.clone())),
+                _ => None,
+            })
+            .collect::<Vec<(String, String)>>();
+        let expected = [
+            (Literal::PositiveInteger, "0xFFff"),
+            (Literal::PositiveInteger, "0b1010"),
+            (Literal::PositiveInteger, "0o17i64"),
+            (Literal::PositiveInteger, "1000000"),
+            (Literal::Float, ".5"),
+            (Literal::Float, "1.25e-9"),
+            (Literal::Float, "1e3"),
+            (Literal::PositiveInteger, "10i64"),
+            (Literal::Float, "2.0f64"),
+            (Literal::Float, "3f32"),
+            (Literal::PositiveInteger, "1"),
+        ]
+        .iter()
+        .map(|(kid, literal)| (kid.to_string(), literal.to_string()))
+        .collect::<Vec<(String, String)>>();
+        assert_eq!(literals, expected);
+
+        // `1.abs` is an integer followed by a member access
+        let len = lexer.tokens.len();
+        assert!(lexer.tokens[len - 3].is_token_type(&Tokens::DotToken));
+    }
+
+    #[test]
+    fn test_number_errors() {
+        let lexer = lex("0b102 0x 1e+ 10u8 1.5i32");
+        assert_eq!(lexer.errors.len(), 5, "{:?}", lexer.errors);
+        assert!(format!("{:?}", lexer.errors[0]).contains("invalid digit `2` for a binary literal"));
+        assert!(format!("{:?}", lexer.errors[1]).contains("no valid digits"));
+        assert!(format!("{:?}", lexer.errors[2]).contains("exponent"));
+        assert!(format!("{:?}", lexer.errors[3]).contains("invalid suffix `u8`"));
+        assert!(format!("{:?}", lexer.errors[4]).contains("for float literal"));
+    }
+}
+
+#[cfg(test)]
+mod escapes_test {
+    use lexer::Lexer;
+    use util::error::ZXError;
+    use util::source_map::FileId;
+    use util::token::Tokens;
+
+    fn literals(lexer: &Lexer) -> Vec<String> {
+        lexer
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.token_type {
+                Tokens::LiteralToken { literal, .. } => Some(literal.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_escapes() {
+        let source = r#""a\\b\"c\'\x41\u{4e2d}\u{1F600}" '中' '\u{61}' '\''"#.to_string();
+        let mut lexer = Lexer::new(&source, FileId(0));
+
+        assert!(lexer.lexer().is_empty(), "{:?}", lexer.errors);
+        assert_eq!(literals(&lexer), vec!["a\\b\"c'A中😀", "中", "a", "'"]);
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let source = r#""\q \x8f \x4 \u00e9 \u{} \u{d800} \u{1234567}" 'ab' 'é'"#.to_string();
+        let mut lexer = Lexer::new(&source, FileId(0));
+        let errors = lexer.lexer();
+
+        let messages = errors
+            .iter()
+            .map(|error| match error {
+                ZXError::SyntaxError { message, pos } => (message.clone(), pos.start, pos.end),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<(String, usize, usize)>>();
+        assert_eq!(messages.len(), 8, "{:?}", messages);
+        assert_eq!(
+            messages[0],
+            ("unknown character escape: `q`".to_string(), 1, 3)
+        );
+        assert!(messages[1].0.starts_with("out of range hex escape"));
+        assert_eq!(messages[2].0, "numeric character escape is too short");
+        assert!(messages[3]
+            .0
+            .starts_with("incorrect unicode escape sequence"));
+        assert_eq!(messages[4].0, "empty unicode escape");
+        assert!(messages[5]
+            .0
+            .starts_with("invalid unicode character escape"));
+        assert!(messages[6].0.starts_with("overlong unicode escape"));
+        assert_eq!(
+            messages[7].0,
+            "character literal may only contain one codepoint"
+        );
+        // the string is still produced with replacement characters
+        assert_eq!(literals(&lexer)[1], "é");
+    }
+}
+
+#[cfg(test)]
+mod interpolation_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+
+    fn kinds(source: &str) -> (Vec<String>, Lexer<'_>) {
+        let mut lexer = Lexer::new(source, FileId(0));
+        lexer.lexer();
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.to_string())
+            .collect();
+        (kinds, lexer)
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let (kinds, lexer) = kinds(r#""hi ${name}, ${f("}")} \${x}""#);
+
+        assert!(lexer.errors.is_empty(), "{:?}", lexer.errors);
+        assert_eq!(
+            kinds,
+            vec![
+                "TemplateStartToken",
+                "LiteralToken",
+                "InterpolationStartToken",
+                "IdentifierToken",
+                "InterpolationEndToken",
+                "LiteralToken",
+                "InterpolationStartToken",
+                "IdentifierToken",
+                "LeftParenthesesToken",
+                "LiteralToken",
+                "RightParenthesesToken",
+                "InterpolationEndToken",
+                "LiteralToken",
+                "TemplateEndToken",
+                "EOF",
+            ]
+        );
+        assert_eq!(lexer.tokens[1].get_string().unwrap(), "hi ");
+        assert_eq!(lexer.tokens[12].get_string().unwrap(), " ${x}");
+        assert_eq!(
+            (lexer.tokens[3].pos.start, lexer.tokens[3].pos.end),
+            (6, 10)
+        );
+    }
+
+    #[test]
+    fn test_unclosed_interpolation() {
+        let (kinds, lexer) = kinds(r#""a ${b"#);
+
+        assert_eq!(lexer.errors.len(), 1);
+        assert_eq!(kinds.last().unwrap(), "EOF");
+        assert!(kinds.contains(&"TemplateEndToken".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod raw_string_test {
+    use lexer::Lexer;
+    use util::error::ZXError;
+    use util::source_map::FileId;
+    use util::token::{Literal, Tokens};
+
+    fn strings(source: &str) -> Vec<(String, usize, usize)> {
+        let mut lexer = Lexer::new(source, FileId(0));
+
+        assert!(lexer.lexer().is_empty(), "{:?}", lexer.errors);
+        lexer
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.token_type {
+                Tokens::LiteralToken {
+                    kid: Literal::String,
+                    literal,
+                } => Some((literal.clone(), token.pos.start, token.pos.end)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let source = r###"r"C:\path\n" r#"say "hi""# r##"a"#b"## r"${x}""###;
+
+        assert_eq!(
+            strings(source),
+            vec![
+                (r"C:\path\n".to_string(), 0, 12),
+                (r#"say "hi""#.to_string(), 13, 26),
+                (r##"a"#b"##.to_string(), 27, 38),
+                ("${x}".to_string(), 39, 46),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let source = "var a = \"\"\"\n    SELECT *\n\n      FROM users\\n\n    \"\"\"\nvar b = \"\"\"one \"line\" here\"\"\"";
+
+        assert_eq!(
+            strings(source),
+            vec![
+                ("SELECT *\n\n  FROM users\n".to_string(), 8, 52),
+                ("one \"line\" here".to_string(), 61, 82),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_raw_strings() {
+        let mut lexer = Lexer::new("r#\"abc\" \"\"\"\n", FileId(0));
+        let errors = lexer.lexer();
+
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            ZXError::SyntaxError { message, pos } => {
+                assert_eq!(message, "EOF while scanning raw string literal");
+                assert_eq!((pos.start, pos.end), (0, 3));
+            }
+            error => panic!("unexpected {:?}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod doc_comment_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+    use util::token::Tokens;
+
+    #[test]
+    fn test_doc_comments() {
+        let source = "/// line doc\n//// plain\n// plain\n/**\n * block\n *   doc\n */\n/**/ /*** plain */ a";
+        let mut lexer = Lexer::new(source, FileId(0));
+
+        assert!(lexer.lexer().is_empty(), "{:?}", lexer.errors);
+        let docs = lexer
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.token_type {
+                Tokens::DocCommentToken { literal } => {
+                    Some((literal.clone(), token.pos.start, token.pos.end))
+                }
+                _ => None,
+            })
+            .collect::<Vec<(String, usize, usize)>>();
+        assert_eq!(
+            docs,
+            vec![
+                ("line doc".to_string(), 0, 12),
+                ("block\n  doc".to_string(), 33, 58),
+            ]
+        );
+        // two doc comments, two line separators, `a` and `EOF`
+        assert_eq!(lexer.tokens.len(), 6);
+    }
+}
+
+#[cfg(test)]
+mod block_comment_test {
+    use lexer::Lexer;
+    use util::error::ZXError;
+    use util::source_map::FileId;
+    use util::token::Tokens;
+
+    #[test]
+    fn test_nested_block_comments() {
+        let source = "a /* outer /* inner */ still comment */ b /** doc /* code */ */ c";
+        let mut lexer = Lexer::new(source, FileId(0));
+
+        assert!(lexer.lexer().is_empty(), "{:?}", lexer.errors);
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| match &token.token_type {
+                Tokens::IdentifierToken { literal } => literal.to_string(),
+                Tokens::DocCommentToken { literal } => format!("doc: {}", literal),
+                token_type => token_type.to_string(),
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(kinds, vec!["a", "b", "doc: doc /* code */", "c", "EOF"]);
+    }
+
+    #[test]
+    fn test_unclosed_nested_block_comment() {
+        let source = "/* outer /* closed */ /* inner\n";
+        let mut lexer = Lexer::new(source, FileId(0));
+        let errors = lexer.lexer();
+
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            ZXError::SyntaxError { message, pos } => {
+                assert_eq!(message, "EOL while scanning comment");
+                assert_eq!((pos.start, pos.end), (22, 24));
+            }
+            error => panic!("unexpected {:?}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod line_separator_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+
+    #[test]
+    fn test_line_separators() {
+        let source = "\n\na // comment\n\n  b\n\"c\nd";
+        let mut lexer = Lexer::new(source, FileId(0));
+
+        assert_eq!(lexer.lexer().len(), 1);
+        let kinds = lexer
+            .tokens
+            .iter()
+            .map(|token| token.token_type.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "IdentifierToken",
+                "LineSeparatorToken",
+                "IdentifierToken",
+                "LineSeparatorToken",
+                "ErrorToken",
+                "LineSeparatorToken",
+                "IdentifierToken",
+                "EOF",
+            ]
+        );
+        assert_eq!(
+            (lexer.tokens[1].pos.start, lexer.tokens[1].pos.end),
+            (14, 15)
+        );
+    }
+}
+
+#[cfg(test)]
+mod identifier_test {
+    use lexer::Lexer;
+    use util::error::ZXError;
+    use util::source_map::FileId;
+    use util::token::Tokens;
+
+    fn identifiers(lexer: &Lexer) -> Vec<String> {
+        lexer
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.token_type {
+                Tokens::IdentifierToken { literal } => Some(literal.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let source = "變數 _名前 x2 \u{e9}t\u{e9} e\u{301}te\u{301}";
+        let mut lexer = Lexer::new(source, FileId(0));
+
+        assert!(lexer.lexer().is_empty());
+        assert!(lexer.warnings.is_empty());
+        assert_eq!(
+            identifiers(&lexer),
+            vec!["變數", "_名前", "x2", "\u{e9}t\u{e9}", "\u{e9}t\u{e9}"]
+        );
+        assert_eq!(
+            lexer.tokens[3].get_symbol().unwrap(),
+            lexer.tokens[4].get_symbol().unwrap()
+        );
+        // spans cover the source text, not the normalized name
+        assert_eq!(
+            (lexer.tokens[4].pos.start, lexer.tokens[4].pos.end),
+            (24, 31)
+        );
+    }
+
+    #[test]
+    fn test_invalid_identifier_characters() {
+        let mut lexer = Lexer::new("a\u{1F600}b c\u{200D}d \u{301}e", FileId(0));
+        let errors = lexer.lexer();
+
+        // emoji, zero-width joiner and a leading combining mark
+        assert_eq!(errors.len(), 3);
+        assert_eq!(identifiers(&lexer), vec!["a", "b", "c", "d", "e"]);
+        match &errors[0] {
+            ZXError::SyntaxError { message, pos } => {
+                assert_eq!(message, "unexpected character `\u{1F600}` (\\u{1f600})");
+                assert_eq!((pos.start, pos.end), (1, 5));
+            }
+            error => panic!("unexpected {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_mixed_script_confusables() {
+        // a Cyrillic `а` in a Latin name
+        let mut lexer = Lexer::new("p\u{430}ypal paypal 用户name", FileId(0));
+
+        assert!(lexer.lexer().is_empty());
+        assert_eq!(lexer.warnings.len(), 1);
+        match &lexer.warnings[0] {
+            ZXError::Warning { pos, .. } => assert_eq!((pos.start, pos.end), (0, 7)),
+            warning => panic!("unexpected {:?}", warning),
+        }
+    }
+}
+
+#[cfg(test)]
+mod token_iterator_test {
+    use lexer::Lexer;
+    use util::source_map::FileId;
+    use util::token::TokenKind;
+
+    #[test]
+    fn test_token_iterator() {
+        let mut lexer = Lexer::new("a + #\nb", FileId(0));
+
+        assert!(matches!(lexer.peek_nth(2), Some(Err(_))));
+        assert!(lexer.tokens.is_empty());
+
+        let kinds = lexer
+            .map(|item| item.map(|token| token.kind()))
+            .collect::<Vec<_>>();
+
+        assert!(kinds[2].is_err());
+        assert_eq!(
+            kinds
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::IdentifierToken,
+                TokenKind::PlusToken,
+                TokenKind::ErrorToken,
+                TokenKind::LineSeparatorToken,
+                TokenKind::IdentifierToken,
+                TokenKind::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partial_input() {
+        // only the tokens that were asked for are lexed
+        let mut lexer = Lexer::new("var a = \"unterminated", FileId(0));
+
+        assert_eq!(lexer.next().unwrap().unwrap().kind(), TokenKind::VarToken);
+        assert_eq!(lexer.next().unwrap().unwrap().get_string().unwrap(), "a");
+    }
+}
+
 #[cfg(test)]
 mod file_stream_test {
     use lexer::file_stream::StringStream;
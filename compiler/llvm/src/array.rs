@@ -0,0 +1,167 @@
+use crate::Builder;
+use inkwell::module::Linkage;
+use inkwell::types::BasicType;
+use inkwell::values::{BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::{AddressSpace, IntPredicate};
+use util::token::Position;
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// A new array holding `elements`, the length is stored in front of
+    /// them
+    pub fn build_array(
+        &self,
+        element_type: &ZXTyped,
+        elements: &[BasicValueEnum<'a>],
+    ) -> Option<PointerValue<'a>> {
+        let array_type = self.structs.array_type(self.context, element_type)?;
+        let element_size = match element_type {
+            ZXTyped::Void => self.context.i8_type().size_of(),
+            element_type => self
+                .structs
+                .field_type(self.context, element_type)?
+                .size_of()?,
+        };
+        let len = self
+            .context
+            .i64_type()
+            .const_int(elements.len() as u64, false);
+        let size = self.builder.build_int_add(
+            array_type.size_of()?,
+            self.builder
+                .build_int_mul(element_size, len, "elements_size"),
+            "array_size",
+        );
+        let memory = self
+            .builder
+            .build_array_malloc(self.context.i8_type(), size, "array_memory")
+            .ok()?;
+        let array = self.builder.build_pointer_cast(
+            memory,
+            array_type.ptr_type(AddressSpace::Generic),
+            "array",
+        );
+
+        let len_field = self.builder.build_struct_gep(array, 0, "len_field").ok()?;
+        self.builder.build_store(len_field, len);
+        for (index, element) in elements.iter().enumerate() {
+            let index = self.context.i64_type().const_int(index as u64, false);
+            self.builder
+                .build_store(self.element_field(array, index), *element);
+        }
+
+        Some(array)
+    }
+
+    /// The number of elements of `array` as an `Int`
+    pub fn build_len(&self, array: PointerValue<'a>) -> IntValue<'a> {
+        let len = self.load_len(array);
+
+        self.builder
+            .build_int_truncate(len, self.context.i32_type(), "len")
+    }
+
+    /// Pointer to the element at `index` of `array`. An index out of bounds
+    /// prints the length, the index and `pos` and exits the program.
+    pub fn element_pointer(
+        &self,
+        array: PointerValue<'a>,
+        index: IntValue<'a>,
+        pos: &Position,
+        function: FunctionValue<'a>,
+    ) -> PointerValue<'a> {
+        let i64_type = self.context.i64_type();
+        let len = self.load_len(array);
+        let index = self
+            .builder
+            .build_int_s_extend_or_bit_cast(index, i64_type, "index");
+        // a negative index is a large unsigned one
+        let in_bounds = self
+            .builder
+            .build_int_compare(IntPredicate::ULT, index, len, "in_bounds");
+        let out_of_bounds_block = self
+            .context
+            .append_basic_block(function, "index_out_of_bounds");
+        let in_bounds_block = self.context.append_basic_block(function, "index_in_bounds");
+        self.builder
+            .build_conditional_branch(in_bounds, in_bounds_block, out_of_bounds_block);
+
+        self.builder.position_at_end(out_of_bounds_block);
+        let message = self
+            .builder
+            .build_global_string_ptr(&self.out_of_bounds_message(pos), "out_of_bounds");
+        self.builder.build_call(
+            self.printf(),
+            &[message.as_pointer_value().into(), len.into(), index.into()],
+            "",
+        );
+        self.builder.build_call(
+            self.exit(),
+            &[self.context.i32_type().const_int(1, false).into()],
+            "",
+        );
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(in_bounds_block);
+        self.element_field(array, index)
+    }
+
+    pub fn load_len(&self, array: PointerValue<'a>) -> IntValue<'a> {
+        let len_field = self
+            .builder
+            .build_struct_gep(array, 0, "len_field")
+            .unwrap();
+
+        self.builder.build_load(len_field, "len").into_int_value()
+    }
+
+    // the element at `index` without a bounds check
+    pub fn element_field(&self, array: PointerValue<'a>, index: IntValue<'a>) -> PointerValue<'a> {
+        let elements = self.builder.build_struct_gep(array, 1, "elements").unwrap();
+
+        unsafe {
+            self.builder.build_in_bounds_gep(
+                elements,
+                &[self.context.i64_type().const_zero(), index],
+                "element",
+            )
+        }
+    }
+
+    // `main.zx:3:5: index out of bounds: ...` with the position of the
+    // indexed expression, the `%` in the path are not formats
+    fn out_of_bounds_message(&self, pos: &Position) -> String {
+        let (start, _) = self.source_map.span(pos);
+
+        format!(
+            "{}:{}:{}: index out of bounds: the len is %lld but the index is %lld\n",
+            self.source_map.file(pos.file).path.replace('%', "%%"),
+            start.line + 1,
+            start.column + 1
+        )
+    }
+
+    fn printf(&self) -> FunctionValue<'a> {
+        self.module.get_function("printf").unwrap_or_else(|| {
+            let format = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+            self.module.add_function(
+                "printf",
+                self.context.i32_type().fn_type(&[format.into()], true),
+                Some(Linkage::External),
+            )
+        })
+    }
+
+    fn exit(&self) -> FunctionValue<'a> {
+        self.module.get_function("exit").unwrap_or_else(|| {
+            self.module.add_function(
+                "exit",
+                self.context
+                    .void_type()
+                    .fn_type(&[self.context.i32_type().into()], false),
+                Some(Linkage::External),
+            )
+        })
+    }
+}
@@ -1,15 +1,16 @@
 use std::sync::Mutex;
 
-use inkwell::values::FunctionValue;
-use util::bytecode::BytecodeType;
+use inkwell::values::{BasicValueEnum, FunctionValue};
+use util::bytecode::{BytecodeType, BytecodeValue};
+use util::zx_type::ZXTyped;
 
 use crate::Builder;
 
-impl Builder<'_> {
+impl<'a> Builder<'a> {
     pub fn bytecodes(
         &self,
         bytecode: &BytecodeType,
-        function: FunctionValue,
+        function: FunctionValue<'a>,
         index: &Mutex<usize>,
     ) {
         match &bytecode {
@@ -19,15 +20,238 @@ impl Builder<'_> {
             BytecodeType::Box { bytecodes } => bytecodes
                 .iter()
                 .for_each(|bytecode| self.bytecodes(bytecode, function, index)),
+            BytecodeType::IndexStore {
+                array,
+                index: element_index,
+                value,
+                pos,
+                ..
+            } => {
+                if let (Some(array), Some(element_index)) = (
+                    self.required_value(array, function),
+                    self.required_value(element_index, function),
+                ) {
+                    let element = self.element_pointer(
+                        array.into_pointer_value(),
+                        element_index.into_int_value(),
+                        pos,
+                        function,
+                    );
+                    if let Some(value) = self.required_value(value, function) {
+                        self.builder.build_store(element, value);
+                    }
+                }
+            }
+            BytecodeType::Store { path, value } => {
+                if let Some(value) = self.required_value(value, function) {
+                    let variable = *self
+                        .variables
+                        .borrow_mut()
+                        .entry(path.clone())
+                        .or_insert_with(|| self.builder.build_alloca(value.get_type(), path));
+                    self.builder.build_store(variable, value);
+                }
+            }
+            BytecodeType::FieldStore {
+                object,
+                index: field_index,
+                value,
+                ..
+            } => self.store_field(object, *field_index, value, function),
+            BytecodeType::ForLoop { .. } => self.build_for_loop(bytecode, function, index),
+            bytecode if has_value(bytecode) => {
+                self.required_value(bytecode, function);
+            }
+            // calls and matches of `Void`
+            BytecodeType::Call { .. }
+            | BytecodeType::CallMethod { .. }
+            | BytecodeType::CallValue { .. }
+            | BytecodeType::Match { .. } => {
+                self.value(bytecode, function);
+            }
             _ => {}
         }
     }
 
+    /// The value of `bytecode` where one is needed, a bytecode without a
+    /// lowering to a value is reported
+    pub fn required_value(
+        &self,
+        bytecode: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let value = self.value(bytecode, function);
+        if value.is_none() {
+            self.internal_error(format!(
+                "cannot build the value of `{}`",
+                bytecode_name(bytecode)
+            ));
+        }
+
+        value
+    }
+
+    /// The value of `bytecode`, `None` for bytecodes without a lowering to
+    /// a value
+    pub fn value(
+        &self,
+        bytecode: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        match bytecode {
+            BytecodeType::Value { value } => Some(match value {
+                BytecodeValue::Int { value } => self
+                    .context
+                    .i32_type()
+                    .const_int(*value as u64, true)
+                    .into(),
+                BytecodeValue::Long { value } => self
+                    .context
+                    .i64_type()
+                    .const_int(*value as u64, true)
+                    .into(),
+                BytecodeValue::Float { value } => {
+                    self.context.f32_type().const_float(*value as f64).into()
+                }
+                BytecodeValue::Double { value } => {
+                    self.context.f64_type().const_float(*value).into()
+                }
+                BytecodeValue::Bool { value } => self
+                    .context
+                    .bool_type()
+                    .const_int(*value as u64, false)
+                    .into(),
+                BytecodeValue::Char { value } => self
+                    .context
+                    .i8_type()
+                    .const_int(*value as u64, false)
+                    .into(),
+                BytecodeValue::String { value } => self
+                    .builder
+                    .build_global_string_ptr(value, "string")
+                    .as_pointer_value()
+                    .into(),
+                BytecodeValue::Param { index } => function.get_nth_param(*index as u32)?,
+                BytecodeValue::PointerValue { .. } => return None,
+            }),
+            BytecodeType::ToStr { value, value_type } => {
+                self.build_to_str(value, value_type, function)
+            }
+            BytecodeType::Concat { left, right } => self.build_concat(left, right, function),
+            BytecodeType::Binary {
+                operator,
+                operand_type,
+                left,
+                right,
+            } => self.build_binary(operator, operand_type, left, right, function),
+            BytecodeType::Logical {
+                operator,
+                left,
+                right,
+            } => self.build_logical(operator, left, right, function),
+            BytecodeType::Unary {
+                operator, value, ..
+            } => self.build_unary(operator, value, function),
+            BytecodeType::Call {
+                path,
+                type_arguments,
+                arguments,
+                ..
+            } => self.call_function(path, type_arguments, arguments, function),
+            BytecodeType::CallMethod {
+                object,
+                class,
+                name,
+                direct,
+                arguments,
+                ..
+            } => self.call_method(object, class, name, *direct, arguments, function),
+            BytecodeType::New { name, path } => self.build_new(name, path, function),
+            BytecodeType::FieldLoad { object, index, .. } => {
+                self.load_field(object, *index, function)
+            }
+            BytecodeType::Array {
+                element_type,
+                elements,
+            } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.value(element, function))
+                    .collect::<Option<Vec<BasicValueEnum>>>()?;
+
+                Some(self.build_array(element_type, &elements)?.into())
+            }
+            BytecodeType::Index {
+                array, index, pos, ..
+            } => {
+                let array = self.value(array, function)?.into_pointer_value();
+                let index = self.value(index, function)?.into_int_value();
+                let element = self.element_pointer(array, index, pos, function);
+
+                Some(self.builder.build_load(element, "element"))
+            }
+            BytecodeType::Len { array } => {
+                let array = self.value(array, function)?.into_pointer_value();
+
+                Some(self.build_len(array).into())
+            }
+            BytecodeType::Range {
+                element_type,
+                start,
+                end,
+                inclusive,
+            } => {
+                let start = self.value(start, function)?;
+                let end = self.value(end, function)?;
+                let inclusive = self.context.bool_type().const_int(*inclusive as u64, false);
+                let range = self
+                    .structs
+                    .range_type(self.context, element_type)?
+                    .get_undef();
+                let range = [start, end, inclusive.into()].iter().enumerate().try_fold(
+                    range,
+                    |range, (field, value)| {
+                        Some(
+                            self.builder
+                                .build_insert_value(range, *value, field as u32, "range")?
+                                .into_struct_value(),
+                        )
+                    },
+                )?;
+
+                Some(range.into())
+            }
+            BytecodeType::Load { path } => {
+                let variable = *self.variables.borrow().get(path)?;
+
+                Some(self.builder.build_load(variable, path))
+            }
+            BytecodeType::FunctionRef {
+                path,
+                function_type,
+            } => self.function_ref(path, function_type),
+            BytecodeType::Closure { .. } => self.build_closure(bytecode, function),
+            BytecodeType::CallValue {
+                function: callee,
+                function_type,
+                arguments,
+            } => self.call_value(callee, function_type, arguments, function),
+            BytecodeType::EnumValue {
+                name,
+                variant,
+                tag,
+                payload,
+            } => self.build_enum_value(name, variant, *tag, payload, function),
+            BytecodeType::Match { .. } => self.build_match(bytecode, function),
+            _ => None,
+        }
+    }
+
     pub fn block_statement(
         &self,
         name: &String,
         bytecodes: &Vec<BytecodeType>,
-        function: FunctionValue,
+        function: FunctionValue<'a>,
         index: &Mutex<usize>,
     ) {
         let block = self.context.append_basic_block(function, name);
@@ -37,3 +261,46 @@ impl Builder<'_> {
         });
     }
 }
+
+// whether `bytecode` is an expression with a value, calls and matches of
+// `Void` have none
+fn has_value(bytecode: &BytecodeType) -> bool {
+    match bytecode {
+        BytecodeType::Call { ret_type, .. } | BytecodeType::CallMethod { ret_type, .. } => {
+            *ret_type != ZXTyped::Void
+        }
+        BytecodeType::CallValue {
+            function_type: ZXTyped::Function { return_type, .. },
+            ..
+        } => **return_type != ZXTyped::Void,
+        BytecodeType::Match { result_type, .. } => *result_type != ZXTyped::Void,
+        BytecodeType::Value { .. }
+        | BytecodeType::Load { .. }
+        | BytecodeType::Binary { .. }
+        | BytecodeType::Unary { .. }
+        | BytecodeType::Logical { .. }
+        | BytecodeType::ToStr { .. }
+        | BytecodeType::Concat { .. }
+        | BytecodeType::Array { .. }
+        | BytecodeType::Index { .. }
+        | BytecodeType::Len { .. }
+        | BytecodeType::Range { .. }
+        | BytecodeType::FunctionRef { .. }
+        | BytecodeType::Closure { .. }
+        | BytecodeType::EnumValue { .. }
+        | BytecodeType::New { .. }
+        | BytecodeType::FieldLoad { .. } => true,
+        _ => false,
+    }
+}
+
+// `Binary` for a `Binary` bytecode, internal errors name the bytecode
+fn bytecode_name(bytecode: &BytecodeType) -> String {
+    let debug = format!("{:?}", bytecode);
+
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
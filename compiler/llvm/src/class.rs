@@ -0,0 +1,363 @@
+use std::sync::Mutex;
+
+use crate::Builder;
+use inkwell::types::{BasicMetadataTypeEnum, FunctionType};
+use inkwell::values::{
+    BasicMetadataValueEnum, BasicValueEnum, CallableValue, FunctionValue, GlobalValue, PointerValue,
+};
+use inkwell::AddressSpace;
+use util::bytecode::BytecodeType;
+use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// Build the methods declared by `class`, inherited methods are built
+    /// with the class declaring them
+    pub fn build_class(&self, class: &Scope) {
+        let (members, base) = match &class.scope_type {
+            ScopeType::DefClass { members, base, .. } => (members, base),
+            _ => return,
+        };
+
+        for member in &members.scopes {
+            if member.path == format!("{}${}", class.path, member.name) {
+                if let ScopeType::DefFunction {
+                    parameters,
+                    block,
+                    return_type,
+                    ..
+                } = &member.scope_type
+                {
+                    self.build_method(class, *base, &member.path, parameters, return_type, block);
+                }
+            }
+        }
+    }
+
+    // a method takes the object it is called on as `this` before its
+    // parameters, `super` is the same object as a value of the base class
+    fn build_method(
+        &self,
+        class: &Scope,
+        base: Option<Symbol>,
+        path: &str,
+        parameters: &[Scope],
+        return_type: &ZXTyped,
+        block: &BytecodeType,
+    ) {
+        let method = match self.declare_method(path, parameters, return_type) {
+            Some(method) => method,
+            None => {
+                return self.internal_error(format!(
+                    "a parameter or the return type of `{}` has no layout",
+                    path
+                ))
+            }
+        };
+        let basic_block = self.context.append_basic_block(method, "entry");
+        self.builder.position_at_end(basic_block);
+
+        // the methods of an instance of a generic class use `this` of the
+        // generic class
+        let class_path = class.path.split('<').next().unwrap_or(&class.path);
+        let object_type = |name: &str| {
+            self.structs
+                .find(&name.to_string())
+                .ptr_type(AddressSpace::Generic)
+        };
+        if let Some(this) = method.get_first_param() {
+            let this = this.into_pointer_value();
+            self.store_variable(
+                &format!("{}$this", class_path),
+                self.builder
+                    .build_pointer_cast(this, object_type(class.name.as_str()), "this")
+                    .into(),
+            );
+            if let Some(base) = base {
+                self.store_variable(
+                    &format!("{}$super", class_path),
+                    self.builder
+                        .build_pointer_cast(this, object_type(base.as_str()), "super")
+                        .into(),
+                );
+            }
+        }
+        for (index, parameter) in parameters.iter().enumerate() {
+            if let Some(value) = method.get_nth_param(index as u32 + 1) {
+                self.store_variable(&parameter.path, value);
+            }
+        }
+
+        let index = Mutex::new(parameters.len() + 2);
+        self.build_body(path, block, return_type, method, &index);
+    }
+
+    // the method at `path`, added without a body when it is not built yet
+    fn declare_method(
+        &self,
+        path: &str,
+        parameters: &[Scope],
+        return_type: &ZXTyped,
+    ) -> Option<FunctionValue<'a>> {
+        if let Some(method) = self.module.get_function(path) {
+            return Some(method);
+        }
+
+        Some(
+            self.module
+                .add_function(path, self.method_type(parameters, return_type)?, None),
+        )
+    }
+
+    // `ret (i8*, parameters...)`, an overriding method has the type of the
+    // method it overrides
+    fn method_type(&self, parameters: &[Scope], return_type: &ZXTyped) -> Option<FunctionType<'a>> {
+        let this = self
+            .context
+            .i8_type()
+            .ptr_type(AddressSpace::Generic)
+            .into();
+        let parameters = std::iter::once(this)
+            .chain(self.function_parameters_type(parameters.to_vec())?)
+            .collect::<Vec<BasicMetadataTypeEnum>>();
+
+        self.function_type(return_type, &parameters)
+    }
+
+    /// A constant array of the methods of the class at `path`. An
+    /// overriding method is in the slot of the method it overrides, so a
+    /// method has the same slot in every class inheriting it.
+    pub fn build_vtable(&self, path: &str, members: &Scopes) {
+        let i8_ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let methods = methods(members)
+            .map(|method| match self.module.get_function(&method.path) {
+                Some(function) => function
+                    .as_global_value()
+                    .as_pointer_value()
+                    .const_cast(i8_ptr),
+                None => i8_ptr.const_null(),
+            })
+            .collect::<Vec<PointerValue>>();
+        let vtable = self.vtable(path, members);
+
+        vtable.set_initializer(&i8_ptr.const_array(&methods));
+        vtable.set_constant(true);
+    }
+
+    // the vtable of the class at `path`, objects can be created before it is
+    // built
+    fn vtable(&self, path: &str, members: &Scopes) -> GlobalValue<'a> {
+        let name = format!("{}$vtable", path);
+
+        self.module.get_global(&name).unwrap_or_else(|| {
+            self.module.add_global(
+                self.context
+                    .i8_type()
+                    .ptr_type(AddressSpace::Generic)
+                    .array_type(methods(members).count() as u32),
+                None,
+                &name,
+            )
+        })
+    }
+
+    /// A `New` bytecode, an object of the class `name` declared at `path`
+    /// pointing to the vtable of the class. The fields with a value are set.
+    pub fn build_new(
+        &self,
+        name: &str,
+        path: &str,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let members = self.class_members(path)?;
+        let object = self
+            .builder
+            .build_malloc(self.structs.find(&name.to_string()), "object")
+            .ok()?;
+        let vtable_field = self
+            .builder
+            .build_struct_gep(object, 0, "vtable_field")
+            .ok()?;
+        let vtable = self.builder.build_pointer_cast(
+            self.vtable(path, members).as_pointer_value(),
+            self.context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .ptr_type(AddressSpace::Generic),
+            "vtable",
+        );
+        self.builder.build_store(vtable_field, vtable);
+
+        let fields = members
+            .scopes
+            .iter()
+            .filter_map(|member| match &member.scope_type {
+                ScopeType::DefVariable { value, .. } => Some(value),
+                _ => None,
+            });
+        for (index, value) in fields.enumerate() {
+            if let Some(value) = value
+                .as_ref()
+                .and_then(|value| self.required_value(value, function))
+            {
+                let field = self
+                    .builder
+                    .build_struct_gep(object, index as u32 + 1, "field")
+                    .ok()?;
+                self.builder.build_store(field, value);
+            }
+        }
+
+        Some(object.into())
+    }
+
+    /// A `FieldLoad` bytecode
+    pub fn load_field(
+        &self,
+        object: &BytecodeType,
+        index: usize,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let field = self.field_pointer(object, index, function)?;
+
+        Some(self.builder.build_load(field, "field"))
+    }
+
+    /// A `FieldStore` bytecode
+    pub fn store_field(
+        &self,
+        object: &BytecodeType,
+        index: usize,
+        value: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) {
+        if let (Some(field), Some(value)) = (
+            self.field_pointer(object, index, function),
+            self.required_value(value, function),
+        ) {
+            self.builder.build_store(field, value);
+        }
+    }
+
+    // the field `index` of `object`, the vtable comes before the fields
+    fn field_pointer(
+        &self,
+        object: &BytecodeType,
+        index: usize,
+        function: FunctionValue<'a>,
+    ) -> Option<PointerValue<'a>> {
+        let object = self.required_value(object, function)?.into_pointer_value();
+
+        self.builder
+            .build_struct_gep(object, index as u32 + 1, "field")
+            .ok()
+    }
+
+    /// A `CallMethod` bytecode, the object is passed as `this`
+    pub fn call_method(
+        &self,
+        object: &BytecodeType,
+        class: &str,
+        name: &str,
+        direct: bool,
+        arguments: &[BytecodeType],
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let members = self.class_members(class)?;
+        let method = members
+            .scopes
+            .iter()
+            .find(|member| member.name.as_str() == name)?;
+        let (parameters, return_type) = match &method.scope_type {
+            ScopeType::DefFunction {
+                parameters,
+                return_type,
+                ..
+            } => (parameters, return_type),
+            _ => return None,
+        };
+        let object = self.value(object, function)?.into_pointer_value();
+        let this = self.builder.build_pointer_cast(
+            object,
+            self.context.i8_type().ptr_type(AddressSpace::Generic),
+            "this",
+        );
+        let arguments = std::iter::once(Some(this.into()))
+            .chain(
+                arguments
+                    .iter()
+                    .map(|argument| self.value(argument, function)),
+            )
+            .map(|argument| argument.map(BasicMetadataValueEnum::from))
+            .collect::<Option<Vec<BasicMetadataValueEnum>>>()?;
+        let callee = if direct {
+            CallableValue::from(self.declare_method(&method.path, parameters, return_type)?)
+        } else {
+            let method = self.builder.build_pointer_cast(
+                self.virtual_method(object, method_slot(members, method.name)?)?,
+                self.method_type(parameters, return_type)?
+                    .ptr_type(AddressSpace::Generic),
+                "method",
+            );
+            CallableValue::try_from(method).ok()?
+        };
+
+        self.builder
+            .build_call(callee, &arguments, "call")
+            .try_as_basic_value()
+            .left()
+    }
+
+    /// Load the method in `slot` of the vtable of `object`, a pointer to a
+    /// class struct. This is the method of the class the object was created
+    /// as, not of the type `object` is used as.
+    pub fn virtual_method(&self, object: PointerValue<'a>, slot: u32) -> Option<PointerValue<'a>> {
+        let vtable_field = self
+            .builder
+            .build_struct_gep(object, 0, "vtable_field")
+            .ok()?;
+        let vtable = self
+            .builder
+            .build_load(vtable_field, "vtable")
+            .into_pointer_value();
+        let method = unsafe {
+            self.builder.build_in_bounds_gep(
+                vtable,
+                &[self.context.i32_type().const_int(slot as u64, false)],
+                "method_field",
+            )
+        };
+
+        Some(
+            self.builder
+                .build_load(method, "method")
+                .into_pointer_value(),
+        )
+    }
+
+    // the members of the class at `path`
+    fn class_members(&self, path: &str) -> Option<&Scopes> {
+        self.scopes
+            .scopes
+            .iter()
+            .find_map(|scope| match &scope.scope_type {
+                ScopeType::DefClass { members, .. } if scope.path == path => Some(members),
+                _ => None,
+            })
+    }
+}
+
+// vtable slot of the method `name`
+fn method_slot(members: &Scopes, name: Symbol) -> Option<u32> {
+    methods(members)
+        .position(|method| method.name == name)
+        .map(|slot| slot as u32)
+}
+
+fn methods(members: &Scopes) -> impl Iterator<Item = &Scope> {
+    members
+        .scopes
+        .iter()
+        .filter(|member| matches!(member.scope_type, ScopeType::DefFunction { .. }))
+}
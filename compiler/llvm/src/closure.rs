@@ -0,0 +1,306 @@
+use std::sync::Mutex;
+
+use crate::generic::find_path;
+use crate::Builder;
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, FunctionType, StructType};
+use inkwell::values::{
+    BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue,
+};
+use inkwell::AddressSpace;
+use util::bytecode::{BytecodeType, Variable};
+use util::scope::ScopeType;
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// A `Closure` bytecode. The code takes the environment before the
+    /// parameters, the captured values are copied into a new environment.
+    pub fn build_closure(
+        &self,
+        closure: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let (path, parameters, return_type, captures, body) = match closure {
+            BytecodeType::Closure {
+                path,
+                parameters,
+                return_type,
+                captures,
+                body,
+            } => (path, parameters, return_type, captures, body),
+            _ => return None,
+        };
+        let environment_type = self.context.struct_type(
+            &captures
+                .iter()
+                .map(|capture| self.structs.field_type(self.context, &capture.var_type))
+                .collect::<Option<Vec<_>>>()?,
+            false,
+        );
+        let values = captures
+            .iter()
+            .map(|capture| {
+                self.value(
+                    &BytecodeType::Load {
+                        path: capture.path.clone(),
+                    },
+                    function,
+                )
+            })
+            .collect::<Option<Vec<BasicValueEnum>>>()?;
+        let code = match self.module.get_function(path) {
+            Some(code) => code,
+            None => self.closure_code(
+                path,
+                parameters,
+                return_type,
+                captures,
+                environment_type,
+                body,
+            )?,
+        };
+
+        let i8_ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let environment = if captures.is_empty() {
+            i8_ptr.const_null()
+        } else {
+            let environment = self
+                .builder
+                .build_malloc(environment_type, "environment")
+                .ok()?;
+            for (index, value) in values.into_iter().enumerate() {
+                let field = self
+                    .builder
+                    .build_struct_gep(environment, index as u32, "capture")
+                    .ok()?;
+                self.builder.build_store(field, value);
+            }
+
+            self.builder
+                .build_pointer_cast(environment, i8_ptr, "environment")
+        };
+
+        self.closure_value(code, environment)
+    }
+
+    /// A `FunctionRef` bytecode, the function at `path` as a value. Its code
+    /// is a function taking an environment it ignores and calling the
+    /// function at `path`.
+    pub fn function_ref(&self, path: &str, function_type: &ZXTyped) -> Option<BasicValueEnum<'a>> {
+        let (parameters, return_type) = match function_type {
+            ZXTyped::Function {
+                parameters,
+                return_type,
+            } => (parameters, return_type.as_ref()),
+            _ => return None,
+        };
+        let name = format!("{}$closure", path);
+        let code = match self.module.get_function(&name) {
+            Some(code) => code,
+            None => {
+                let target = self.function_at(path)?;
+                let outer = self.builder.get_insert_block();
+                let code = self.module.add_function(
+                    &name,
+                    self.closure_code_type(parameters, return_type)?,
+                    None,
+                );
+                let entry = self.context.append_basic_block(code, "entry");
+                self.builder.position_at_end(entry);
+                let arguments = code
+                    .get_params()
+                    .into_iter()
+                    .skip(1)
+                    .map(|parameter| parameter.into())
+                    .collect::<Vec<BasicMetadataValueEnum>>();
+                let result = self
+                    .builder
+                    .build_call(target, &arguments, "result")
+                    .try_as_basic_value()
+                    .left();
+                self.builder
+                    .build_return(result.as_ref().map(|result| result as &dyn BasicValue));
+                if let Some(outer) = outer {
+                    self.builder.position_at_end(outer);
+                }
+
+                code
+            }
+        };
+
+        self.closure_value(
+            code,
+            self.context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .const_null(),
+        )
+    }
+
+    /// A `CallValue` bytecode, the code of the function value is called
+    /// with its environment first
+    pub fn call_value(
+        &self,
+        callee: &BytecodeType,
+        function_type: &ZXTyped,
+        arguments: &[BytecodeType],
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let (parameters, return_type) = match function_type {
+            ZXTyped::Function {
+                parameters,
+                return_type,
+            } => (parameters, return_type.as_ref()),
+            _ => return None,
+        };
+        let closure = self.value(callee, function)?.into_struct_value();
+        let code = self
+            .builder
+            .build_extract_value(closure, 0, "code")?
+            .into_pointer_value();
+        let environment = self
+            .builder
+            .build_extract_value(closure, 1, "environment")?;
+        let code = self.builder.build_pointer_cast(
+            code,
+            self.closure_code_type(parameters, return_type)?
+                .ptr_type(AddressSpace::Generic),
+            "code",
+        );
+        let arguments = std::iter::once(Some(environment))
+            .chain(
+                arguments
+                    .iter()
+                    .map(|argument| self.value(argument, function)),
+            )
+            .map(|argument| argument.map(BasicMetadataValueEnum::from))
+            .collect::<Option<Vec<BasicMetadataValueEnum>>>()?;
+
+        self.builder
+            .build_call(CallableValue::try_from(code).ok()?, &arguments, "call")
+            .try_as_basic_value()
+            .left()
+    }
+
+    // the function of an anonymous function, the captures are loaded from
+    // the environment into stack slots of their own
+    fn closure_code(
+        &self,
+        path: &str,
+        parameters: &[Variable],
+        return_type: &ZXTyped,
+        captures: &[Variable],
+        environment_type: StructType<'a>,
+        body: &BytecodeType,
+    ) -> Option<FunctionValue<'a>> {
+        let parameter_types = parameters
+            .iter()
+            .map(|parameter| parameter.var_type.clone())
+            .collect::<Vec<ZXTyped>>();
+        let code = self.module.add_function(
+            path,
+            self.closure_code_type(&parameter_types, return_type)?,
+            None,
+        );
+        let outer = self.builder.get_insert_block();
+        // the variables of the function around it are not in this one
+        let outer_variables = self.variables.take();
+        let entry = self.context.append_basic_block(code, "entry");
+        self.builder.position_at_end(entry);
+
+        if !captures.is_empty() {
+            let environment = self.builder.build_pointer_cast(
+                code.get_first_param()?.into_pointer_value(),
+                environment_type.ptr_type(AddressSpace::Generic),
+                "environment",
+            );
+            for (index, capture) in captures.iter().enumerate() {
+                let field = self
+                    .builder
+                    .build_struct_gep(environment, index as u32, "capture")
+                    .ok()?;
+                let value = self.builder.build_load(field, &capture.path);
+                self.store_variable(&capture.path, value);
+            }
+        }
+        for (index, parameter) in parameters.iter().enumerate() {
+            if let Some(value) = code.get_nth_param(index as u32 + 1) {
+                self.store_variable(&parameter.path, value);
+            }
+        }
+
+        let index = Mutex::new(parameters.len() + 2);
+        self.build_body(path, body, return_type, code, &index);
+
+        self.variables.replace(outer_variables);
+        if let Some(outer) = outer {
+            self.builder.position_at_end(outer);
+        }
+
+        Some(code)
+    }
+
+    // `ret (i8*, parameters...)`, the type of the code of every function
+    // value of the same type
+    fn closure_code_type(
+        &self,
+        parameters: &[ZXTyped],
+        return_type: &ZXTyped,
+    ) -> Option<FunctionType<'a>> {
+        let parameters = std::iter::once(Some(
+            self.context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .as_basic_type_enum(),
+        ))
+        .chain(
+            parameters
+                .iter()
+                .map(|parameter| self.structs.field_type(self.context, parameter)),
+        )
+        .map(|parameter| parameter.map(BasicMetadataTypeEnum::from))
+        .collect::<Option<Vec<BasicMetadataTypeEnum>>>()?;
+
+        self.function_type(return_type, &parameters)
+    }
+
+    fn closure_value(
+        &self,
+        code: FunctionValue<'a>,
+        environment: PointerValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let code = self.builder.build_pointer_cast(
+            code.as_global_value().as_pointer_value(),
+            self.context.i8_type().ptr_type(AddressSpace::Generic),
+            "code",
+        );
+        let closure = self.structs.closure_type(self.context).get_undef();
+        let closure = self
+            .builder
+            .build_insert_value(closure, code, 0, "closure")?
+            .into_struct_value();
+        let closure = self
+            .builder
+            .build_insert_value(closure, environment, 1, "closure")?
+            .into_struct_value();
+
+        Some(closure.into())
+    }
+
+    /// The function at `path`, top level functions are built under their
+    /// name and methods under their path
+    pub fn function_at(&self, path: &str) -> Option<FunctionValue<'a>> {
+        let (name, scope) = match self.scopes.scopes.iter().find(|scope| scope.path == path) {
+            Some(scope) => (scope.name.to_string(), scope),
+            None => (path.to_string(), find_path(&self.scopes, path)?),
+        };
+
+        match &scope.scope_type {
+            ScopeType::DefFunction {
+                parameters,
+                return_type,
+                ..
+            } => self.declare_function(&name, parameters, return_type),
+            _ => None,
+        }
+    }
+}
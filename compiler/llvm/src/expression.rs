@@ -0,0 +1,379 @@
+use crate::Builder;
+use inkwell::module::Linkage;
+use inkwell::values::{
+    BasicMetadataValueEnum, BasicValueEnum, FunctionValue, IntValue, PointerValue,
+};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
+use util::ast::{Operator, UnaryOperator};
+use util::bytecode::BytecodeType;
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// A `Binary` bytecode. The instruction follows the operands that were
+    /// built, `Char` values are compared unsigned and `Str` values by
+    /// `strcmp`.
+    pub fn build_binary(
+        &self,
+        operator: &Operator,
+        operand_type: &ZXTyped,
+        left: &BytecodeType,
+        right: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let left = self.value(left, function)?;
+        let right = self.value(right, function)?;
+
+        Some(match (left, right) {
+            (BasicValueEnum::IntValue(left), BasicValueEnum::IntValue(right)) => match operator {
+                Operator::Add => self.builder.build_int_add(left, right, "add"),
+                Operator::Sub => self.builder.build_int_sub(left, right, "sub"),
+                Operator::Mul => self.builder.build_int_mul(left, right, "mul"),
+                Operator::Div => self.builder.build_int_signed_div(left, right, "div"),
+                Operator::Mod => self.builder.build_int_signed_rem(left, right, "mod"),
+                operator => self.builder.build_int_compare(
+                    int_predicate(operator, matches!(operand_type, ZXTyped::Char { .. }))?,
+                    left,
+                    right,
+                    "compare",
+                ),
+            }
+            .into(),
+            (BasicValueEnum::FloatValue(left), BasicValueEnum::FloatValue(right)) => match operator
+            {
+                Operator::Add => self.builder.build_float_add(left, right, "add").into(),
+                Operator::Sub => self.builder.build_float_sub(left, right, "sub").into(),
+                Operator::Mul => self.builder.build_float_mul(left, right, "mul").into(),
+                Operator::Div => self.builder.build_float_div(left, right, "div").into(),
+                Operator::Mod => self.builder.build_float_rem(left, right, "mod").into(),
+                operator => self
+                    .builder
+                    .build_float_compare(float_predicate(operator)?, left, right, "compare")
+                    .into(),
+            },
+            (BasicValueEnum::PointerValue(left), BasicValueEnum::PointerValue(right))
+                if matches!(operand_type, ZXTyped::String { .. }) =>
+            {
+                let order = self
+                    .builder
+                    .build_call(self.strcmp(), &[left.into(), right.into()], "order")
+                    .try_as_basic_value()
+                    .left()?
+                    .into_int_value();
+
+                self.builder
+                    .build_int_compare(
+                        int_predicate(operator, false)?,
+                        order,
+                        self.context.i32_type().const_zero(),
+                        "compare",
+                    )
+                    .into()
+            }
+            _ => return None,
+        })
+    }
+
+    /// A `Logical` bytecode. `right` is built in a block of its own that is
+    /// skipped when `left` decides the result, which is `left` then.
+    pub fn build_logical(
+        &self,
+        operator: &Operator,
+        left: &BytecodeType,
+        right: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let left = self.value(left, function)?.into_int_value();
+        let left_end = self.builder.get_insert_block()?;
+        let right_block = self.context.append_basic_block(function, "logical_right");
+        let end = self.context.append_basic_block(function, "logical_end");
+        match operator {
+            Operator::And => self
+                .builder
+                .build_conditional_branch(left, right_block, end),
+            Operator::Or => self
+                .builder
+                .build_conditional_branch(left, end, right_block),
+            _ => return None,
+        };
+
+        self.builder.position_at_end(right_block);
+        let right = self.value(right, function)?.into_int_value();
+        // `right` can end in another block
+        let right_end = self.builder.get_insert_block()?;
+        self.builder.build_unconditional_branch(end);
+
+        self.builder.position_at_end(end);
+        let result = self.builder.build_phi(self.context.bool_type(), "logical");
+        result.add_incoming(&[(&left, left_end), (&right, right_end)]);
+
+        Some(result.as_basic_value())
+    }
+
+    /// A `Unary` bytecode, `!` on a `Bool` and `~` on an integer both flip
+    /// every bit
+    pub fn build_unary(
+        &self,
+        operator: &UnaryOperator,
+        value: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        Some(match (operator, self.value(value, function)?) {
+            (UnaryOperator::Neg, BasicValueEnum::IntValue(value)) => {
+                self.builder.build_int_neg(value, "neg").into()
+            }
+            (UnaryOperator::Neg, BasicValueEnum::FloatValue(value)) => {
+                self.builder.build_float_neg(value, "neg").into()
+            }
+            (UnaryOperator::Not | UnaryOperator::BitNot, BasicValueEnum::IntValue(value)) => {
+                self.builder.build_not(value, "not").into()
+            }
+            _ => return None,
+        })
+    }
+
+    /// A `Call` bytecode. An instance of a generic function is called at the
+    /// path of the instance, a function returning `Void` has no value.
+    pub fn call_function(
+        &self,
+        path: &str,
+        type_arguments: &[ZXTyped],
+        arguments: &[BytecodeType],
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let path = match type_arguments {
+            [] => path.to_string(),
+            type_arguments => format!(
+                "{}<{}>",
+                path,
+                type_arguments
+                    .iter()
+                    .map(|type_argument| type_argument.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        };
+        let target = self.function_at(&path)?;
+        let arguments = arguments
+            .iter()
+            .map(|argument| {
+                self.value(argument, function)
+                    .map(BasicMetadataValueEnum::from)
+            })
+            .collect::<Option<Vec<BasicMetadataValueEnum>>>()?;
+
+        self.builder
+            .build_call(target, &arguments, "call")
+            .try_as_basic_value()
+            .left()
+    }
+
+    /// A `ToStr` bytecode, the value is printed into a new string
+    pub fn build_to_str(
+        &self,
+        value: &BytecodeType,
+        value_type: &ZXTyped,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let value = self.value(value, function)?;
+        // variadic arguments are at least an `int` or a `double`
+        let (format, value): (&str, BasicMetadataValueEnum) = match value_type {
+            ZXTyped::String { .. } => return Some(value),
+            ZXTyped::Integer { .. } => ("%d", value.into()),
+            ZXTyped::Long { .. } => ("%lld", value.into()),
+            ZXTyped::Float { .. } => (
+                "%g",
+                self.builder
+                    .build_float_ext(value.into_float_value(), self.context.f64_type(), "double")
+                    .into(),
+            ),
+            ZXTyped::Double { .. } => ("%g", value.into()),
+            ZXTyped::Char { .. } => (
+                "%c",
+                self.builder
+                    .build_int_z_extend(value.into_int_value(), self.context.i32_type(), "char")
+                    .into(),
+            ),
+            ZXTyped::Bool { .. } => {
+                let text = |text: &str| {
+                    self.builder
+                        .build_global_string_ptr(text, text)
+                        .as_pointer_value()
+                };
+
+                (
+                    "%s",
+                    self.builder
+                        .build_select(value.into_int_value(), text("true"), text("false"), "bool")
+                        .into(),
+                )
+            }
+            _ => return None,
+        };
+        let format = self
+            .builder
+            .build_global_string_ptr(format, "format")
+            .as_pointer_value();
+        let i8_ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+        // the first call only counts the characters
+        let len = self
+            .builder
+            .build_call(
+                self.snprintf(),
+                &[
+                    i8_ptr.const_null().into(),
+                    self.context.i64_type().const_zero().into(),
+                    format.into(),
+                    value,
+                ],
+                "len",
+            )
+            .try_as_basic_value()
+            .left()?
+            .into_int_value();
+        let len = self
+            .builder
+            .build_int_s_extend(len, self.context.i64_type(), "len");
+        let size =
+            self.builder
+                .build_int_add(len, self.context.i64_type().const_int(1, false), "size");
+        let string = self.new_string(size)?;
+        self.builder.build_call(
+            self.snprintf(),
+            &[string.into(), size.into(), format.into(), value],
+            "",
+        );
+
+        Some(string.into())
+    }
+
+    /// A `Concat` bytecode, both strings are copied into a new one
+    pub fn build_concat(
+        &self,
+        left: &BytecodeType,
+        right: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let left = self.value(left, function)?;
+        let right = self.value(right, function)?;
+        let len = |string: BasicValueEnum<'a>| {
+            Some(
+                self.builder
+                    .build_call(self.strlen(), &[string.into()], "len")
+                    .try_as_basic_value()
+                    .left()?
+                    .into_int_value(),
+            )
+        };
+        let len = self.builder.build_int_add(len(left)?, len(right)?, "len");
+        let size =
+            self.builder
+                .build_int_add(len, self.context.i64_type().const_int(1, false), "size");
+        let string = self.new_string(size)?;
+        self.builder
+            .build_call(self.strcpy(), &[string.into(), left.into()], "");
+        self.builder
+            .build_call(self.strcat(), &[string.into(), right.into()], "");
+
+        Some(string.into())
+    }
+
+    // room for `size` characters, the terminating zero included
+    fn new_string(&self, size: IntValue<'a>) -> Option<PointerValue<'a>> {
+        self.builder
+            .build_array_malloc(self.context.i8_type(), size, "string")
+            .ok()
+    }
+
+    fn snprintf(&self) -> FunctionValue<'a> {
+        self.module.get_function("snprintf").unwrap_or_else(|| {
+            let string = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+            self.module.add_function(
+                "snprintf",
+                self.context.i32_type().fn_type(
+                    &[string.into(), self.context.i64_type().into(), string.into()],
+                    true,
+                ),
+                Some(Linkage::External),
+            )
+        })
+    }
+
+    pub fn strlen(&self) -> FunctionValue<'a> {
+        self.module.get_function("strlen").unwrap_or_else(|| {
+            let string = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+            self.module.add_function(
+                "strlen",
+                self.context.i64_type().fn_type(&[string.into()], false),
+                Some(Linkage::External),
+            )
+        })
+    }
+
+    pub fn strcmp(&self) -> FunctionValue<'a> {
+        self.module.get_function("strcmp").unwrap_or_else(|| {
+            let string = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+            self.module.add_function(
+                "strcmp",
+                self.context
+                    .i32_type()
+                    .fn_type(&[string.into(), string.into()], false),
+                Some(Linkage::External),
+            )
+        })
+    }
+
+    fn strcpy(&self) -> FunctionValue<'a> {
+        self.string_function("strcpy")
+    }
+
+    fn strcat(&self) -> FunctionValue<'a> {
+        self.string_function("strcat")
+    }
+
+    // `strcpy` and `strcat`, both take the destination and the source
+    fn string_function(&self, name: &str) -> FunctionValue<'a> {
+        self.module.get_function(name).unwrap_or_else(|| {
+            let string = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+            self.module.add_function(
+                name,
+                string.fn_type(&[string.into(), string.into()], false),
+                Some(Linkage::External),
+            )
+        })
+    }
+}
+
+// the predicate of the comparison `operator`, `None` for other operators
+fn int_predicate(operator: &Operator, unsigned: bool) -> Option<IntPredicate> {
+    Some(match (operator, unsigned) {
+        (Operator::Equal, _) => IntPredicate::EQ,
+        (Operator::NotEqual, _) => IntPredicate::NE,
+        (Operator::Less, false) => IntPredicate::SLT,
+        (Operator::Less, true) => IntPredicate::ULT,
+        (Operator::LessEqual, false) => IntPredicate::SLE,
+        (Operator::LessEqual, true) => IntPredicate::ULE,
+        (Operator::More, false) => IntPredicate::SGT,
+        (Operator::More, true) => IntPredicate::UGT,
+        (Operator::MoreEqual, false) => IntPredicate::SGE,
+        (Operator::MoreEqual, true) => IntPredicate::UGE,
+        _ => return None,
+    })
+}
+
+// `NaN` is unequal to every value, itself included
+fn float_predicate(operator: &Operator) -> Option<FloatPredicate> {
+    Some(match operator {
+        Operator::Equal => FloatPredicate::OEQ,
+        Operator::NotEqual => FloatPredicate::UNE,
+        Operator::Less => FloatPredicate::OLT,
+        Operator::LessEqual => FloatPredicate::OLE,
+        Operator::More => FloatPredicate::OGT,
+        Operator::MoreEqual => FloatPredicate::OGE,
+        _ => return None,
+    })
+}
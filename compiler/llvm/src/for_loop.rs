@@ -0,0 +1,185 @@
+use std::sync::Mutex;
+
+use crate::Builder;
+use inkwell::values::{BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::IntPredicate;
+use util::bytecode::BytecodeType;
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// A `ForLoop` bytecode. A counter goes from the start to the end of a
+    /// range, or over the indexes of an array or a `Str`, the loop variable
+    /// is stored before every run of the body.
+    pub fn build_for_loop(
+        &self,
+        for_loop: &BytecodeType,
+        function: FunctionValue<'a>,
+        index: &Mutex<usize>,
+    ) {
+        let (variable, element_type, iterable, iterable_type, body) = match for_loop {
+            BytecodeType::ForLoop {
+                variable,
+                element_type,
+                iterable,
+                iterable_type,
+                body,
+            } => (variable, element_type, iterable, iterable_type, body),
+            _ => return,
+        };
+        let iterable = match self.required_value(iterable, function) {
+            Some(iterable) => iterable,
+            None => return,
+        };
+        let i64_type = self.context.i64_type();
+        // the counter starts at `start` and the loop ends after `end`, which
+        // is only reached when `inclusive`
+        let (start, end, inclusive) = match iterable_type {
+            ZXTyped::Range { .. } => {
+                let range = iterable.into_struct_value();
+                let field = |index: u32, name: &str| {
+                    self.builder
+                        .build_extract_value(range, index, name)
+                        .unwrap()
+                        .into_int_value()
+                };
+
+                (
+                    field(0, "start"),
+                    field(1, "end"),
+                    Some(field(2, "inclusive")),
+                )
+            }
+            ZXTyped::Array { .. } => (
+                i64_type.const_zero(),
+                self.load_len(iterable.into_pointer_value()),
+                None,
+            ),
+            ZXTyped::String { .. } => {
+                let len = self
+                    .builder
+                    .build_call(self.strlen(), &[iterable.into()], "len")
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap()
+                    .into_int_value();
+
+                (i64_type.const_zero(), len, None)
+            }
+            iterable_type => {
+                return self.internal_error(format!(
+                    "cannot iterate over a value of type `{}`",
+                    iterable_type
+                ))
+            }
+        };
+
+        let element_type = match self.structs.field_type(self.context, element_type) {
+            Some(element_type) => element_type,
+            None => {
+                return self
+                    .internal_error(format!("the loop variable `{}` has no layout", variable))
+            }
+        };
+        let variable_pointer = self.builder.build_alloca(element_type, variable);
+        self.variables
+            .borrow_mut()
+            .insert(variable.clone(), variable_pointer);
+        let counter = self.builder.build_alloca(start.get_type(), "counter");
+        self.builder.build_store(counter, start);
+
+        let header = self.context.append_basic_block(function, "for_header");
+        let body_block = self.context.append_basic_block(function, "for_body");
+        let latch = self.context.append_basic_block(function, "for_latch");
+        let step = self.context.append_basic_block(function, "for_step");
+        let exit = self.context.append_basic_block(function, "for_exit");
+        self.builder.build_unconditional_branch(header);
+
+        self.builder.position_at_end(header);
+        let current = self.load_counter(counter);
+        let before_end =
+            self.builder
+                .build_int_compare(IntPredicate::SLT, current, end, "before_end");
+        let condition = match inclusive {
+            Some(inclusive) => {
+                let at_end =
+                    self.builder
+                        .build_int_compare(IntPredicate::SLE, current, end, "at_end");
+
+                self.builder
+                    .build_select(inclusive, at_end, before_end, "in_range")
+                    .into_int_value()
+            }
+            None => before_end,
+        };
+        self.builder
+            .build_conditional_branch(condition, body_block, exit);
+
+        self.builder.position_at_end(body_block);
+        let element = self.loop_element(iterable, iterable_type, current);
+        self.builder.build_store(variable_pointer, element);
+        if let BytecodeType::Block { bytecodes, .. } = body.as_ref() {
+            bytecodes
+                .iter()
+                .for_each(|bytecode| self.bytecodes(bytecode, function, index));
+        }
+        self.builder.build_unconditional_branch(latch);
+
+        // `0..=max` ends at `max` instead of counting past it
+        self.builder.position_at_end(latch);
+        let current = self.load_counter(counter);
+        match inclusive {
+            Some(inclusive) => {
+                let is_end =
+                    self.builder
+                        .build_int_compare(IntPredicate::EQ, current, end, "is_end");
+                let last = self.builder.build_and(inclusive, is_end, "last");
+
+                self.builder.build_conditional_branch(last, exit, step);
+            }
+            None => {
+                self.builder.build_unconditional_branch(step);
+            }
+        }
+
+        self.builder.position_at_end(step);
+        let next =
+            self.builder
+                .build_int_add(current, current.get_type().const_int(1, false), "next");
+        self.builder.build_store(counter, next);
+        self.builder.build_unconditional_branch(header);
+
+        self.builder.position_at_end(exit);
+    }
+
+    fn load_counter(&self, counter: PointerValue<'a>) -> IntValue<'a> {
+        self.builder.build_load(counter, "current").into_int_value()
+    }
+
+    // the value of the loop variable when the counter is `current`
+    fn loop_element(
+        &self,
+        iterable: BasicValueEnum<'a>,
+        iterable_type: &ZXTyped,
+        current: IntValue<'a>,
+    ) -> BasicValueEnum<'a> {
+        match iterable_type {
+            ZXTyped::Array { .. } => {
+                let element = self.element_field(iterable.into_pointer_value(), current);
+
+                self.builder.build_load(element, "element")
+            }
+            ZXTyped::String { .. } => {
+                let char_pointer = unsafe {
+                    self.builder.build_in_bounds_gep(
+                        iterable.into_pointer_value(),
+                        &[current],
+                        "char",
+                    )
+                };
+
+                self.builder.build_load(char_pointer, "char")
+            }
+            _ => current.into(),
+        }
+    }
+}
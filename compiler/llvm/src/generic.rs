@@ -0,0 +1,395 @@
+use util::bytecode::{BytecodeType, Pattern};
+use util::scope::{Scope, ScopeType, Scopes};
+use util::symbol::Symbol;
+use util::zx_type::ZXTyped;
+
+/// An instance of every generic function and class `scopes` use, named like
+/// the type of a class instance, `max<Int>` or `List<Str>`. Instances used
+/// by other instances are included, the generic declarations are not built.
+pub fn instances(scopes: &Scopes) -> Vec<Scope> {
+    let mut instances = Instances {
+        scopes,
+        instances: vec![],
+    };
+
+    for scope in &scopes.scopes {
+        instances.scope(scope, &[], &[]);
+    }
+
+    instances.instances
+}
+
+/// Whether `scope` is a generic function or class, only its instances are
+/// built
+pub fn is_generic(scope: &Scope) -> bool {
+    match &scope.scope_type {
+        ScopeType::DefFunction {
+            type_parameters, ..
+        }
+        | ScopeType::DefClass {
+            type_parameters, ..
+        } => !type_parameters.is_empty(),
+        _ => false,
+    }
+}
+
+struct Instances<'s> {
+    scopes: &'s Scopes,
+    instances: Vec<Scope>,
+}
+
+impl Instances<'_> {
+    fn scope(&mut self, scope: &Scope, parameters: &[Symbol], arguments: &[ZXTyped]) {
+        if !is_generic(scope) {
+            self.contents(scope, parameters, arguments)
+        }
+    }
+
+    // types and calls in `scope` with `parameters` of the instance being
+    // visited replaced by `arguments`
+    fn contents(&mut self, scope: &Scope, parameters: &[Symbol], arguments: &[ZXTyped]) {
+        match &scope.scope_type {
+            ScopeType::DefVariable { var_type, value } => {
+                self.zx_type(var_type.substitute(parameters, arguments));
+                if let Some(value) = value {
+                    self.bytecode(value, parameters, arguments)
+                }
+            }
+            ScopeType::DefFunction {
+                parameters: function_parameters,
+                block,
+                return_type,
+                children,
+                ..
+            } => {
+                function_parameters
+                    .iter()
+                    .for_each(|parameter| self.scope(parameter, parameters, arguments));
+                self.zx_type(return_type.substitute(parameters, arguments));
+                self.bytecode(block, parameters, arguments);
+                children
+                    .scopes
+                    .iter()
+                    .for_each(|child| self.scope(child, parameters, arguments));
+            }
+            ScopeType::DefClass { members, .. } => members
+                .scopes
+                .iter()
+                .for_each(|member| self.scope(member, parameters, arguments)),
+            ScopeType::DefEnum { variants } => variants
+                .scopes
+                .iter()
+                .for_each(|variant| self.scope(variant, parameters, arguments)),
+            ScopeType::DefVariant { payload, .. } => payload
+                .iter()
+                .for_each(|field| self.zx_type(field.substitute(parameters, arguments))),
+            _ => {}
+        }
+    }
+
+    fn zx_type(&mut self, zx_type: ZXTyped) {
+        if let ZXTyped::Array { element, .. } | ZXTyped::Range { element } = zx_type {
+            return self.zx_type(*element);
+        }
+        if let ZXTyped::Function {
+            parameters,
+            return_type,
+        } = zx_type
+        {
+            parameters
+                .into_iter()
+                .for_each(|parameter| self.zx_type(parameter));
+            return self.zx_type(*return_type);
+        }
+        if let ZXTyped::Generic { name, arguments } = &zx_type {
+            arguments
+                .iter()
+                .for_each(|argument| self.zx_type(argument.clone()));
+            if arguments.is_empty() || !arguments.iter().all(is_concrete) {
+                return;
+            }
+
+            let scopes = self.scopes;
+            if let Some(class) = scopes.scopes.iter().find(|scope| {
+                scope.name.as_str() == name
+                    && matches!(scope.scope_type, ScopeType::DefClass { .. })
+            }) {
+                self.instance(class, arguments)
+            }
+        }
+    }
+
+    fn bytecode(&mut self, bytecode: &BytecodeType, parameters: &[Symbol], arguments: &[ZXTyped]) {
+        let substitute = |zx_type: &ZXTyped| zx_type.substitute(parameters, arguments);
+
+        match bytecode {
+            BytecodeType::Call {
+                path,
+                ret_type,
+                argument_types,
+                type_arguments,
+                arguments: call_arguments,
+            } => {
+                self.zx_type(substitute(ret_type));
+                argument_types
+                    .iter()
+                    .for_each(|argument_type| self.zx_type(substitute(argument_type)));
+                call_arguments
+                    .iter()
+                    .for_each(|argument| self.bytecode(argument, parameters, arguments));
+
+                let type_arguments = type_arguments
+                    .iter()
+                    .map(substitute)
+                    .collect::<Vec<ZXTyped>>();
+                if type_arguments.is_empty() || !type_arguments.iter().all(is_concrete) {
+                    return;
+                }
+                // a generic method can be in an instance of its class
+                let function = find_path(self.scopes, path)
+                    .or_else(|| {
+                        self.instances
+                            .iter()
+                            .find_map(|scope| find_path_in(scope, path))
+                    })
+                    .cloned();
+                if let Some(function) = function {
+                    self.instance(&function, &type_arguments)
+                }
+            }
+            BytecodeType::CallMethod {
+                object,
+                ret_type,
+                arguments: call_arguments,
+                ..
+            } => {
+                self.zx_type(substitute(ret_type));
+                self.bytecode(object, parameters, arguments);
+                call_arguments
+                    .iter()
+                    .for_each(|argument| self.bytecode(argument, parameters, arguments))
+            }
+            BytecodeType::FieldLoad {
+                object, field_type, ..
+            } => {
+                self.zx_type(substitute(field_type));
+                self.bytecode(object, parameters, arguments)
+            }
+            BytecodeType::FieldStore { object, value, .. } => {
+                self.bytecode(object, parameters, arguments);
+                self.bytecode(value, parameters, arguments)
+            }
+            BytecodeType::Alloca { alloca_type, .. } => self.zx_type(substitute(alloca_type)),
+            BytecodeType::Store { value, .. } => self.bytecode(value, parameters, arguments),
+            BytecodeType::ToStr { value, value_type } => {
+                self.zx_type(substitute(value_type));
+                self.bytecode(value, parameters, arguments)
+            }
+            BytecodeType::Unary {
+                operand_type,
+                value,
+                ..
+            } => {
+                self.zx_type(substitute(operand_type));
+                self.bytecode(value, parameters, arguments)
+            }
+            BytecodeType::Binary {
+                operand_type,
+                left,
+                right,
+                ..
+            } => {
+                self.zx_type(substitute(operand_type));
+                self.bytecode(left, parameters, arguments);
+                self.bytecode(right, parameters, arguments)
+            }
+            BytecodeType::Concat { left, right } | BytecodeType::Logical { left, right, .. } => {
+                self.bytecode(left, parameters, arguments);
+                self.bytecode(right, parameters, arguments)
+            }
+            BytecodeType::Array {
+                element_type,
+                elements,
+            } => {
+                self.zx_type(substitute(element_type));
+                elements
+                    .iter()
+                    .for_each(|element| self.bytecode(element, parameters, arguments))
+            }
+            BytecodeType::Index {
+                element_type,
+                array,
+                index,
+                ..
+            } => {
+                self.zx_type(substitute(element_type));
+                self.bytecode(array, parameters, arguments);
+                self.bytecode(index, parameters, arguments)
+            }
+            BytecodeType::IndexStore {
+                element_type,
+                array,
+                index,
+                value,
+                ..
+            } => {
+                self.zx_type(substitute(element_type));
+                self.bytecode(array, parameters, arguments);
+                self.bytecode(index, parameters, arguments);
+                self.bytecode(value, parameters, arguments)
+            }
+            BytecodeType::Len { array } => self.bytecode(array, parameters, arguments),
+            BytecodeType::Range {
+                element_type,
+                start,
+                end,
+                ..
+            } => {
+                self.zx_type(substitute(element_type));
+                self.bytecode(start, parameters, arguments);
+                self.bytecode(end, parameters, arguments)
+            }
+            BytecodeType::ForLoop {
+                element_type,
+                iterable,
+                iterable_type,
+                body,
+                ..
+            } => {
+                self.zx_type(substitute(element_type));
+                self.zx_type(substitute(iterable_type));
+                self.bytecode(iterable, parameters, arguments);
+                self.bytecode(body, parameters, arguments)
+            }
+            BytecodeType::FunctionRef { function_type, .. } => {
+                self.zx_type(substitute(function_type))
+            }
+            BytecodeType::Closure {
+                parameters: closure_parameters,
+                return_type,
+                captures,
+                body,
+                ..
+            } => {
+                closure_parameters
+                    .iter()
+                    .chain(captures)
+                    .for_each(|variable| self.zx_type(substitute(&variable.var_type)));
+                self.zx_type(substitute(return_type));
+                self.bytecode(body, parameters, arguments)
+            }
+            BytecodeType::CallValue {
+                function,
+                function_type,
+                arguments: call_arguments,
+            } => {
+                self.zx_type(substitute(function_type));
+                self.bytecode(function, parameters, arguments);
+                call_arguments
+                    .iter()
+                    .for_each(|argument| self.bytecode(argument, parameters, arguments))
+            }
+            BytecodeType::EnumValue { payload, .. } => payload
+                .iter()
+                .for_each(|field| self.bytecode(field, parameters, arguments)),
+            BytecodeType::Match {
+                value,
+                value_type,
+                result_type,
+                arms,
+            } => {
+                self.zx_type(substitute(value_type));
+                self.zx_type(substitute(result_type));
+                self.bytecode(value, parameters, arguments);
+                for arm in arms {
+                    self.pattern(&arm.pattern, parameters, arguments);
+                    arm.body
+                        .iter()
+                        .for_each(|bytecode| self.bytecode(bytecode, parameters, arguments))
+                }
+            }
+            BytecodeType::Block { bytecodes, .. } | BytecodeType::Box { bytecodes } => bytecodes
+                .iter()
+                .for_each(|bytecode| self.bytecode(bytecode, parameters, arguments)),
+            _ => {}
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern, parameters: &[Symbol], arguments: &[ZXTyped]) {
+        match pattern {
+            Pattern::Binding { .. } => {}
+            Pattern::Value { value, .. } => self.bytecode(value, parameters, arguments),
+            Pattern::Variant { fields, .. } => fields
+                .iter()
+                .for_each(|field| self.pattern(field, parameters, arguments)),
+        }
+    }
+
+    fn instance(&mut self, scope: &Scope, arguments: &[ZXTyped]) {
+        let type_parameters = match &scope.scope_type {
+            ScopeType::DefFunction {
+                type_parameters, ..
+            }
+            | ScopeType::DefClass {
+                type_parameters, ..
+            } if type_parameters.len() == arguments.len() => type_parameters.clone(),
+            _ => return,
+        };
+        let instance = Scope {
+            name: Symbol::intern(
+                &ZXTyped::Generic {
+                    name: scope.name.to_string(),
+                    arguments: arguments.to_vec(),
+                }
+                .to_string(),
+            ),
+            ..scope.instantiate(arguments)
+        };
+
+        if self
+            .instances
+            .iter()
+            .any(|built| built.path == instance.path)
+        {
+            return;
+        }
+        self.instances.push(instance);
+        self.contents(scope, &type_parameters, arguments);
+    }
+}
+
+// a type without type parameters in it
+fn is_concrete(zx_type: &ZXTyped) -> bool {
+    match zx_type {
+        ZXTyped::Param(_) => false,
+        ZXTyped::Generic { arguments, .. } => {
+            !arguments.is_empty() && arguments.iter().all(is_concrete)
+        }
+        ZXTyped::Array { element, .. } | ZXTyped::Range { element } => is_concrete(element),
+        ZXTyped::Function {
+            parameters,
+            return_type,
+        } => parameters.iter().all(is_concrete) && is_concrete(return_type),
+        _ => true,
+    }
+}
+
+pub(crate) fn find_path<'s>(scopes: &'s Scopes, path: &str) -> Option<&'s Scope> {
+    scopes
+        .scopes
+        .iter()
+        .find_map(|scope| find_path_in(scope, path))
+}
+
+// `scope` or the member or nested function of it at `path`
+fn find_path_in<'s>(scope: &'s Scope, path: &str) -> Option<&'s Scope> {
+    if scope.path == path {
+        return Some(scope);
+    }
+
+    match &scope.scope_type {
+        ScopeType::DefClass { members, .. } => find_path(members, path),
+        ScopeType::DefFunction { children, .. } => find_path(children, path),
+        _ => None,
+    }
+}
@@ -1,30 +1,58 @@
+mod array;
 mod bytecodes;
+mod class;
+mod closure;
+mod expression;
+mod for_loop;
+mod generic;
+mod matching;
 mod scope;
 mod struct_type;
+mod test;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::Path;
 
+use generic::is_generic;
 pub use inkwell::context::Context;
 use inkwell::module::Module;
+use inkwell::values::PointerValue;
 use struct_type::Structs;
-use util::report::Report;
-use util::scope::Scopes;
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{ScopeType, Scopes};
+use util::source_map::SourceMap;
 
 pub struct Builder<'a> {
     scopes: Scopes,
     structs: Structs<'a>,
-    reports: Vec<Report>,
+    // errors found while building, taken by `take_reports`
+    reports: RefCell<Vec<Report>>,
+    // positions in runtime errors
+    source_map: &'a SourceMap,
+    // stack slots of the local variables built so far, by path
+    variables: RefCell<HashMap<String, PointerValue<'a>>>,
     context: &'a Context,
     module: Module<'a>,
     builder: inkwell::builder::Builder<'a>,
 }
 
-impl Builder<'_> {
-    pub fn new(scopes: Scopes, context: &Context) -> Builder {
+impl<'a> Builder<'a> {
+    pub fn new(scopes: Scopes, source_map: &'a SourceMap, context: &'a Context) -> Builder<'a> {
+        // generic functions and classes are built once for every instance
+        let mut scopes = scopes;
+        let instances = generic::instances(&scopes);
+        scopes.scopes.extend(instances);
+        let mut reports = vec![];
+        let structs = Structs::from_scopes(context, &scopes, &mut reports);
+
         Builder {
             scopes,
-            reports: vec![],
-            structs: Structs::new(),
+            reports: RefCell::new(reports),
+            structs,
+            source_map,
+            variables: RefCell::new(HashMap::new()),
             builder: context.create_builder(),
             module: context.create_module("main"),
             context,
@@ -35,9 +63,29 @@ impl Builder<'_> {
         for scope in &self.scopes.scopes {
             self.scope(scope)
         }
+        // after every method is built
+        for scope in self.scopes.scopes.iter().filter(|scope| !is_generic(scope)) {
+            if let ScopeType::DefClass { members, .. } = &scope.scope_type {
+                self.build_vtable(&scope.path, members)
+            }
+        }
+    }
+
+    /// The errors found while building
+    pub fn take_reports(&self) -> Vec<Report> {
+        self.reports.take()
     }
 
     pub fn compile(&self, path: &Path) {
         self.module.write_bitcode_to_path(path);
     }
+
+    // a checked program the backend has no lowering for, or a bug in the
+    // checker
+    fn internal_error(&self, message: String) {
+        self.reports.borrow_mut().push(Report {
+            level: Level::Error,
+            error: ZXError::InternalError { message },
+        });
+    }
 }
@@ -0,0 +1,231 @@
+use std::sync::Mutex;
+
+use crate::Builder;
+use inkwell::basic_block::BasicBlock;
+use inkwell::values::{BasicValueEnum, FunctionValue, PointerValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
+use util::bytecode::{BytecodeType, MatchArm, Pattern};
+use util::zx_type::ZXTyped;
+
+impl<'a> Builder<'a> {
+    /// An `EnumValue` bytecode, a new struct of the variant holding its tag
+    /// and payload. The value points to the tag.
+    pub fn build_enum_value(
+        &self,
+        name: &str,
+        variant: &str,
+        tag: u32,
+        payload: &[BytecodeType],
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let fields = payload
+            .iter()
+            .map(|field| self.value(field, function))
+            .collect::<Option<Vec<BasicValueEnum>>>()?;
+        let value = self
+            .builder
+            .build_malloc(
+                self.structs.find(&format!("{}::{}", name, variant)),
+                "variant",
+            )
+            .ok()?;
+        let tag_field = self.builder.build_struct_gep(value, 0, "tag").ok()?;
+        self.builder.build_store(
+            tag_field,
+            self.context.i32_type().const_int(tag as u64, false),
+        );
+        for (index, field) in fields.into_iter().enumerate() {
+            let pointer = self
+                .builder
+                .build_struct_gep(value, index as u32 + 1, "field")
+                .ok()?;
+            self.builder.build_store(pointer, field);
+        }
+
+        Some(
+            self.builder
+                .build_pointer_cast(
+                    value,
+                    self.structs
+                        .find(&name.to_string())
+                        .ptr_type(AddressSpace::Generic),
+                    name,
+                )
+                .into(),
+        )
+    }
+
+    /// A `Match` bytecode. The arms are tried in order and a failed pattern
+    /// jumps to the next arm. Tags and `Int`, `Long`, `Char` and `Bool`
+    /// values are tested by a `switch`. The checker makes sure every value
+    /// matches an arm, nothing gets past the last one.
+    pub fn build_match(
+        &self,
+        matching: &BytecodeType,
+        function: FunctionValue<'a>,
+    ) -> Option<BasicValueEnum<'a>> {
+        let (value, result_type, arms) = match matching {
+            BytecodeType::Match {
+                value,
+                result_type,
+                arms,
+                ..
+            } => (value, result_type, arms),
+            _ => return None,
+        };
+        let value = self.value(value, function)?;
+        // every arm stores its value here
+        let result = match result_type {
+            ZXTyped::Void => None,
+            result_type => Some(self.builder.build_alloca(
+                self.structs.field_type(self.context, result_type)?,
+                "match_result",
+            )),
+        };
+        let end = self.context.append_basic_block(function, "match_end");
+
+        for arm in arms {
+            let next = self.context.append_basic_block(function, "match_next");
+            self.test_pattern(&arm.pattern, value, next, function)?;
+            self.match_arm(arm, result, function);
+            self.builder.build_unconditional_branch(end);
+            self.builder.position_at_end(next);
+        }
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(end);
+        result.map(|result| self.builder.build_load(result, "match"))
+    }
+
+    // the bytecodes of `arm`, the last one is stored to `result` and
+    // reported when it cannot be built
+    fn match_arm(
+        &self,
+        arm: &MatchArm,
+        result: Option<PointerValue<'a>>,
+        function: FunctionValue<'a>,
+    ) {
+        let index = Mutex::new(0);
+
+        match (result, arm.body.split_last()) {
+            (Some(result), Some((last, rest))) => {
+                rest.iter()
+                    .for_each(|bytecode| self.bytecodes(bytecode, function, &index));
+                if let Some(value) = self.required_value(last, function) {
+                    self.builder.build_store(result, value);
+                }
+            }
+            _ => arm
+                .body
+                .iter()
+                .for_each(|bytecode| self.bytecodes(bytecode, function, &index)),
+        }
+    }
+
+    // carries on after the test when `value` matches `pattern` and jumps to
+    // `failed` otherwise, the bindings are stored on the way
+    fn test_pattern(
+        &self,
+        pattern: &Pattern,
+        value: BasicValueEnum<'a>,
+        failed: BasicBlock<'a>,
+        function: FunctionValue<'a>,
+    ) -> Option<()> {
+        match pattern {
+            Pattern::Binding { path: Some(path) } => self.store_variable(path, value),
+            Pattern::Binding { path: None } => {}
+            Pattern::Value {
+                value: expected,
+                value_type,
+            } => {
+                let expected = self.value(expected, function)?;
+                let matched = self.context.append_basic_block(function, "pattern_matched");
+
+                match value_type {
+                    ZXTyped::Integer { .. }
+                    | ZXTyped::Long { .. }
+                    | ZXTyped::Char { .. }
+                    | ZXTyped::Bool { .. } => {
+                        let value = value.into_int_value();
+                        let expected = expected.into_int_value().const_cast(value.get_type(), true);
+
+                        self.builder
+                            .build_switch(value, failed, &[(expected, matched)]);
+                    }
+                    ZXTyped::Float { .. } | ZXTyped::Double { .. } => {
+                        let equal = self.builder.build_float_compare(
+                            FloatPredicate::OEQ,
+                            value.into_float_value(),
+                            expected.into_float_value(),
+                            "equal",
+                        );
+
+                        self.builder
+                            .build_conditional_branch(equal, matched, failed);
+                    }
+                    ZXTyped::String { .. } => {
+                        let order = self
+                            .builder
+                            .build_call(self.strcmp(), &[value.into(), expected.into()], "order")
+                            .try_as_basic_value()
+                            .left()?
+                            .into_int_value();
+                        let equal = self.builder.build_int_compare(
+                            IntPredicate::EQ,
+                            order,
+                            self.context.i32_type().const_zero(),
+                            "equal",
+                        );
+
+                        self.builder
+                            .build_conditional_branch(equal, matched, failed);
+                    }
+                    _ => return None,
+                }
+                self.builder.position_at_end(matched);
+            }
+            Pattern::Variant {
+                name,
+                variant,
+                tag,
+                fields,
+            } => {
+                let header = value.into_pointer_value();
+                let tag_field = self.builder.build_struct_gep(header, 0, "tag").ok()?;
+                let found = self.builder.build_load(tag_field, "tag").into_int_value();
+                let matched = self.context.append_basic_block(function, "variant_matched");
+                self.builder.build_switch(
+                    found,
+                    failed,
+                    &[(
+                        self.context.i32_type().const_int(*tag as u64, false),
+                        matched,
+                    )],
+                );
+                self.builder.position_at_end(matched);
+
+                // the payload follows the tag in the struct of the variant
+                let variant = self.builder.build_pointer_cast(
+                    header,
+                    self.structs
+                        .find(&format!("{}::{}", name, variant))
+                        .ptr_type(AddressSpace::Generic),
+                    "variant",
+                );
+                for (index, field) in fields.iter().enumerate() {
+                    if let Pattern::Binding { path: None } = field {
+                        continue;
+                    }
+                    let pointer = self
+                        .builder
+                        .build_struct_gep(variant, index as u32 + 1, "field")
+                        .ok()?;
+                    let field_value = self.builder.build_load(pointer, "field");
+                    self.test_pattern(field, field_value, failed, function)?;
+                }
+            }
+        }
+
+        Some(())
+    }
+}
@@ -1,8 +1,10 @@
 use std::sync::Mutex;
 
+use crate::generic::is_generic;
 use crate::Builder;
 use inkwell::{
-    types::{BasicMetadataTypeEnum, FunctionType},
+    types::{BasicMetadataTypeEnum, BasicType, FunctionType},
+    values::{BasicValueEnum, FunctionValue},
     AddressSpace,
 };
 use util::{
@@ -13,82 +15,195 @@ use util::{
 
 impl<'a> Builder<'a> {
     pub fn scope(&self, scope: &Scope) {
+        if is_generic(scope) {
+            return;
+        }
+
         match &scope.scope_type {
             ScopeType::DefFunction {
                 parameters,
                 block,
                 return_type,
-                children,
-            } => self.build_function(&scope.name, parameters, return_type, block),
+                ..
+            } => self.build_function(scope.name.as_str(), parameters, return_type, block),
+            ScopeType::DefClass { .. } => self.build_class(scope),
             _ => {}
         }
     }
 
     pub fn build_function(
         &self,
-        name: &String,
+        name: &str,
         parameters: &Vec<Scope>,
         ret_type: &ZXTyped,
         block: &BytecodeType,
     ) {
         let index = Mutex::new(parameters.len() + 2);
-        let function = self.module.add_function(
-            &name,
-            self.function_type(
-                ret_type,
-                self.function_parameters_type(parameters.clone()).as_slice(),
-            ),
-            None,
-        );
+        let function = match self.declare_function(name, parameters, ret_type) {
+            Some(function) => function,
+            None => {
+                return self.internal_error(format!(
+                    "a parameter or the return type of `{}` has no layout",
+                    name
+                ))
+            }
+        };
         let basic_block = self.context.append_basic_block(function, "entry");
         self.builder.position_at_end(basic_block);
-        self.bytecodes(block, function, &index);
+        // parameters live in stack slots like the other variables
+        for (index, parameter) in parameters.iter().enumerate() {
+            if let Some(value) = function.get_nth_param(index as u32) {
+                self.store_variable(&parameter.path, value);
+            }
+        }
+        self.build_body(name, block, ret_type, function, &index);
+    }
+
+    /// The body of the function `name`, the last bytecode is the value
+    /// returned unless `return_type` is `Void`. A value that cannot be built
+    /// is reported and nothing is returned.
+    pub fn build_body(
+        &self,
+        name: &str,
+        body: &BytecodeType,
+        return_type: &ZXTyped,
+        function: FunctionValue<'a>,
+        index: &Mutex<usize>,
+    ) {
+        let bytecodes = match body {
+            BytecodeType::Box { bytecodes } => bytecodes.as_slice(),
+            body => std::slice::from_ref(body),
+        };
+
+        match (return_type, bytecodes.split_last()) {
+            (ZXTyped::Void, _) => {
+                bytecodes
+                    .iter()
+                    .for_each(|bytecode| self.bytecodes(bytecode, function, index));
+                self.builder.build_return(None);
+            }
+            (_, Some((last, rest))) => {
+                rest.iter()
+                    .for_each(|bytecode| self.bytecodes(bytecode, function, index));
+                if let Some(result) = self.required_value(last, function) {
+                    self.builder.build_return(Some(&result));
+                }
+            }
+            (_, None) => self.internal_error(format!("`{}` returns no value", name)),
+        }
+    }
+
+    /// The function `name`, added without a body when it is not built yet.
+    /// A function used as a value can be built after the use.
+    pub fn declare_function(
+        &self,
+        name: &str,
+        parameters: &[Scope],
+        ret_type: &ZXTyped,
+    ) -> Option<FunctionValue<'a>> {
+        if let Some(function) = self.module.get_function(name) {
+            return Some(function);
+        }
+        let function_type = self.function_type(
+            ret_type,
+            self.function_parameters_type(parameters.to_vec())?
+                .as_slice(),
+        )?;
+
+        Some(self.module.add_function(name, function_type, None))
+    }
+
+    /// A new stack slot for the variable at `path` holding `value`
+    pub fn store_variable(&self, path: &str, value: BasicValueEnum<'a>) {
+        let variable = self.builder.build_alloca(value.get_type(), path);
+
+        self.builder.build_store(variable, value);
+        self.variables
+            .borrow_mut()
+            .insert(path.to_string(), variable);
     }
 
     pub fn function_type(
         &self,
         ret_type: &ZXTyped,
         param_types: &[BasicMetadataTypeEnum<'a>],
-    ) -> FunctionType<'a> {
-        match ret_type {
+    ) -> Option<FunctionType<'a>> {
+        Some(match ret_type {
             ZXTyped::Integer { .. } => self.context.i32_type().fn_type(param_types, false),
+            ZXTyped::Long { .. } => self.context.i64_type().fn_type(param_types, false),
             ZXTyped::Float { .. } => self.context.f32_type().fn_type(param_types, false),
+            ZXTyped::Double { .. } => self.context.f64_type().fn_type(param_types, false),
+            ZXTyped::Bool { .. } => self.context.bool_type().fn_type(param_types, false),
             ZXTyped::Char { .. } => self.context.i8_type().fn_type(param_types, false),
             ZXTyped::String { .. } => self
                 .context
                 .i8_type()
                 .ptr_type(AddressSpace::Generic)
                 .fn_type(param_types, false),
+            ZXTyped::Array { element, .. } => self
+                .structs
+                .array_type(self.context, element)?
+                .ptr_type(AddressSpace::Generic)
+                .fn_type(param_types, false),
+            ZXTyped::Range { element } => self
+                .structs
+                .range_type(self.context, element)?
+                .fn_type(param_types, false),
+            ZXTyped::Function { .. } => self
+                .structs
+                .closure_type(self.context)
+                .fn_type(param_types, false),
+            // objects and values of an enum are pointers
+            ZXTyped::Other(_) | ZXTyped::Generic { .. } => self
+                .structs
+                .field_type(self.context, ret_type)?
+                .fn_type(param_types, false),
             ZXTyped::Void => self.context.void_type().fn_type(param_types, false),
             _ => self.context.void_type().fn_type(param_types, false),
-        }
+        })
     }
 
+    /// The types of `parameters`, `None` when one of them has no layout
     pub fn function_parameters_type(
         &self,
         parameters: Vec<Scope>,
-    ) -> Vec<BasicMetadataTypeEnum<'a>> {
+    ) -> Option<Vec<BasicMetadataTypeEnum<'a>>> {
         parameters
             .iter()
             .map(|parameter| {
-                if let ScopeType::DefVariable { var_type, .. } = &parameter.scope_type {
-                    Ok(match var_type {
-                        ZXTyped::Char { .. } => self.context.i8_type().into(),
-                        ZXTyped::String { .. } => self
-                            .context
-                            .i8_type()
-                            .ptr_type(AddressSpace::Generic)
-                            .into(),
-                        ZXTyped::Integer { .. } => self.context.i32_type().into(),
-                        ZXTyped::Float { .. } => self.context.f32_type().into(),
-                        ZXTyped::Other(name) => self.structs.find(&name).into(),
-                        ZXTyped::Void => todo!("error"),
-                    })
-                } else {
-                    Err(())
-                }
+                let var_type = match &parameter.scope_type {
+                    ScopeType::DefVariable { var_type, .. } => var_type,
+                    _ => return None,
+                };
+
+                Some(match var_type {
+                    ZXTyped::Char { .. } => self.context.i8_type().into(),
+                    ZXTyped::String { .. } => self
+                        .context
+                        .i8_type()
+                        .ptr_type(AddressSpace::Generic)
+                        .into(),
+                    ZXTyped::Integer { .. } => self.context.i32_type().into(),
+                    ZXTyped::Long { .. } => self.context.i64_type().into(),
+                    ZXTyped::Float { .. } => self.context.f32_type().into(),
+                    ZXTyped::Double { .. } => self.context.f64_type().into(),
+                    ZXTyped::Bool { .. } => self.context.bool_type().into(),
+                    // objects and values of an enum are pointers
+                    ZXTyped::Other(_) | ZXTyped::Generic { .. } => {
+                        self.structs.field_type(self.context, var_type)?.into()
+                    }
+                    ZXTyped::Array { element, .. } => self
+                        .structs
+                        .array_type(self.context, element)?
+                        .ptr_type(AddressSpace::Generic)
+                        .into(),
+                    ZXTyped::Range { element } => {
+                        self.structs.range_type(self.context, element)?.into()
+                    }
+                    ZXTyped::Function { .. } => self.structs.closure_type(self.context).into(),
+                    ZXTyped::Param(_) | ZXTyped::Void => return None,
+                })
             })
-            .collect::<Result<Vec<BasicMetadataTypeEnum>, ()>>()
-            .unwrap()
+            .collect()
     }
 }
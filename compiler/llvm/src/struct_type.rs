@@ -1,4 +1,11 @@
-use inkwell::types::StructType;
+use crate::generic::is_generic;
+use inkwell::context::Context;
+use inkwell::types::{BasicType, BasicTypeEnum, StructType};
+use inkwell::AddressSpace;
+use util::error::ZXError;
+use util::report::{Level, Report};
+use util::scope::{ScopeType, Scopes};
+use util::zx_type::ZXTyped;
 
 pub struct Structs<'a> {
     structs: Vec<(String, StructType<'a>)>,
@@ -11,6 +18,109 @@ impl<'a> Structs<'a> {
         }
     }
 
+    /// A struct for every class of `scopes`, field 0 points to the vtable of
+    /// the class and the fields follow in member order. Inherited members
+    /// come first, so the struct of a base class is a prefix of the struct of
+    /// every class inheriting from it. Generic classes only get a struct for
+    /// each of their instances.
+    ///
+    /// An enum is a tagged union, its values point to `{ i32 }` named like
+    /// the enum, the tag of the variant. Every variant has a struct of its
+    /// own named `Shape::Circle`, the tag followed by the payload.
+    ///
+    /// A field without a layout is reported to `reports`, its struct is
+    /// left opaque.
+    pub fn from_scopes(
+        context: &'a Context,
+        scopes: &Scopes,
+        reports: &mut Vec<Report>,
+    ) -> Structs<'a> {
+        let mut structs: Structs<'a> = Structs::new();
+        let classes = scopes
+            .scopes
+            .iter()
+            .filter(|scope| !is_generic(scope))
+            .filter_map(|scope| match &scope.scope_type {
+                ScopeType::DefClass { members, .. } => Some((scope.name.to_string(), members)),
+                _ => None,
+            })
+            .collect::<Vec<(String, &Scopes)>>();
+        let variants = scopes
+            .scopes
+            .iter()
+            .filter_map(|scope| match &scope.scope_type {
+                ScopeType::DefEnum { variants } => Some((scope.name.to_string(), variants)),
+                _ => None,
+            })
+            .flat_map(|(name, variants)| {
+                variants
+                    .scopes
+                    .iter()
+                    .filter_map(move |variant| match &variant.scope_type {
+                        ScopeType::DefVariant { payload, .. } => {
+                            Some((format!("{}::{}", name, variant.name), payload))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect::<Vec<(String, &Vec<ZXTyped>)>>();
+
+        // every class and enum has a type before the fields are laid out, a
+        // field can point to any of them
+        for (name, _) in &classes {
+            structs
+                .structs
+                .push((name.clone(), context.opaque_struct_type(name)));
+        }
+        for scope in &scopes.scopes {
+            if let ScopeType::DefEnum { .. } = scope.scope_type {
+                let name = scope.name.to_string();
+                let header = context.opaque_struct_type(&name);
+                header.set_body(&[context.i32_type().into()], false);
+                structs.structs.push((name, header));
+            }
+        }
+        for (name, _) in &variants {
+            structs
+                .structs
+                .push((name.clone(), context.opaque_struct_type(name)));
+        }
+        for (name, members) in &classes {
+            let vtable = context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .ptr_type(AddressSpace::Generic);
+            let fields = std::iter::once(Some(BasicTypeEnum::PointerType(vtable)))
+                .chain(
+                    members
+                        .scopes
+                        .iter()
+                        .filter_map(|member| match &member.scope_type {
+                            ScopeType::DefVariable { var_type, .. } => {
+                                Some(structs.field_type(context, var_type))
+                            }
+                            _ => None,
+                        }),
+                )
+                .collect::<Option<Vec<BasicTypeEnum<'a>>>>();
+
+            structs.set_body(name, fields, reports);
+        }
+        for (name, payload) in &variants {
+            let fields = std::iter::once(Some(context.i32_type().into()))
+                .chain(
+                    payload
+                        .iter()
+                        .map(|field| structs.field_type(context, field)),
+                )
+                .collect::<Option<Vec<BasicTypeEnum<'a>>>>();
+
+            structs.set_body(name, fields, reports);
+        }
+
+        structs
+    }
+
     pub fn find(&self, name: &String) -> StructType<'a> {
         self.structs
             .iter()
@@ -18,4 +128,82 @@ impl<'a> Structs<'a> {
             .unwrap()
             .1
     }
+
+    fn set_body(
+        &self,
+        name: &String,
+        fields: Option<Vec<BasicTypeEnum<'a>>>,
+        reports: &mut Vec<Report>,
+    ) {
+        match fields {
+            Some(fields) => {
+                self.find(name).set_body(&fields, false);
+            }
+            None => reports.push(Report {
+                level: Level::Error,
+                error: ZXError::InternalError {
+                    message: format!("a field of `{}` has no layout", name),
+                },
+            }),
+        }
+    }
+
+    /// `{ i64, [0 x T] }`, the length of an array followed by its elements.
+    /// The elements of `[]` have no type yet, an `i8` stands in for them.
+    pub fn array_type(&self, context: &'a Context, element: &ZXTyped) -> Option<StructType<'a>> {
+        let element = match element {
+            ZXTyped::Void => context.i8_type().into(),
+            element => self.field_type(context, element)?,
+        };
+
+        Some(context.struct_type(
+            &[context.i64_type().into(), element.array_type(0).into()],
+            false,
+        ))
+    }
+
+    /// `{ T, T, i1 }`, the start, the end and whether the end is included
+    pub fn range_type(&self, context: &'a Context, element: &ZXTyped) -> Option<StructType<'a>> {
+        let bound = self.field_type(context, element)?;
+
+        Some(context.struct_type(&[bound, bound, context.bool_type().into()], false))
+    }
+
+    /// `{ i8*, i8* }`, the code of a function value and the environment
+    /// holding what it captured, null when it captured nothing
+    pub fn closure_type(&self, context: &'a Context) -> StructType<'a> {
+        let i8_ptr = context.i8_type().ptr_type(AddressSpace::Generic);
+
+        context.struct_type(&[i8_ptr.into(), i8_ptr.into()], false)
+    }
+
+    // objects and arrays are stored as pointers. A type parameter left in
+    // an instance and `Void` have no layout.
+    pub fn field_type(
+        &self,
+        context: &'a Context,
+        var_type: &ZXTyped,
+    ) -> Option<BasicTypeEnum<'a>> {
+        Some(match var_type {
+            ZXTyped::Char { .. } => context.i8_type().into(),
+            ZXTyped::String { .. } => context.i8_type().ptr_type(AddressSpace::Generic).into(),
+            ZXTyped::Integer { .. } => context.i32_type().into(),
+            ZXTyped::Long { .. } => context.i64_type().into(),
+            ZXTyped::Float { .. } => context.f32_type().into(),
+            ZXTyped::Double { .. } => context.f64_type().into(),
+            ZXTyped::Bool { .. } => context.bool_type().into(),
+            ZXTyped::Other(name) => self.find(name).ptr_type(AddressSpace::Generic).into(),
+            ZXTyped::Generic { .. } => self
+                .find(&var_type.to_string())
+                .ptr_type(AddressSpace::Generic)
+                .into(),
+            ZXTyped::Array { element, .. } => self
+                .array_type(context, element)?
+                .ptr_type(AddressSpace::Generic)
+                .into(),
+            ZXTyped::Range { element } => self.range_type(context, element)?.into(),
+            ZXTyped::Function { .. } => self.closure_type(context).into(),
+            ZXTyped::Param(_) | ZXTyped::Void => return None,
+        })
+    }
 }
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod test {
+    use crate::{Builder, Context};
+    use check::Checker;
+    use inkwell::execution_engine::JitFunction;
+    use inkwell::targets::{InitializationConfig, Target};
+    use inkwell::OptimizationLevel;
+    use lexer::Lexer;
+    use parser::Parser;
+    use util::report::Level;
+    use util::source_map::{FileId, SourceMap};
+
+    // build `source` and run its `main`, which takes no arguments and
+    // returns an `Int`
+    fn run(source: &str) -> i32 {
+        let source_map = SourceMap::new();
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        parser.parse(&source_map);
+        assert!(!parser.has_errors(), "`{}` does not parse", source);
+        let mut checker = Checker::new(parser.asts);
+        let scopes = checker.check();
+        let errors = checker
+            .reposts
+            .iter()
+            .filter(|report| matches!(report.level, Level::Error))
+            .map(|report| format!("{:?}", report.error))
+            .collect::<Vec<String>>();
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let context = Context::create();
+        let builder = Builder::new(scopes, &source_map, &context);
+        builder.build();
+        let reports = builder
+            .take_reports()
+            .iter()
+            .map(|report| format!("{:?}", report.error))
+            .collect::<Vec<String>>();
+        assert!(reports.is_empty(), "{:?}", reports);
+        if let Err(error) = builder.module.verify() {
+            panic!("{}", error.to_string());
+        }
+
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = builder
+            .module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let main: JitFunction<unsafe extern "C" fn() -> i32> =
+                engine.get_function("main").unwrap();
+            main.call()
+        }
+    }
+
+    const SHAPE: &str = "class Shape {\n    fn area(): Int { 1 }\n    fn describe(): Int { this.area() }\n}\nclass Square : Shape {\n    override fn area(): Int { 4 }\n}\n";
+
+    #[test]
+    fn test_virtual_methods() {
+        // `this` of `describe` is a `Shape`
+        assert_eq!(
+            run(&format!(
+                "{}fn main(): Int {{\n    Shape().describe()\n}}",
+                SHAPE
+            )),
+            1
+        );
+        assert_eq!(
+            run(&format!(
+                "{}fn main(): Int {{\n    Square().describe()\n}}",
+                SHAPE
+            )),
+            4
+        );
+        // `super.area()` is not looked up in the vtable of the `Cube`
+        assert_eq!(
+            run(&format!(
+                "{}class Cube : Square {{\n    override fn area(): Int {{ super.area() }}\n}}\nfn main(): Int {{\n    Cube().describe()\n}}",
+                SHAPE
+            )),
+            4
+        );
+    }
+
+    #[test]
+    fn test_field_loads() {
+        // `legs` comes first in a `Dog`, before the fields it declares
+        assert_eq!(
+            run("class Animal {\n    var legs = 4\n}\nclass Dog : Animal {\n    var age = 3\n    fn years(): Int { this.age }\n}\nfn main(): Int {\n    var dog = Dog()\n    var legs = dog.legs\n    legs * 10 + dog.years()\n}"),
+            43
+        );
+    }
+
+    #[test]
+    fn test_field_stores() {
+        assert_eq!(
+            run("class Counter {\n    var n = 1\n    fn add(): Int {\n        this.n += 2\n        this.n\n    }\n}\nfn main(): Int {\n    var counter = Counter()\n    counter.n = 10\n    counter.add()\n}"),
+            12
+        );
+    }
+}
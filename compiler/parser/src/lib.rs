@@ -1,34 +1,69 @@
 mod syntax;
 
-use std::slice::Iter;
+use std::iter::Peekable;
 use util::ast::Statement;
 use util::error::ZXError;
 use util::report::{Level, Report};
-use util::token::Token;
-use util::token::Tokens;
+use util::source_map::{FileId, SourceMap};
+use util::token::{Position, Token, TokenKind, Tokens};
+
+type TokenStream<'a> = Box<dyn Iterator<Item = Result<Token, ZXError>> + 'a>;
 
 pub struct Parser<'a> {
-    pub tokens: Iter<'a, Token>,
+    pub tokens: Peekable<TokenStream<'a>>,
     pub index: usize,
-    currently: &'a Token,
+    currently: Token,
     is_eof: bool,
     pub asts: Vec<Statement>,
-    reposts: Vec<Report>,
+    /// every error and warning found while parsing, in source order
+    pub reposts: Vec<Report>,
+    // doc comments right before `currently`
+    docs: Vec<Token>,
+    // a line break was skipped right before `currently`
+    newline_before: bool,
+    // depth of the brackets around `currently`, line breaks inside them
+    // never end an expression
+    nesting: usize,
 }
 
-impl Parser<'_> {
-    pub fn new(tokens: &Vec<Token>) -> Parser {
-        let mut tokens_iter = tokens.iter();
-        let next_token = tokens_iter.next().unwrap();
-
-        Parser {
-            tokens: tokens_iter,
+impl<'a> Parser<'a> {
+    /// Parse the tokens as they are produced, usually straight from a `Lexer`.
+    /// Lexical errors in the stream are reported together with the syntax
+    /// errors.
+    pub fn new<I>(tokens: I) -> Parser<'a>
+    where
+        I: IntoIterator<Item = Result<Token, ZXError>>,
+        I::IntoIter: 'a,
+    {
+        let tokens: TokenStream<'a> = Box::new(tokens.into_iter());
+        let mut parser = Parser {
+            tokens: tokens.peekable(),
             index: 0,
-            is_eof: next_token.is_token_type(&Tokens::EOF),
-            currently: next_token,
+            currently: Token {
+                token_type: Tokens::EOF,
+                pos: Position {
+                    file: FileId(0),
+                    start: 0,
+                    end: 0,
+                },
+            },
+            is_eof: true,
             asts: vec![],
             reposts: vec![],
-        }
+            docs: vec![],
+            newline_before: false,
+            nesting: 0,
+        };
+
+        parser.next(false);
+        parser.index = 0;
+        parser.newline_before = false;
+        parser
+    }
+
+    /// Parse tokens that were already lexed
+    pub fn from_tokens(tokens: &'a [Token]) -> Parser<'a> {
+        Parser::new(tokens.iter().cloned().map(Ok))
     }
 
     pub fn comparison(&mut self, token: &Tokens) -> Result<Token, ZXError> {
@@ -52,76 +87,223 @@ impl Parser<'_> {
         }
     }
 
-    pub fn comparison_string(&mut self, tokens: Vec<&str>) -> Result<Token, ZXError> {
-        for token in &tokens {
-            if self.currently.is_token_type_str(token) {
-                let ret_token = self.currently.clone();
-                self.next(*token == "LineSeparatorToken");
-                return Ok(ret_token);
-            }
+    pub fn comparison_kinds(&mut self, kinds: &[TokenKind]) -> Result<Token, ZXError> {
+        let kind = self.currently.kind();
+
+        if kinds.contains(&kind) {
+            let ret_token = self.currently.clone();
+            self.next(kind == TokenKind::LineSeparatorToken);
+            return Ok(ret_token);
         }
 
         Err(ZXError::SyntaxError {
             message: format!(
                 "Unexpected token {}, expected token {}",
-                self.currently.token_type.to_string(),
-                tokens.iter().map(|&x| x).collect::<Vec<&str>>().join(", ")
+                kind,
+                kinds
+                    .iter()
+                    .map(|kind| kind.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
             ),
             pos: self.currently.pos.clone(),
         })
     }
 
+    /// Expect a name, reserved words get their own error
+    pub fn identifier(&mut self) -> Result<Token, ZXError> {
+        if let Some(keyword) = self.currently.token_type.keyword_str() {
+            return Err(ZXError::SyntaxError {
+                message: format!(
+                    "expected identifier, found keyword `{}`, reserved words cannot be used as names",
+                    keyword
+                ),
+                pos: self.currently.pos.clone(),
+            });
+        }
+
+        self.comparison_kinds(&[TokenKind::IdentifierToken])
+    }
+
     pub fn next(&mut self, line_separator_token: bool) {
+        self.docs.clear();
+        self.newline_before = false;
         let token = loop {
-            let token = self.tokens.next();
-
-            if let Some(content) = token {
-                if !content.is_token_type(&Tokens::LineSeparatorToken) || line_separator_token {
-                    break token;
+            let content = match self.tokens.next() {
+                Some(Ok(content)) => content,
+                Some(Err(error)) => {
+                    self.add_error(error);
+                    continue;
                 }
-            } else {
-                break token;
+                None => break None,
+            };
+
+            // the error was reported with the `Err` before it
+            if content.is_token_type(&Tokens::ErrorToken) {
+                continue;
+            }
+            if let Tokens::DocCommentToken { .. } = content.token_type {
+                self.docs.push(content);
+                continue;
+            }
+            if !content.is_token_type(&Tokens::LineSeparatorToken) || line_separator_token {
+                break Some(content);
             }
+            self.newline_before = true;
         };
 
         self.is_eof = token.is_none();
 
-        if !self.is_eof {
-            self.currently = token.unwrap();
-            if !token.unwrap().is_token_type(&Tokens::EOF) {
+        if let Some(token) = token {
+            if !token.is_token_type(&Tokens::EOF) {
                 self.index += 1;
             } else {
                 self.is_eof = true;
             }
+            self.currently = token;
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.reposts
+            .iter()
+            .any(|repost| matches!(repost.level, Level::Error))
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.is_eof
+    }
+
+    /// Whether the current token can continue the expression before it, a
+    /// line break ends the expression unless it is inside brackets
+    pub fn line_continues(&self) -> bool {
+        !self.newline_before || self.nesting > 0
+    }
+
+    /// A statement ends with `;`, a line break, the `}` of its block or the
+    /// end of the file
+    pub fn end_of_statement(&mut self) -> Result<(), ZXError> {
+        match self.currently.token_type {
+            Tokens::SemicolonToken => {
+                while self.currently.is_token_type(&Tokens::SemicolonToken) {
+                    self.comparison(&Tokens::SemicolonToken)?;
+                }
+                Ok(())
+            }
+            Tokens::RightCurlyBracketsToken => Ok(()),
+            _ if self.is_eof || self.newline_before => Ok(()),
+            _ => Err(ZXError::SyntaxError {
+                message: format!(
+                    "expected `;` or a line break between statements, found {}",
+                    self.currently.token_type
+                ),
+                pos: self.currently.pos.clone(),
+            }),
         }
     }
 
     pub fn peek(&mut self) -> Option<&Token> {
-        let mut tokens = self.tokens.clone();
-        tokens.next()
+        self.tokens.peek().and_then(|token| token.as_ref().ok())
+    }
+
+    /// Text of the doc comments right before the current token
+    pub fn take_doc(&mut self) -> Option<String> {
+        if self.docs.is_empty() {
+            return None;
+        }
+
+        let doc = self
+            .docs
+            .iter()
+            .map(|token| token.get_string())
+            .collect::<Result<Vec<String>, ZXError>>()
+            .ok()?
+            .join("\n");
+        self.docs.clear();
+
+        Some(doc)
     }
 
     fn add_error(&mut self, error: ZXError) {
-        self.reposts.push(Report {
-            level: Level::Error,
-            error: error,
-        });
+        let level = match error {
+            ZXError::Warning { .. } => Level::Warning,
+            _ => Level::Error,
+        };
+
+        self.reposts.push(Report { level, error });
     }
 
-    pub fn parse(&mut self, path: &String, source: &String) {
-        while !self.is_eof {
-            let statement = self.statement();
+    /// Parse a statement and what ends it. A syntax error is reported and the
+    /// parser skips to the next statement boundary, the broken statement is
+    /// kept as `Statement::Error` so one typo does not hide later errors.
+    pub fn recoverable_statement(&mut self) -> Statement {
+        let start = self.index;
+        let pos = self.currently.pos.clone();
+        let statement = self
+            .statement()
+            .and_then(|statement| self.end_of_statement().map(|_| statement));
 
-            if let Ok(statement) = statement {
-                self.asts.push(statement);
-            } else if let Err(error) = statement {
-                self.add_error(error);
-                break;
+        statement.unwrap_or_else(|error| {
+            self.add_error(error);
+            self.synchronize();
+            // the statement failed on a boundary, skip it so parsing moves on
+            if self.index == start && !self.is_eof {
+                self.next(false);
             }
+
+            Statement::Error { pos }
+        })
+    }
+
+    // Skip to `}`, past `;`, to the first token of the next line or to a
+    // keyword that starts a statement. Blocks in the skipped tokens are
+    // skipped as a whole.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        self.nesting = 0;
+
+        while !self.is_eof {
+            match self.currently.token_type {
+                Tokens::LeftCurlyBracketsToken => depth += 1,
+                Tokens::RightCurlyBracketsToken if depth == 0 => return,
+                Tokens::RightCurlyBracketsToken => depth -= 1,
+                Tokens::SemicolonToken if depth == 0 => {
+                    self.next(false);
+                    return;
+                }
+                Tokens::FnToken
+                | Tokens::ClassToken
+                | Tokens::EnumToken
+                | Tokens::MatchToken
+                | Tokens::VarToken
+                | Tokens::IfToken
+                | Tokens::WhileToken
+                | Tokens::ForToken
+                | Tokens::ReturnToken
+                | Tokens::PubToken
+                | Tokens::StaticToken
+                | Tokens::OverrideToken
+                | Tokens::ImToken
+                    if depth == 0 =>
+                {
+                    return
+                }
+                _ if depth == 0 && self.newline_before => return,
+                _ => {}
+            }
+
+            self.next(false);
+        }
+    }
+
+    pub fn parse(&mut self, source_map: &SourceMap) {
+        while !self.is_eof {
+            let statement = self.recoverable_statement();
+            self.asts.push(statement);
         }
 
         for repost in &self.reposts {
-            repost.print(source, path);
+            repost.print(source_map);
         }
     }
 }
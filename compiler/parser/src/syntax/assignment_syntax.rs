@@ -0,0 +1,61 @@
+use crate::syntax::syntax_util::set_error_message;
+use crate::Parser;
+use util::ast::{Expression, Operator, Statement};
+use util::error::ZXError;
+use util::token::Tokens;
+
+impl Parser<'_> {
+    /// An expression, or an assignment to it when `=` or a compound
+    /// assignment operator follows
+    pub fn expression_syntax(&mut self) -> Result<Statement, ZXError> {
+        let expression = self.expressions(0)?;
+        let operator_type = match self.currently.token_type {
+            Tokens::EqualToken => None,
+            Tokens::PlusEqualToken => Some(Operator::Add),
+            Tokens::MinusEqualToken => Some(Operator::Sub),
+            Tokens::MultiplyEqualToken => Some(Operator::Mul),
+            Tokens::SlashEqualToken => Some(Operator::Div),
+            Tokens::PercentEqualToken => Some(Operator::Mod),
+            _ => return Ok(Statement::Expression { expression }),
+        };
+        let equal = self.comparison(&self.currently.token_type.clone())?;
+
+        if !is_assignable(&expression) {
+            return Err(ZXError::SyntaxError {
+                message: String::from("invalid left-hand side of assignment"),
+                pos: equal.pos,
+            });
+        }
+
+        let value = set_error_message(
+            self.expressions(0),
+            String::from("expected an expression after the assignment operator"),
+            &equal.pos,
+        )?;
+
+        Ok(Statement::Assignment {
+            target: expression,
+            equal,
+            operator_type,
+            value,
+        })
+    }
+}
+
+// a variable, a field of one or an element of them, `a`, `a.b.c` or
+// `a.b[0]`
+fn is_assignable(expression: &Expression) -> bool {
+    match expression {
+        Expression::Index {
+            target, next: None, ..
+        } => is_assignable(target),
+        Expression::Identifier { next: None, .. } => true,
+        Expression::Identifier {
+            next: Some(next), ..
+        } => match next.as_ref() {
+            Expression::SubMember { sub_member } => is_assignable(sub_member),
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -7,9 +7,11 @@ impl Parser<'_> {
     pub fn block_syntax(&mut self) -> Result<Statement, ZXError> {
         let left_curly_brackets = self.comparison(&Tokens::LeftCurlyBracketsToken)?;
         let mut statements = vec![];
+        // line breaks end statements again, even inside brackets
+        let nesting = std::mem::take(&mut self.nesting);
 
         loop {
-            match self.currently {
+            match &self.currently {
                 token if token.is_token_type(&Tokens::RightCurlyBracketsToken) => break,
                 _ if self.is_eof => {
                     return Err(ZXError::SyntaxError {
@@ -17,9 +19,10 @@ impl Parser<'_> {
                         pos: left_curly_brackets.pos,
                     });
                 }
-                _ => statements.push(self.statement()?),
+                _ => statements.push(self.recoverable_statement()),
             }
         }
+        self.nesting = nesting;
 
         let right_curly_brackets = self.comparison(&Tokens::RightCurlyBracketsToken)?;
 
@@ -5,13 +5,21 @@ use util::token::Tokens;
 
 impl Parser<'_> {
     pub fn class_syntax(&mut self) -> Result<Statement, ZXError> {
-        let class_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-        let class_name = self.comparison_string(vec!["IdentifierToken"])?;
+        let class_keyword = self.comparison(&Tokens::ClassToken)?;
+        let class_name = self.identifier()?;
+        let type_parameters = self.type_parameters()?;
+        let inherit = match self.currently.token_type {
+            Tokens::ColonToken => {
+                self.comparison(&Tokens::ColonToken)?;
+                Some(self.identifier()?)
+            }
+            _ => None,
+        };
         let left_curly_bracket = self.comparison(&Tokens::LeftCurlyBracketsToken)?;
         let mut member: Vec<Statement> = vec![];
 
         loop {
-            match self.currently {
+            match &self.currently {
                 token if token.is_token_type(&Tokens::RightCurlyBracketsToken) => break,
                 _ if self.is_eof => {
                     return Err(ZXError::SyntaxError {
@@ -20,20 +28,24 @@ impl Parser<'_> {
                     });
                 }
                 _ => {
-                    let statement = self.statement()?;
+                    let pos = self.currently.pos.clone();
+                    let statement = self.recoverable_statement();
 
                     match statement {
                         Statement::Public { .. }
                         | Statement::Static { .. }
+                        | Statement::Override { .. }
                         | Statement::FunctionDeclaration { .. }
-                        | Statement::VariableDeclaration { .. } => {
+                        | Statement::VariableDeclaration { .. }
+                        | Statement::Error { .. } => {
                             member.push(statement);
                         }
                         _ => {
-                            return Err(ZXError::SyntaxError {
-                                pos: left_curly_bracket.pos,
+                            self.add_error(ZXError::SyntaxError {
+                                pos: pos.clone(),
                                 message: "unknown statement".to_string(),
-                            })
+                            });
+                            member.push(Statement::Error { pos });
                         }
                     }
                 }
@@ -43,10 +55,12 @@ impl Parser<'_> {
         self.comparison(&Tokens::RightCurlyBracketsToken)?;
 
         Ok(Statement::Class {
+            doc: None,
             class_keyword,
             class_name,
+            type_parameters,
             clone: None,
-            inherit: None,
+            inherit,
             member,
         })
     }
@@ -0,0 +1,77 @@
+use crate::Parser;
+use util::ast::{Statement, Variant};
+use util::error::ZXError;
+use util::token::Tokens;
+
+impl Parser<'_> {
+    /// `enum Shape { Circle(Float), Rect(Float, Float), Empty }`, variants
+    /// are separated by `,` or line breaks
+    pub fn enum_syntax(&mut self) -> Result<Statement, ZXError> {
+        let enum_keyword = self.comparison(&Tokens::EnumToken)?;
+        let enum_name = self.identifier()?;
+        let left_curly_brackets = self.comparison(&Tokens::LeftCurlyBracketsToken)?;
+        let nesting = std::mem::take(&mut self.nesting);
+        let mut variants = vec![];
+
+        while !self
+            .currently
+            .is_token_type(&Tokens::RightCurlyBracketsToken)
+        {
+            if self.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "unclosed curly bracket".to_string(),
+                    pos: left_curly_brackets.pos,
+                });
+            }
+            if !variants.is_empty() && !self.newline_before {
+                self.comparison(&Tokens::CommaToken)?;
+            }
+            // a `,` after the last variant is allowed
+            if self
+                .currently
+                .is_token_type(&Tokens::RightCurlyBracketsToken)
+            {
+                break;
+            }
+
+            variants.push(self.variant()?);
+        }
+        self.nesting = nesting;
+        self.comparison(&Tokens::RightCurlyBracketsToken)?;
+
+        Ok(Statement::Enum {
+            doc: None,
+            enum_keyword,
+            enum_name,
+            variants,
+        })
+    }
+
+    // `Circle(Float)` or `Empty`
+    fn variant(&mut self) -> Result<Variant, ZXError> {
+        let name = self.identifier()?;
+        let mut payload = vec![];
+
+        if self.currently.is_token_type(&Tokens::LeftParenthesesToken) {
+            let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
+            self.nesting += 1;
+
+            while !self.currently.is_token_type(&Tokens::RightParenthesesToken) {
+                if self.is_eof {
+                    return Err(ZXError::SyntaxError {
+                        message: "unclosed parenthese".to_string(),
+                        pos: left_parentheses.pos,
+                    });
+                }
+                if !payload.is_empty() {
+                    self.comparison(&Tokens::CommaToken)?;
+                }
+                payload.push(self.type_expression()?);
+            }
+            self.nesting -= 1;
+            self.comparison(&Tokens::RightParenthesesToken)?;
+        }
+
+        Ok(Variant { name, payload })
+    }
+}
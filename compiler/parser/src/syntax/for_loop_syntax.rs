@@ -1,20 +1,26 @@
+use crate::syntax::syntax_util::set_error_message;
 use crate::Parser;
 use util::ast::Statement;
 use util::error::ZXError;
+use util::token::Tokens;
 
 impl Parser<'_> {
     pub(crate) fn for_syntax(&mut self) -> Result<Statement, ZXError> {
-        let for_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-        let for_var_name = self.comparison_string(vec!["IdentifierToken"])?;
-        let for_in_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-        let iter = Box::new(self.statement()?);
+        let for_keyword = self.comparison(&Tokens::ForToken)?;
+        let for_var_name = self.identifier()?;
+        let for_in_keyword = self.comparison(&Tokens::InToken)?;
+        let iter = set_error_message(
+            self.expressions(0),
+            String::from("expected an iterable after `in`"),
+            &for_in_keyword.pos,
+        )?;
         let block = self.block_syntax()?;
 
         Ok(Statement::ForLoop {
             for_keyword,
             for_var_name,
             for_in_keyword,
-            iter,
+            iter: Box::new(Statement::Expression { expression: iter }),
             block: Box::new(block),
         })
     }
@@ -5,8 +5,9 @@ use util::token::{Position, Tokens};
 
 impl Parser<'_> {
     pub fn function_syntax(&mut self) -> Result<Statement, ZXError> {
-        let fn_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-        let function_name = self.comparison_string(vec!["IdentifierToken"])?;
+        let fn_keyword = self.comparison(&Tokens::FnToken)?;
+        let function_name = self.identifier()?;
+        let type_parameters = self.type_parameters()?;
         let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
         let parameters = self.parameters_parse(left_parentheses.pos.clone())?;
         let right_parentheses = self.comparison(&Tokens::RightParenthesesToken)?;
@@ -17,8 +18,10 @@ impl Parser<'_> {
         let block = self.block_syntax()?;
 
         Ok(Statement::FunctionDeclaration {
+            doc: None,
             fn_keyword,
             function_name,
+            type_parameters,
             left_parentheses,
             parameters,
             right_parentheses,
@@ -27,6 +30,28 @@ impl Parser<'_> {
         })
     }
 
+    /// `fn(a: Int): Int { a + 1 }`, the return type can be left out
+    pub(crate) fn function_expression(&mut self) -> Result<Expression, ZXError> {
+        let fn_keyword = self.comparison(&Tokens::FnToken)?;
+        let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
+        let parameters = self.parameters_parse(left_parentheses.pos.clone())?;
+        let right_parentheses = self.comparison(&Tokens::RightParenthesesToken)?;
+        let return_type = match self.currently.token_type {
+            Tokens::ColonToken => Some(Box::new(self.type_syntax()?)),
+            _ => None,
+        };
+        let block = self.block_syntax()?;
+
+        Ok(Expression::Function {
+            fn_keyword,
+            left_parentheses,
+            parameters,
+            right_parentheses,
+            return_type,
+            block: Box::new(block),
+        })
+    }
+
     fn parameters_parse(&mut self, left: Position) -> Result<Vec<Parameter>, ZXError> {
         let mut parameters: Vec<Parameter> = vec![];
         let mut comma = true;
@@ -42,7 +67,7 @@ impl Parser<'_> {
                 Tokens::IdentifierToken { literal: _ } => {
                     if comma {
                         comma = false;
-                        let parameter_name = self.comparison_string(vec!["IdentifierToken"])?;
+                        let parameter_name = self.identifier()?;
                         let type_expression = self.type_syntax()?;
 
                         parameters.push(Parameter {
@@ -53,14 +78,17 @@ impl Parser<'_> {
                         let pos = if let Expression::Type {
                             nullable,
                             ref identifier,
+                            ..
                         } = parameters.last().unwrap().type_expression
                         {
+                            // right after the parameter type
                             let pos = if nullable {
-                                identifier.pos.start + 1
+                                identifier.pos.end + 1
                             } else {
-                                identifier.pos.start
+                                identifier.pos.end
                             };
                             Some(Position {
+                                file: identifier.pos.file,
                                 start: pos,
                                 end: pos,
                             })
@@ -77,6 +105,10 @@ impl Parser<'_> {
                     self.comparison(&Tokens::CommaToken)?;
                     comma = true;
                 }
+                // reserved word used as a parameter name
+                token_type if token_type.keyword_str().is_some() => {
+                    self.identifier()?;
+                }
                 _ => break, //return Err(ZXError::SyntaxError {
                             //                     message: format!("expected parameter name, found `{:?}`", self.currently.token_type),
                             //                     pos: left,
@@ -6,7 +6,7 @@ use util::token::Tokens;
 
 impl Parser<'_> {
     pub fn if_syntax(&mut self) -> Result<Statement, ZXError> {
-        let if_keyword = self.comparison_string(vec!["IdentifierToken"])?;
+        let if_keyword = self.comparison(&Tokens::IfToken)?;
         let condition = set_error_message(
             self.expressions(0),
             String::from("missing condition"),
@@ -15,13 +15,7 @@ impl Parser<'_> {
         let block = self.block_syntax()?;
 
         let else_statement = match &self.currently.token_type {
-            Tokens::IdentifierToken { literal } => {
-                if literal.eq("else") {
-                    Some(self.else_syntax()?)
-                } else {
-                    None
-                }
-            }
+            Tokens::ElseToken => Some(self.else_syntax()?),
             _ => None,
         };
 
@@ -34,16 +28,10 @@ impl Parser<'_> {
     }
 
     pub fn else_syntax(&mut self) -> Result<Statement, ZXError> {
-        let else_keyword = self.comparison_string(vec!["IdentifierToken"])?;
+        let else_keyword = self.comparison(&Tokens::ElseToken)?;
 
         let next = match &self.currently.token_type {
-            Tokens::IdentifierToken { literal } => {
-                if literal.eq("if") {
-                    Some(self.if_syntax()?)
-                } else {
-                    None
-                }
-            }
+            Tokens::IfToken => Some(self.if_syntax()?),
             _ => Some(self.block_syntax()?),
         };
 
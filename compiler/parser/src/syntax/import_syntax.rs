@@ -0,0 +1,91 @@
+use crate::Parser;
+use util::ast::{ImportItem, ImportKind, Statement};
+use util::error::ZXError;
+use util::token::{Token, Tokens};
+
+impl Parser<'_> {
+    pub fn import_syntax(&mut self) -> Result<Statement, ZXError> {
+        let im_keyword = self.comparison(&Tokens::ImToken)?;
+        let mut path = vec![self.identifier()?];
+
+        let kind = loop {
+            if !self.currently.is_token_type(&Tokens::DoubleColonToken) {
+                break ImportKind::Single {
+                    alias: self.import_alias()?,
+                };
+            }
+            self.comparison(&Tokens::DoubleColonToken)?;
+
+            match self.currently.token_type {
+                Tokens::MultiplyToken => {
+                    self.comparison(&Tokens::MultiplyToken)?;
+                    break ImportKind::Glob;
+                }
+                Tokens::LeftCurlyBracketsToken => {
+                    break ImportKind::Group {
+                        items: self.import_items()?,
+                    }
+                }
+                _ => path.push(self.identifier()?),
+            }
+        };
+
+        Ok(Statement::Import {
+            im_keyword,
+            path,
+            kind,
+        })
+    }
+
+    // `{ a, b as c }`, line breaks are allowed between the names
+    fn import_items(&mut self) -> Result<Vec<ImportItem>, ZXError> {
+        let left_curly_brackets = self.comparison(&Tokens::LeftCurlyBracketsToken)?;
+        let mut items = vec![];
+
+        while !self
+            .currently
+            .is_token_type(&Tokens::RightCurlyBracketsToken)
+        {
+            items.push(ImportItem {
+                name: self.identifier()?,
+                alias: self.import_alias()?,
+            });
+
+            match self.currently.token_type {
+                Tokens::CommaToken => {
+                    self.comparison(&Tokens::CommaToken)?;
+                }
+                Tokens::RightCurlyBracketsToken => {}
+                _ => {
+                    return Err(ZXError::SyntaxError {
+                        message: format!(
+                            "expected `,` or `}}` in the import list, found {}",
+                            self.currently.token_type
+                        ),
+                        pos: self.currently.pos.clone(),
+                    })
+                }
+            }
+        }
+
+        let right_curly_brackets = self.comparison(&Tokens::RightCurlyBracketsToken)?;
+
+        if items.is_empty() {
+            return Err(ZXError::SyntaxError {
+                message: String::from("empty import list"),
+                pos: left_curly_brackets.pos.to(&right_curly_brackets.pos),
+            });
+        }
+
+        Ok(items)
+    }
+
+    fn import_alias(&mut self) -> Result<Option<Token>, ZXError> {
+        if !self.currently.is_token_type(&Tokens::AsToken) {
+            return Ok(None);
+        }
+        self.comparison(&Tokens::AsToken)?;
+
+        Ok(Some(self.identifier()?))
+    }
+}
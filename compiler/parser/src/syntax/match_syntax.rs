@@ -0,0 +1,170 @@
+use crate::syntax::syntax_util::set_error_message;
+use crate::Parser;
+use util::ast::{Expression, MatchArm, Pattern, Statement};
+use util::error::ZXError;
+use util::token::{Literal, TokenKind, Tokens};
+
+impl Parser<'_> {
+    /// `match value { pattern => arm }`, arms are separated by `,` or line
+    /// breaks and an arm is an expression or a block
+    pub(crate) fn match_expression(&mut self) -> Result<Expression, ZXError> {
+        let match_keyword = self.comparison(&Tokens::MatchToken)?;
+        let value = set_error_message(
+            self.expressions(0),
+            String::from("expected the value to match after `match`"),
+            &match_keyword.pos,
+        )?;
+        let left_curly_brackets = self.comparison(&Tokens::LeftCurlyBracketsToken)?;
+        // line breaks end arms again, even inside brackets
+        let nesting = std::mem::take(&mut self.nesting);
+        let mut arms = vec![];
+
+        while !self
+            .currently
+            .is_token_type(&Tokens::RightCurlyBracketsToken)
+        {
+            if self.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "unclosed curly bracket".to_string(),
+                    pos: left_curly_brackets.pos,
+                });
+            }
+
+            arms.push(self.match_arm()?);
+            match self.currently.token_type {
+                Tokens::CommaToken => {
+                    self.comparison(&Tokens::CommaToken)?;
+                }
+                Tokens::RightCurlyBracketsToken => {}
+                _ if self.is_eof || self.newline_before => {}
+                _ => {
+                    return Err(ZXError::SyntaxError {
+                        message: format!(
+                            "expected `,` or a line break between match arms, found {}",
+                            self.currently.token_type
+                        ),
+                        pos: self.currently.pos.clone(),
+                    })
+                }
+            }
+        }
+        self.nesting = nesting;
+        let right_curly_brackets = self.comparison(&Tokens::RightCurlyBracketsToken)?;
+
+        Ok(Expression::Match {
+            match_keyword,
+            value: Box::new(value),
+            arms,
+            right_curly_brackets,
+        })
+    }
+
+    fn match_arm(&mut self) -> Result<MatchArm, ZXError> {
+        let pattern = self.pattern()?;
+        let pos = self.currently.pos.clone();
+        let fat_arrow = set_error_message(
+            self.comparison(&Tokens::FatArrowToken),
+            String::from("expected `=>` after the pattern"),
+            &pos,
+        )?;
+        let body = match self.currently.token_type {
+            Tokens::LeftCurlyBracketsToken => self.block_syntax()?,
+            _ => Statement::Expression {
+                expression: set_error_message(
+                    self.expressions(0),
+                    String::from("expected an expression or a block after `=>`"),
+                    &fat_arrow.pos,
+                )?,
+            },
+        };
+
+        Ok(MatchArm {
+            pattern,
+            fat_arrow,
+            body: Box::new(body),
+        })
+    }
+
+    // `1`, `-1`, `"a"`, `true`, `Shape::Circle(r)`, `name` or `_`
+    fn pattern(&mut self) -> Result<Pattern, ZXError> {
+        match &self.currently.token_type {
+            Tokens::LiteralToken { kid, .. } => {
+                let kid = kid.clone();
+                let content = self.comparison_kinds(&[TokenKind::LiteralToken])?;
+
+                Ok(Pattern::Literal { kid, content })
+            }
+            Tokens::MinusToken => {
+                let minus = self.comparison(&Tokens::MinusToken)?;
+
+                match self.currently.token_type {
+                    Tokens::LiteralToken {
+                        kid: Literal::PositiveInteger,
+                        ..
+                    } => Ok(Pattern::Literal {
+                        kid: Literal::NegativeInteger,
+                        content: self.comparison_kinds(&[TokenKind::LiteralToken])?,
+                    }),
+                    _ => Err(ZXError::SyntaxError {
+                        message: String::from("expected an integer after `-` in the pattern"),
+                        pos: minus.pos,
+                    }),
+                }
+            }
+            Tokens::IdentifierToken { literal } => match literal.as_str() {
+                "true" | "false" => Ok(Pattern::Bool {
+                    identifier: self.identifier()?,
+                }),
+                "_" => Ok(Pattern::Wildcard {
+                    underscore: self.identifier()?,
+                }),
+                _ => {
+                    let name = self.identifier()?;
+                    if !self.currently.is_token_type(&Tokens::DoubleColonToken) {
+                        return Ok(Pattern::Binding { name });
+                    }
+
+                    self.comparison(&Tokens::DoubleColonToken)?;
+                    let variant_name = self.identifier()?;
+
+                    Ok(Pattern::Variant {
+                        enum_name: name,
+                        variant_name,
+                        fields: self.pattern_fields()?,
+                    })
+                }
+            },
+            _ => Err(ZXError::SyntaxError {
+                message: format!("expected a pattern, found {}", self.currently.token_type),
+                pos: self.currently.pos.clone(),
+            }),
+        }
+    }
+
+    // `(a, _)` after a variant, empty without the `(`
+    fn pattern_fields(&mut self) -> Result<Vec<Pattern>, ZXError> {
+        let mut fields = vec![];
+        if !self.currently.is_token_type(&Tokens::LeftParenthesesToken) {
+            return Ok(fields);
+        }
+
+        let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
+        self.nesting += 1;
+        while !self.currently.is_token_type(&Tokens::RightParenthesesToken) {
+            if self.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "unclosed parenthese".to_string(),
+                    pos: left_parentheses.pos,
+                });
+            }
+            if !fields.is_empty() {
+                self.comparison(&Tokens::CommaToken)?;
+            }
+            fields.push(self.pattern()?);
+        }
+        self.nesting -= 1;
+        self.comparison(&Tokens::RightParenthesesToken)?;
+
+        Ok(fields)
+    }
+}
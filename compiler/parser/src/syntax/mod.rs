@@ -1,108 +1,170 @@
+mod assignment_syntax;
 mod block_syntax;
 mod class_syntax;
+mod enum_syntax;
 mod for_loop_syntax;
 mod function_syntax;
 mod if_syntax;
+mod import_syntax;
+mod match_syntax;
 mod return_syntax;
 mod syntax_util;
 mod type_syntax;
 mod variable_declaration_syntax;
 mod while_syntax;
 
-use crate::syntax::syntax_util::{infix_binding_power, is_operator, operator_type};
+use crate::syntax::syntax_util::{
+    infix_binding_power, is_operator, operator_type, set_error_message, unary_operator_type,
+    with_doc, PREFIX_BINDING_POWER,
+};
 use crate::Parser;
-use util::ast::{Expression, Statement};
+use util::ast::{Expression, Statement, UnaryOperator};
 use util::error::ZXError;
-use util::token::{Literal, Token, Tokens};
+use util::token::{Literal, Token, TokenKind, Tokens};
 
 impl Parser<'_> {
     pub fn statement(&mut self) -> Result<Statement, ZXError> {
-        let keyword = self.currently;
-
-        return if let Tokens::IdentifierToken { ref literal } = keyword.token_type {
-            // Parse the statement according to the keyword
-            // The rest without a keyword is express
-            let statement = match literal.as_str() {
-                "fn" => self.function_syntax()?,
-                "pub" => {
-                    let pub_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-                    let statement = self.statement()?;
-                    match statement {
-                        Statement::FunctionDeclaration { .. }
-                        | Statement::VariableDeclaration { .. } => Statement::Public {
-                            statement: Box::new(statement),
-                        },
-                        _ => {
-                            return Err(ZXError::SyntaxError {
-                                message: String::from(
-                                    "visibility `pub` is not followed by an item",
-                                ),
-                                pos: pub_keyword.pos,
-                            })
-                        }
+        let doc = self.take_doc();
+        // Parse the statement according to the keyword
+        // The rest without a keyword is express
+        let statement = match self.currently.token_type {
+            Tokens::FnToken => {
+                let anonymous = self
+                    .peek()
+                    .is_some_and(|token| token.is_token_type(&Tokens::LeftParenthesesToken));
+
+                // `fn(` starts an anonymous function
+                if anonymous {
+                    self.expression_syntax()?
+                } else {
+                    self.function_syntax()?
+                }
+            }
+            Tokens::PubToken => {
+                let pub_keyword = self.comparison(&Tokens::PubToken)?;
+                let statement = self.statement()?;
+                match statement {
+                    Statement::FunctionDeclaration { .. }
+                    | Statement::VariableDeclaration { .. }
+                    | Statement::Override { .. } => Statement::Public {
+                        statement: Box::new(statement),
+                    },
+                    _ => {
+                        return Err(ZXError::SyntaxError {
+                            message: String::from("visibility `pub` is not followed by an item"),
+                            pos: pub_keyword.pos,
+                        })
                     }
                 }
-                "static" => {
-                    let static_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-                    let statement = self.statement()?;
-
-                    match statement {
-                        Statement::FunctionDeclaration { .. }
-                        | Statement::VariableDeclaration { .. } => Statement::Static {
-                            statement: Box::new(statement),
-                        },
-                        _ => {
-                            return Err(ZXError::SyntaxError {
-                                message: String::from(
-                                    "visibility `pub` is not followed by an item",
-                                ),
-                                pos: static_keyword.pos,
-                            })
-                        }
+            }
+            Tokens::StaticToken => {
+                let static_keyword = self.comparison(&Tokens::StaticToken)?;
+                let statement = self.statement()?;
+
+                match statement {
+                    Statement::FunctionDeclaration { .. }
+                    | Statement::VariableDeclaration { .. } => Statement::Static {
+                        statement: Box::new(statement),
+                    },
+                    _ => {
+                        return Err(ZXError::SyntaxError {
+                            message: String::from("visibility `pub` is not followed by an item"),
+                            pos: static_keyword.pos,
+                        })
                     }
                 }
-                "return" => self.return_syntax()?,
-                "var" => self.variable_declaration_syntax()?,
-                "if" => self.if_syntax()?,
-                "while" => self.while_syntax()?,
-                "for" => self.for_syntax()?,
-                "class" => self.class_syntax()?,
-                _ => Statement::Expression {
-                    expression: self.expressions(0)?,
-                },
-            };
+            }
+            Tokens::OverrideToken => {
+                let override_keyword = self.comparison(&Tokens::OverrideToken)?;
+                let statement = self.statement()?;
 
-            Ok(statement)
-        } else if let Tokens::LeftCurlyBracketsToken = keyword.token_type {
+                match statement {
+                    Statement::FunctionDeclaration { .. } => Statement::Override {
+                        override_keyword,
+                        statement: Box::new(statement),
+                    },
+                    _ => {
+                        return Err(ZXError::SyntaxError {
+                            message: String::from("`override` is not followed by a method"),
+                            pos: override_keyword.pos,
+                        })
+                    }
+                }
+            }
+            Tokens::ReturnToken => self.return_syntax()?,
+            Tokens::VarToken => self.variable_declaration_syntax()?,
+            Tokens::IfToken => self.if_syntax()?,
+            Tokens::WhileToken => self.while_syntax()?,
+            Tokens::ForToken => self.for_syntax()?,
+            Tokens::ClassToken => self.class_syntax()?,
+            Tokens::EnumToken => self.enum_syntax()?,
+            Tokens::ImToken => self.import_syntax()?,
             // left curly brackets starts with block
-            Ok(self.block_syntax()?)
-        } else {
-            Ok(Statement::Expression {
-                expression: self.expressions(0)?,
-            })
+            Tokens::LeftCurlyBracketsToken => self.block_syntax()?,
+            _ => self.expression_syntax()?,
         };
+
+        Ok(with_doc(statement, doc))
     }
 
     pub fn expressions(&mut self, min_bp: u8) -> Result<Expression, ZXError> {
+        let expression = self.range_operand(min_bp)?;
+
+        // a range is looser than every operator, `a + 1..b * 2` is
+        // `(a + 1)..(b * 2)`
+        match self.currently.token_type {
+            Tokens::DoubleDotToken | Tokens::DoubleDotEqualToken
+                if min_bp == 0 && self.line_continues() =>
+            {
+                self.range_expression(expression)
+            }
+            _ => Ok(expression),
+        }
+    }
+
+    fn range_expression(&mut self, start: Expression) -> Result<Expression, ZXError> {
+        let inclusive = self.currently.is_token_type(&Tokens::DoubleDotEqualToken);
+        let range_operator = self.comparison(&self.currently.token_type.clone())?;
+        let end = set_error_message(
+            self.range_operand(1),
+            format!(
+                "expected the end of the range after `{}`",
+                if inclusive { "..=" } else { ".." }
+            ),
+            &range_operator.pos,
+        )?;
+
+        Ok(Expression::Range {
+            start: Box::new(start),
+            range_operator,
+            end: Box::new(end),
+            inclusive,
+        })
+    }
+
+    // an expression without a range in it
+    fn range_operand(&mut self, min_bp: u8) -> Result<Expression, ZXError> {
         match &self.currently.token_type {
             Tokens::LiteralToken { kid, literal: _ } => {
-                let content = self.comparison_string(vec!["LiteralToken"])?;
+                let kid = kid.clone();
+                let content = self.comparison_kinds(&[TokenKind::LiteralToken])?;
                 match &self.currently.token_type {
                     // example: "20".to_int()
                     Tokens::DotToken => Ok(Expression::Value {
                         kid: kid.clone(),
                         content,
-                        next: Box::new(Some(self.expressions(min_bp)?)),
+                        next: Box::new(Some(self.range_operand(min_bp)?)),
                     }),
                     // example: 10 * 20
-                    token_type if is_operator(token_type) => Ok(self.operator_expression(
-                        min_bp,
-                        Expression::Value {
-                            kid: kid.clone(),
-                            content,
-                            next: Box::new(None),
-                        },
-                    )?),
+                    token_type if is_operator(token_type) && self.line_continues() => Ok(self
+                        .operator_expression(
+                            min_bp,
+                            Expression::Value {
+                                kid: kid.clone(),
+                                content,
+                                next: Box::new(None),
+                            },
+                        )?),
                     // The rest is value
                     _ => Ok(Expression::Value {
                         kid: kid.clone(),
@@ -112,24 +174,25 @@ impl Parser<'_> {
                 }
             }
             Tokens::IdentifierToken { .. } | Tokens::StdToken => {
-                let token = self.comparison_string(vec!["IdentifierToken", "StdToken"])?;
+                let token =
+                    self.comparison_kinds(&[TokenKind::IdentifierToken, TokenKind::StdToken])?;
 
                 let expression = match self.currently.token_type {
-                    // call expression
-                    Tokens::LeftParenthesesToken => self.call_expression(token)?,
+                    // call expression, `(` on the next line starts a new statement
+                    Tokens::LeftParenthesesToken if self.line_continues() => {
+                        self.call_expression(token)?
+                    }
                     _ => {
                         let next = match &self.currently.token_type {
-                            Tokens::DotToken
-                            | Tokens::ColonToken
-                            | Tokens::LeftParenthesesToken => {
-                                Some(Box::new(self.expressions(min_bp)?))
+                            Tokens::DotToken | Tokens::DoubleColonToken => {
+                                Some(Box::new(self.range_operand(min_bp)?))
                             }
                             _ => None,
                         };
 
                         match &token.token_type {
                             Tokens::IdentifierToken { ref literal }
-                                if literal == "true" || literal == "false" =>
+                                if literal.as_str() == "true" || literal.as_str() == "false" =>
                             {
                                 Expression::Bool { identifier: token }
                             }
@@ -140,9 +203,10 @@ impl Parser<'_> {
                         }
                     }
                 };
+                let expression = self.index_expression(expression)?;
 
                 Ok(match &self.currently.token_type {
-                    token_type if is_operator(token_type) => {
+                    token_type if is_operator(token_type) && self.line_continues() => {
                         self.operator_expression(min_bp, expression)?
                     }
                     _ => expression,
@@ -150,43 +214,62 @@ impl Parser<'_> {
             }
             Tokens::DotToken => {
                 self.comparison(&Tokens::DotToken)?;
-                let sub_member = Box::new(self.expressions(min_bp)?);
+                let sub_member = Box::new(self.range_operand(min_bp)?);
 
                 Ok(Expression::SubMember { sub_member })
             }
-            Tokens::ColonToken => {
-                self.comparison(&Tokens::ColonToken)?;
-                self.comparison(&Tokens::ColonToken)?;
+            Tokens::DoubleColonToken => {
+                self.comparison(&Tokens::DoubleColonToken)?;
 
-                let expression = self.expressions(min_bp)?;
+                let expression = self.range_operand(min_bp)?;
 
                 Ok(Expression::Path {
                     next: Box::new(expression),
                 })
             }
-            Tokens::LeftParenthesesToken => Ok(self.operator_brackets()?),
-            Tokens::MinusToken => {
-                self.comparison(&Tokens::MinusToken)?;
-                match &self.currently.token_type {
-                    Tokens::LiteralToken { kid, .. } => match kid {
-                        Literal::PositiveInteger => {
-                            let content = self.comparison_string(vec!["LiteralToken"])?;
-                            Ok(Expression::Value {
-                                kid: Literal::NegativeInteger,
-                                content,
-                                next: Box::new(None),
-                            })
-                        }
-                        _ => Err(ZXError::SyntaxError {
-                            message: "Unknown Token".to_string(),
-                            pos: self.currently.pos.clone(),
-                        }),
-                    },
-                    _ => Err(ZXError::SyntaxError {
-                        message: "Unknown Token".to_string(),
-                        pos: self.currently.pos.clone(),
-                    }),
-                }
+            Tokens::LeftParenthesesToken => {
+                let brackets = self.operator_brackets()?;
+                let brackets = self.index_expression(brackets)?;
+
+                Ok(match &self.currently.token_type {
+                    token_type if is_operator(token_type) && self.line_continues() => {
+                        self.operator_expression(min_bp, brackets)?
+                    }
+                    _ => brackets,
+                })
+            }
+            Tokens::LeftSquareBracketsToken => {
+                let array = self.array_expression()?;
+                let array = self.index_expression(array)?;
+
+                Ok(match &self.currently.token_type {
+                    token_type if is_operator(token_type) && self.line_continues() => {
+                        self.operator_expression(min_bp, array)?
+                    }
+                    _ => array,
+                })
+            }
+            Tokens::FnToken => self.function_expression(),
+            Tokens::MatchToken => self.match_expression(),
+            Tokens::TemplateStartToken => {
+                let template = self.string_template()?;
+
+                Ok(match &self.currently.token_type {
+                    token_type if is_operator(token_type) && self.line_continues() => {
+                        self.operator_expression(min_bp, template)?
+                    }
+                    _ => template,
+                })
+            }
+            Tokens::MinusToken | Tokens::ExclamationToken | Tokens::TildeToken => {
+                let unary = self.unary_expression()?;
+
+                Ok(match &self.currently.token_type {
+                    token_type if is_operator(token_type) && self.line_continues() => {
+                        self.operator_expression(min_bp, unary)?
+                    }
+                    _ => unary,
+                })
             }
             _ => Err(ZXError::SyntaxError {
                 message: "Unknown Token".to_string(),
@@ -199,6 +282,7 @@ impl Parser<'_> {
         let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
         let mut comma = true;
         let mut arguments: Vec<Expression> = vec![];
+        self.nesting += 1;
 
         loop {
             match self.currently.token_type {
@@ -221,10 +305,11 @@ impl Parser<'_> {
             }
         }
 
+        self.nesting -= 1;
         let right_parentheses = self.comparison(&Tokens::RightParenthesesToken)?;
 
         let next = match self.currently.token_type {
-            Tokens::ColonToken | Tokens::DotToken => Some(Box::new(self.expressions(0)?)),
+            Tokens::DoubleColonToken | Tokens::DotToken => Some(Box::new(self.range_operand(0)?)),
             _ => None,
         };
 
@@ -237,12 +322,139 @@ impl Parser<'_> {
         })
     }
 
+    fn array_expression(&mut self) -> Result<Expression, ZXError> {
+        let left_square_brackets = self.comparison(&Tokens::LeftSquareBracketsToken)?;
+        let mut elements: Vec<Expression> = vec![];
+        self.nesting += 1;
+
+        // a `,` after the last element is allowed
+        while !self
+            .currently
+            .is_token_type(&Tokens::RightSquareBracketsToken)
+        {
+            if self.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "unclosed square bracket".to_string(),
+                    pos: left_square_brackets.pos,
+                });
+            }
+
+            elements.push(self.expressions(0)?);
+            match self.currently.token_type {
+                Tokens::CommaToken => {
+                    self.comparison(&Tokens::CommaToken)?;
+                }
+                Tokens::RightSquareBracketsToken => {}
+                _ if self.is_eof => continue,
+                _ => {
+                    return Err(ZXError::SyntaxError {
+                        message: format!(
+                            "expected `,` or `]` in the array, found {}",
+                            self.currently.token_type
+                        ),
+                        pos: self.currently.pos.clone(),
+                    })
+                }
+            }
+        }
+
+        self.nesting -= 1;
+        let right_square_brackets = self.comparison(&Tokens::RightSquareBracketsToken)?;
+
+        Ok(Expression::Array {
+            left_square_brackets,
+            elements,
+            right_square_brackets,
+        })
+    }
+
+    // `target[index]`, also `a[0][1]`, `[` on the next line starts a new
+    // statement
+    fn index_expression(&mut self, target: Expression) -> Result<Expression, ZXError> {
+        let mut target = target;
+
+        while self
+            .currently
+            .is_token_type(&Tokens::LeftSquareBracketsToken)
+            && self.line_continues()
+        {
+            let left_square_brackets = self.comparison(&Tokens::LeftSquareBracketsToken)?;
+            self.nesting += 1;
+            let index = set_error_message(
+                self.expressions(0),
+                String::from("expected an index in `[]`"),
+                &left_square_brackets.pos,
+            )?;
+            self.nesting -= 1;
+            let right_square_brackets = self.comparison(&Tokens::RightSquareBracketsToken)?;
+            let next = match self.currently.token_type {
+                Tokens::DotToken | Tokens::DoubleColonToken => {
+                    Some(Box::new(self.range_operand(0)?))
+                }
+                _ => None,
+            };
+            let has_next = next.is_some();
+
+            target = Expression::Index {
+                target: Box::new(target),
+                left_square_brackets,
+                index: Box::new(index),
+                right_square_brackets,
+                next,
+            };
+            // the rest belongs to `next`
+            if has_next {
+                break;
+            }
+        }
+
+        Ok(target)
+    }
+
+    fn unary_expression(&mut self) -> Result<Expression, ZXError> {
+        let operator = self.currently.clone();
+        let operator_type = unary_operator_type(&operator)?;
+        self.next(false);
+
+        // `-1` stays a literal so `-2147483648` still fits in an `Int`
+        if let (
+            UnaryOperator::Neg,
+            Tokens::LiteralToken {
+                kid: Literal::PositiveInteger,
+                ..
+            },
+        ) = (&operator_type, &self.currently.token_type)
+        {
+            let content = self.comparison_kinds(&[TokenKind::LiteralToken])?;
+
+            return Ok(Expression::Value {
+                kid: Literal::NegativeInteger,
+                content,
+                next: Box::new(None),
+            });
+        }
+
+        let expression = set_error_message(
+            self.expressions(PREFIX_BINDING_POWER),
+            String::from("expected an expression after the unary operator"),
+            &operator.pos,
+        )?;
+
+        Ok(Expression::Unary {
+            operator_type,
+            operator,
+            expression: Box::new(expression),
+        })
+    }
+
     fn operator_expression(&mut self, min_bp: u8, left: Expression) -> Result<Expression, ZXError> {
         let mut left_expression = left;
 
         loop {
             let operator = match &self.currently.token_type {
-                token_type if is_operator(token_type) => operator_type(&self.currently)?,
+                token_type if is_operator(token_type) && self.line_continues() => {
+                    operator_type(&self.currently)?
+                }
                 _ => break,
             };
 
@@ -253,7 +465,8 @@ impl Parser<'_> {
             }
 
             self.next(false);
-            let next = self.expressions(min_bp + 1)?;
+            // only tighter operators belong to the right side
+            let next = self.expressions(bp + 1)?;
             let right_expression = self.operator_expression(bp + 1, next)?;
 
             left_expression = Expression::Operator {
@@ -266,10 +479,57 @@ impl Parser<'_> {
         Ok(left_expression)
     }
 
+    fn string_template(&mut self) -> Result<Expression, ZXError> {
+        let left_quote = self.comparison(&Tokens::TemplateStartToken)?;
+        let mut parts = vec![];
+
+        loop {
+            match &self.currently.token_type {
+                Tokens::TemplateEndToken => break,
+                Tokens::LiteralToken { kid, .. } => {
+                    let kid = kid.clone();
+                    parts.push(Expression::Value {
+                        kid,
+                        content: self.comparison_kinds(&[TokenKind::LiteralToken])?,
+                        next: Box::new(None),
+                    })
+                }
+                Tokens::InterpolationStartToken => {
+                    let start = self.comparison(&Tokens::InterpolationStartToken)?;
+                    self.nesting += 1;
+                    let expression = set_error_message(
+                        self.expressions(0),
+                        String::from("expected an expression in `${}`"),
+                        &start.pos,
+                    )?;
+                    parts.push(expression);
+                    self.nesting -= 1;
+                    self.comparison(&Tokens::InterpolationEndToken)?;
+                }
+                _ => {
+                    return Err(ZXError::SyntaxError {
+                        message: "unterminated string template".to_string(),
+                        pos: left_quote.pos,
+                    })
+                }
+            }
+        }
+
+        let right_quote = self.comparison(&Tokens::TemplateEndToken)?;
+
+        Ok(Expression::StringTemplate {
+            left_quote,
+            parts,
+            right_quote,
+        })
+    }
+
     fn operator_brackets(&mut self) -> Result<Expression, ZXError> {
         self.comparison(&Tokens::LeftParenthesesToken)?;
+        self.nesting += 1;
         let next = self.expressions(0)?;
         let operator = self.operator_expression(0, next)?;
+        self.nesting -= 1;
         self.comparison(&Tokens::RightParenthesesToken)?;
 
         Ok(Expression::Brackets {
@@ -1,10 +1,11 @@
 use crate::Parser;
 use util::ast::Statement;
 use util::error::ZXError;
+use util::token::Tokens;
 
 impl Parser<'_> {
     pub fn return_syntax(&mut self) -> Result<Statement, ZXError> {
-        let return_keyword = self.comparison_string(vec!["IdentifierToken"])?;
+        let return_keyword = self.comparison(&Tokens::ReturnToken)?;
         let return_expression = self.statement()?;
 
         Ok(Statement::Return {
@@ -1,4 +1,4 @@
-use util::ast::Operator;
+use util::ast::{Operator, Statement, UnaryOperator};
 use util::error::ZXError;
 use util::token::{Position, Token, Tokens};
 
@@ -16,11 +16,53 @@ pub fn set_error_message<T>(
     }
 }
 
+/// Attach a doc comment to a declaration, also through `pub` and `static`
+pub fn with_doc(statement: Statement, doc: Option<String>) -> Statement {
+    match statement {
+        Statement::Public { statement } => Statement::Public {
+            statement: Box::new(with_doc(*statement, doc)),
+        },
+        Statement::Static { statement } => Statement::Static {
+            statement: Box::new(with_doc(*statement, doc)),
+        },
+        Statement::Override {
+            override_keyword,
+            statement,
+        } => Statement::Override {
+            override_keyword,
+            statement: Box::new(with_doc(*statement, doc)),
+        },
+        mut statement => {
+            if let Statement::FunctionDeclaration { doc: old_doc, .. }
+            | Statement::VariableDeclaration { doc: old_doc, .. }
+            | Statement::Class { doc: old_doc, .. }
+            | Statement::Enum { doc: old_doc, .. } = &mut statement
+            {
+                if doc.is_some() {
+                    *old_doc = doc;
+                }
+            }
+
+            statement
+        }
+    }
+}
+
 pub fn operator_type(token: &Token) -> Result<Operator, ZXError> {
     Ok(match token.token_type {
         Tokens::PlusToken => Operator::Add,
         Tokens::MultiplyToken => Operator::Mul,
         Tokens::MinusToken => Operator::Sub,
+        Tokens::SlashToken => Operator::Div,
+        Tokens::PercentToken => Operator::Mod,
+        Tokens::DoubleEqualToken => Operator::Equal,
+        Tokens::NotEqualToken => Operator::NotEqual,
+        Tokens::LessToken => Operator::Less,
+        Tokens::LessEqualToken => Operator::LessEqual,
+        Tokens::MoreToken => Operator::More,
+        Tokens::MoreEqualToken => Operator::MoreEqual,
+        Tokens::AndToken => Operator::And,
+        Tokens::OrToken => Operator::Or,
         _ => {
             return Err(ZXError::SyntaxError {
                 pos: token.pos.clone(),
@@ -31,15 +73,49 @@ pub fn operator_type(token: &Token) -> Result<Operator, ZXError> {
 }
 
 pub fn is_operator(token_type: &Tokens) -> bool {
-    match token_type {
-        Tokens::PlusToken | Tokens::MultiplyToken | Tokens::MinusToken => true,
-        _ => false,
-    }
+    matches!(
+        token_type,
+        Tokens::PlusToken
+            | Tokens::MultiplyToken
+            | Tokens::MinusToken
+            | Tokens::SlashToken
+            | Tokens::PercentToken
+            | Tokens::DoubleEqualToken
+            | Tokens::NotEqualToken
+            | Tokens::LessToken
+            | Tokens::LessEqualToken
+            | Tokens::MoreToken
+            | Tokens::MoreEqualToken
+            | Tokens::AndToken
+            | Tokens::OrToken
+    )
+}
+
+pub fn unary_operator_type(token: &Token) -> Result<UnaryOperator, ZXError> {
+    Ok(match token.token_type {
+        Tokens::MinusToken => UnaryOperator::Neg,
+        Tokens::ExclamationToken => UnaryOperator::Not,
+        Tokens::TildeToken => UnaryOperator::BitNot,
+        _ => {
+            return Err(ZXError::SyntaxError {
+                pos: token.pos.clone(),
+                message: "not a unary operator".to_string(),
+            })
+        }
+    })
 }
 
+/// Prefix operators bind tighter than every infix operator
+pub const PREFIX_BINDING_POWER: u8 = 13;
+
+/// Higher binds tighter, every operator is left associative
 pub fn infix_binding_power(operator_type: &Operator) -> u8 {
     match operator_type {
-        Operator::Add | Operator::Sub => 1,
-        Operator::Mul => 3,
+        Operator::Or => 1,
+        Operator::And => 3,
+        Operator::Equal | Operator::NotEqual => 5,
+        Operator::Less | Operator::LessEqual | Operator::More | Operator::MoreEqual => 7,
+        Operator::Add | Operator::Sub => 9,
+        Operator::Mul | Operator::Div | Operator::Mod => 11,
     }
 }
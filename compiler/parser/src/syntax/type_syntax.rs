@@ -1,12 +1,59 @@
+use crate::syntax::syntax_util::set_error_message;
 use crate::Parser;
 use util::ast::Expression;
 use util::error::ZXError;
-use util::token::Tokens;
+use util::token::{Position, Token, Tokens};
 
 impl Parser<'_> {
     pub fn type_syntax(&mut self) -> Result<Expression, ZXError> {
         self.comparison(&Tokens::ColonToken)?;
-        let tpye_identifier = self.comparison_string(vec!["IdentifierToken"])?;
+
+        self.type_expression()
+    }
+
+    /// A type without the `:` before it, `Int`, `Str?`, `[Int]`,
+    /// `Map<Str, List<Int>>` or `(Int, Int) -> Int`
+    pub fn type_expression(&mut self) -> Result<Expression, ZXError> {
+        if self.currently.is_token_type(&Tokens::LeftParenthesesToken) {
+            return self.function_type();
+        }
+        if self
+            .currently
+            .is_token_type(&Tokens::LeftSquareBracketsToken)
+        {
+            let left_square_brackets = self.comparison(&Tokens::LeftSquareBracketsToken)?;
+            let element = self.type_expression()?;
+            let right_square_brackets = self.comparison(&Tokens::RightSquareBracketsToken)?;
+            let question_mark = match self.currently.token_type {
+                Tokens::QuestionMarkToken => Some(self.comparison(&Tokens::QuestionMarkToken)?),
+                _ => None,
+            };
+
+            return Ok(Expression::ArrayType {
+                left_square_brackets,
+                element: Box::new(element),
+                right_square_brackets,
+                nullable: question_mark.is_some(),
+            });
+        }
+
+        let tpye_identifier = self.identifier()?;
+
+        let arguments = match self.currently.token_type {
+            Tokens::LessToken => {
+                let less = self.comparison(&Tokens::LessToken)?;
+                let mut arguments = vec![self.type_expression()?];
+
+                while self.currently.is_token_type(&Tokens::CommaToken) {
+                    self.comparison(&Tokens::CommaToken)?;
+                    arguments.push(self.type_expression()?);
+                }
+                self.close_angle_bracket(&less.pos, "type argument")?;
+
+                arguments
+            }
+            _ => vec![],
+        };
 
         let question_mark = match self.currently.token_type {
             Tokens::QuestionMarkToken => Some(self.comparison(&Tokens::QuestionMarkToken)?),
@@ -15,7 +62,102 @@ impl Parser<'_> {
 
         Ok(Expression::Type {
             identifier: tpye_identifier,
+            arguments,
             nullable: !question_mark.is_none(),
         })
     }
+
+    // `(Int, Int) -> Int`, `() -> Void` takes nothing
+    fn function_type(&mut self) -> Result<Expression, ZXError> {
+        let left_parentheses = self.comparison(&Tokens::LeftParenthesesToken)?;
+        let mut parameters = vec![];
+
+        while !self.currently.is_token_type(&Tokens::RightParenthesesToken) {
+            if self.is_eof {
+                return Err(ZXError::SyntaxError {
+                    message: "unclosed parenthese".to_string(),
+                    pos: left_parentheses.pos,
+                });
+            }
+            if !parameters.is_empty() {
+                self.comparison(&Tokens::CommaToken)?;
+            }
+            parameters.push(self.type_expression()?);
+        }
+        let right_parentheses = self.comparison(&Tokens::RightParenthesesToken)?;
+        let arrow = set_error_message(
+            self.comparison(&Tokens::ArrowToken),
+            String::from("expected `->` and the return type of the function type"),
+            &right_parentheses.pos,
+        )?;
+        let return_type = self.type_expression()?;
+
+        Ok(Expression::FunctionType {
+            left_parentheses,
+            parameters,
+            right_parentheses,
+            arrow,
+            return_type: Box::new(return_type),
+        })
+    }
+
+    /// The names in `<T, U>` after a function or class name, empty without
+    /// the `<`
+    pub fn type_parameters(&mut self) -> Result<Vec<Token>, ZXError> {
+        if !self.currently.is_token_type(&Tokens::LessToken) {
+            return Ok(vec![]);
+        }
+
+        let less = self.comparison(&Tokens::LessToken)?;
+        let mut type_parameters = vec![self.identifier()?];
+
+        while self.currently.is_token_type(&Tokens::CommaToken) {
+            self.comparison(&Tokens::CommaToken)?;
+            type_parameters.push(self.identifier()?);
+        }
+        self.close_angle_bracket(&less.pos, "type parameter")?;
+
+        Ok(type_parameters)
+    }
+
+    // `>>` and `>=` close the inner list of `List<List<Int>>` first, the rest
+    // of the token stays
+    fn close_angle_bracket(&mut self, less: &Position, list: &str) -> Result<Token, ZXError> {
+        let rest = match self.currently.token_type {
+            Tokens::MoreToken => return self.comparison(&Tokens::MoreToken),
+            Tokens::RightShiftToken => Tokens::MoreToken,
+            Tokens::MoreEqualToken => Tokens::EqualToken,
+            _ if self.is_eof => {
+                return Err(ZXError::SyntaxError {
+                    message: format!("unclosed {} list", list),
+                    pos: less.clone(),
+                })
+            }
+            _ => {
+                return Err(ZXError::SyntaxError {
+                    message: format!(
+                        "expected `,` or `>` in the {} list, found {}",
+                        list, self.currently.token_type
+                    ),
+                    pos: self.currently.pos.clone(),
+                })
+            }
+        };
+        let pos = self.currently.pos.clone();
+        self.currently = Token {
+            token_type: rest,
+            pos: Position {
+                start: pos.start + 1,
+                ..pos.clone()
+            },
+        };
+
+        Ok(Token {
+            token_type: Tokens::MoreToken,
+            pos: Position {
+                end: pos.start + 1,
+                ..pos
+            },
+        })
+    }
 }
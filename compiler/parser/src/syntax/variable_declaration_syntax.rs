@@ -5,8 +5,8 @@ use util::token::Tokens;
 
 impl Parser<'_> {
     pub fn variable_declaration_syntax(&mut self) -> Result<Statement, ZXError> {
-        let var_keyword = self.comparison_string(vec!["IdentifierToken"])?;
-        let var_name = self.comparison_string(vec!["IdentifierToken"])?;
+        let var_keyword = self.comparison(&Tokens::VarToken)?;
+        let var_name = self.identifier()?;
 
         let type_identifier = match &self.currently.token_type {
             Tokens::ColonToken => Some(self.type_syntax()?),
@@ -23,6 +23,7 @@ impl Parser<'_> {
         };
 
         Ok(Statement::VariableDeclaration {
+            doc: None,
             var_keyword,
             var_name,
             type_identifier,
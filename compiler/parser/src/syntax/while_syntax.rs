@@ -2,10 +2,11 @@ use crate::syntax::syntax_util::set_error_message;
 use crate::Parser;
 use util::ast::Statement;
 use util::error::ZXError;
+use util::token::Tokens;
 
 impl Parser<'_> {
     pub fn while_syntax(&mut self) -> Result<Statement, ZXError> {
-        let while_keyword = self.comparison_string(vec!["IdentifierToken"])?;
+        let while_keyword = self.comparison(&Tokens::WhileToken)?;
 
         let condition = set_error_message(
             self.expressions(0),
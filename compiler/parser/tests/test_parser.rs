@@ -3,13 +3,19 @@ mod test_parser {
     use lexer::Lexer;
     use parser::Parser;
     use std::fs;
-    use util::report::{Level, Report};
+    use util::ast::{Expression, ImportKind, Pattern, Statement};
+    use util::error::ZXError;
+    use util::source_map::{FileId, SourceMap};
     use util::token::{Literal, Position, Token, Tokens};
     use util::view_ast_tree::ViewASTTree;
 
     #[test]
     fn test_parser() {
-        let pos = Position { start: 0, end: 0 };
+        let pos = Position {
+            file: FileId(0),
+            start: 0,
+            end: 0,
+        };
 
         let tokens = vec![
             Token {
@@ -24,7 +30,7 @@ mod test_parser {
                 pos: pos.clone(),
             },
         ];
-        Parser::new(&tokens);
+        Parser::from_tokens(&tokens);
     }
 
     #[test]
@@ -33,26 +39,873 @@ mod test_parser {
         paths.into_iter().for_each(|dir| {
             let path = dir.unwrap().path().display().to_string();
             println!("Test {path} file");
-            let source = fs::read_to_string(&path).expect("Something went wrong reading the file");
-            let mut lexer = Lexer::new(&source);
-
-            match lexer.lexer() {
-                Ok(()) => {
-                    let mut parser = Parser::new(&lexer.tokens);
-                    parser.parse(&path, &source);
-                    ViewASTTree {
-                        ast_tree: parser.asts,
+            let mut source_map = SourceMap::new();
+            let file = source_map
+                .load_file(&path)
+                .expect("Something went wrong reading the file");
+            let mut parser = Parser::new(Lexer::new(source_map.source(file), file));
+            parser.parse(&source_map);
+            ViewASTTree {
+                ast_tree: parser.asts,
+            }
+            .main();
+        });
+    }
+
+    #[test]
+    fn test_string_template() {
+        let source = r#"var a = "x = ${x + 1}!""#;
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        parser.parse(&SourceMap::new());
+
+        match &parser.asts[0] {
+            Statement::VariableDeclaration {
+                value: Some(value), ..
+            } => match &**value {
+                Statement::Expression {
+                    expression: Expression::StringTemplate { parts, .. },
+                } => {
+                    assert_eq!(parts.len(), 3);
+                    assert!(matches!(parts[1], Expression::Operator { .. }));
+                }
+                statement => panic!("unexpected {:?}", statement),
+            },
+            statement => panic!("unexpected {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        for (source, keyword, start) in [
+            ("var fn = 1", "fn", 4),
+            ("fn class() {}", "class", 3),
+            ("fn f(a: Int, while: Int) {}", "while", 13),
+            ("class if {}", "if", 6),
+        ] {
+            let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+
+            match parser.statement() {
+                Err(ZXError::SyntaxError { message, pos }) => {
+                    assert!(
+                        message.starts_with(&format!(
+                            "expected identifier, found keyword `{}`",
+                            keyword
+                        )),
+                        "{}",
+                        message
+                    );
+                    assert_eq!(pos.start, start);
+                }
+                result => panic!("`{}` parsed as {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let source = "/// adds\n/// numbers\npub fn add() {\n    /// ignored\n    1\n}\n/** a class */\nclass A {\n    /// a field\n    var a: Int\n}\nvar b = 1";
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        parser.parse(&SourceMap::new());
+
+        let docs = parser
+            .asts
+            .iter()
+            .map(|statement| match statement {
+                Statement::Public { statement } => match &**statement {
+                    Statement::FunctionDeclaration { doc, .. } => doc.clone(),
+                    statement => panic!("unexpected {:?}", statement),
+                },
+                Statement::Class { doc, member, .. } => {
+                    match &member[0] {
+                        Statement::VariableDeclaration { doc, .. } => {
+                            assert_eq!(doc.as_deref(), Some("a field"))
+                        }
+                        statement => panic!("unexpected {:?}", statement),
+                    }
+                    doc.clone()
+                }
+                Statement::VariableDeclaration { doc, .. } => doc.clone(),
+                statement => panic!("unexpected {:?}", statement),
+            })
+            .collect::<Vec<Option<String>>>();
+        assert_eq!(
+            docs,
+            vec![
+                Some("adds\nnumbers".to_string()),
+                Some("a class".to_string()),
+                None
+            ]
+        );
+    }
+
+    fn parse(source: &str) -> (Vec<Statement>, Result<(), ZXError>) {
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        let mut statements = vec![];
+
+        while !parser.is_eof() {
+            let statement = parser.statement();
+            match statement.and_then(|statement| {
+                statements.push(statement);
+                parser.end_of_statement()
+            }) {
+                Ok(()) => {}
+                Err(error) => return (statements, Err(error)),
+            }
+        }
+
+        (statements, Ok(()))
+    }
+
+    #[test]
+    fn test_statement_termination() {
+        // `(c)` on the next line is not a call
+        let (statements, result) = parse("var a = b\n(c)");
+        assert!(result.is_ok());
+        assert_eq!(statements.len(), 2);
+
+        // an operator at the end of a line or a leading `.` continues it
+        let (statements, result) =
+            parse("var a = 1 +\n  2\nvar b = a\n  .c\nf(\n  1,\n  (2\n  - 3)\n)");
+        assert!(result.is_ok());
+        assert_eq!(statements.len(), 3);
+
+        // a leading `-` starts a new statement
+        let (statements, result) = parse("var a = 1\n-1");
+        assert!(result.is_ok());
+        assert_eq!(statements.len(), 2);
+
+        let (statements, result) = parse("var a = 1; var b = 2;; a\n{ b }");
+        assert!(result.is_ok());
+        assert_eq!(statements.len(), 4);
+    }
+
+    #[test]
+    fn test_statements_on_one_line() {
+        let (statements, result) = parse("var a = 1 var b = 2");
+
+        assert_eq!(statements.len(), 1);
+        match result {
+            Err(ZXError::SyntaxError { message, pos }) => {
+                assert_eq!(
+                    message,
+                    "expected `;` or a line break between statements, found VarToken"
+                );
+                assert_eq!((pos.start, pos.end), (10, 13));
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+
+        // the block recovers, the error is only reported
+        let mut parser = Parser::new(Lexer::new("fn f() { a b }", FileId(0)));
+        assert!(parser.statement().is_ok());
+        assert_eq!(parser.reposts.len(), 1);
+    }
+
+    #[test]
+    fn test_lexer_errors_are_reported() {
+        let mut source_map = SourceMap::new();
+        let file = source_map.add_file(&"test.zx".to_string(), &"var a = 1\n#".to_string());
+        // the bad `#` is skipped by the parser but still reported
+        let mut parser = Parser::new(Lexer::new(source_map.source(file), file));
+        parser.parse(&source_map);
+
+        assert!(parser.has_errors());
+        assert_eq!(parser.asts.len(), 1);
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let source = "var a = )\nvar b = 1\nfn f() {\n    var c = = 2\n    c\n}\nclass A {\n    1\n    var d: Int\n}\nvar e = 1 2; var f = 3";
+        let mut parser = Parser::new(Lexer::new(source, FileId(0)));
+        while !parser.is_eof() {
+            let statement = parser.recoverable_statement();
+            parser.asts.push(statement);
+        }
+
+        let errors = parser
+            .reposts
+            .iter()
+            .map(|report| match &report.error {
+                ZXError::SyntaxError { pos, .. } => pos.start,
+                error => panic!("unexpected {:?}", error),
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(errors, vec![8, 41, 67, 96]);
+
+        assert!(matches!(parser.asts[0], Statement::Error { .. }));
+        assert!(matches!(
+            parser.asts[1],
+            Statement::VariableDeclaration { .. }
+        ));
+        match &parser.asts[2] {
+            Statement::FunctionDeclaration { block, .. } => match &**block {
+                Statement::Block { statements, .. } => {
+                    assert!(matches!(statements[0], Statement::Error { .. }));
+                    assert!(matches!(statements[1], Statement::Expression { .. }));
+                }
+                statement => panic!("unexpected {:?}", statement),
+            },
+            statement => panic!("unexpected {:?}", statement),
+        }
+        match &parser.asts[3] {
+            Statement::Class { member, .. } => assert_eq!(member.len(), 2),
+            statement => panic!("unexpected {:?}", statement),
+        }
+        assert!(matches!(parser.asts[4], Statement::Error { .. }));
+        assert!(matches!(
+            parser.asts[5],
+            Statement::VariableDeclaration { .. }
+        ));
+        assert_eq!(parser.asts.len(), 6);
+    }
+
+    // `1 + 2 * 3` as `(Add 1 (Mul 2 3))`
+    fn tree(expression: &Expression) -> String {
+        match expression {
+            Expression::Operator {
+                operator_type,
+                left,
+                right,
+            } => format!("({} {} {})", operator_type, tree(left), tree(right)),
+            Expression::Brackets { content } => tree(content),
+            Expression::Unary {
+                operator_type,
+                expression,
+                ..
+            } => format!("({} {})", operator_type, tree(expression)),
+            Expression::Value { content, .. } => content.get_string().unwrap(),
+            Expression::Identifier { identifier, .. } | Expression::Bool { identifier } => {
+                identifier.get_string().unwrap()
+            }
+            Expression::Array { elements, .. } => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(tree)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expression::Index {
+                target,
+                index,
+                next: None,
+                ..
+            } => format!("{}[{}]", tree(target), tree(index)),
+            Expression::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => format!(
+                "({}{}{})",
+                tree(start),
+                if *inclusive { "..=" } else { ".." },
+                tree(end)
+            ),
+            expression => panic!("unexpected {:?}", expression),
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        for (source, expected) in [
+            ("1 - 2 - 3", "(Sub (Sub 1 2) 3)"),
+            ("1 + 2 * 3 % 4", "(Add 1 (Mod (Mul 2 3) 4))"),
+            ("8 / 4 / 2", "(Div (Div 8 4) 2)"),
+            ("(1 + 2) * 3", "(Mul (Add 1 2) 3)"),
+            ("a + 1 < b * 2", "(Less (Add a 1) (Mul b 2))"),
+            (
+                "a <= b == c >= d",
+                "(Equal (LessEqual a b) (MoreEqual c d))",
+            ),
+            (
+                "a != b || c > d && e",
+                "(Or (NotEqual a b) (And (More c d) e))",
+            ),
+            ("a || b || true", "(Or (Or a b) true)"),
+            ("-a * b", "(Mul (Neg a) b)"),
+            ("-(a + b)", "(Neg (Add a b))"),
+            ("-1.5 - -2", "(Sub (Neg 1.5) 2)"),
+            ("!a && !!b", "(And (Not a) (Not (Not b)))"),
+            ("~a % 2 == 0", "(Equal (Mod (BitNot a) 2) 0)"),
+            ("1 - -x", "(Sub 1 (Neg x))"),
+        ] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[..] {
+                [Statement::Expression { expression }] => {
+                    assert_eq!(tree(expression), expected, "{}", source)
+                }
+                statements => panic!("`{}` parsed as {:?}", source, statements),
+            }
+        }
+    }
+
+    // `a.b[0]` as `a.b[0]`
+    fn target(expression: &Expression) -> String {
+        match expression {
+            Expression::Identifier {
+                identifier,
+                next: None,
+            } => identifier.get_string().unwrap(),
+            Expression::Index {
+                target: indexed,
+                index,
+                next: None,
+                ..
+            } => format!("{}[{}]", target(indexed), tree(index)),
+            Expression::Identifier {
+                identifier,
+                next: Some(next),
+            } => match next.as_ref() {
+                Expression::SubMember { sub_member } => {
+                    format!(
+                        "{}.{}",
+                        identifier.get_string().unwrap(),
+                        target(sub_member)
+                    )
+                }
+                next => panic!("unexpected {:?}", next),
+            },
+            expression => panic!("unexpected {:?}", expression),
+        }
+    }
+
+    #[test]
+    fn test_assignment() {
+        for (source, expected) in [
+            ("a = 1", "a = 1"),
+            ("this.dian_shen = str", "this.dian_shen = str"),
+            ("a.b.c += 2 * 3", "a.b.c Add= (Mul 2 3)"),
+            ("a -= -b; a *= 2", "a Sub= (Neg b)"),
+            ("a /= 2\na %= 2", "a Div= 2"),
+        ] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[0] {
+                Statement::Assignment {
+                    target: assign_target,
+                    operator_type,
+                    value,
+                    ..
+                } => assert_eq!(
+                    format!(
+                        "{} {}= {}",
+                        target(assign_target),
+                        operator_type
+                            .as_ref()
+                            .map_or(String::new(), |operator| operator.to_string()),
+                        tree(value)
+                    ),
+                    expected,
+                    "{}",
+                    source
+                ),
+                statement => panic!("`{}` parsed as {:?}", source, statement),
+            }
+        }
+
+        for (source, message) in [
+            ("1 = 2", "invalid left-hand side of assignment"),
+            ("a() = 2", "invalid left-hand side of assignment"),
+            ("a::b = 2", "invalid left-hand side of assignment"),
+            (
+                "a = }",
+                "expected an expression after the assignment operator",
+            ),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_imports() {
+        let name = |token: &Token| token.get_string().unwrap();
+        let alias = |alias: &Option<Token>| match alias {
+            Some(alias) => format!(" as {}", name(alias)),
+            None => String::new(),
+        };
+
+        for (source, expected) in [
+            ("im math", "math"),
+            ("im math as m", "math as m"),
+            ("im math::pow", "math::pow"),
+            ("im a::b::c as d", "a::b::c as d"),
+            ("im math::*", "math::*"),
+            ("im math::{ pow, PI as pi }", "math::{pow, PI as pi}"),
+            ("im math::{\n    pow,\n    PI,\n}", "math::{pow, PI}"),
+        ] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            let import = match &statements[..] {
+                [Statement::Import { path, kind, .. }] => {
+                    let path = path.iter().map(name).collect::<Vec<String>>().join("::");
+                    match kind {
+                        ImportKind::Single { alias: as_name } => {
+                            format!("{}{}", path, alias(as_name))
+                        }
+                        ImportKind::Group { items } => format!(
+                            "{}::{{{}}}",
+                            path,
+                            items
+                                .iter()
+                                .map(|item| format!("{}{}", name(&item.name), alias(&item.alias)))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        ),
+                        ImportKind::Glob => format!("{}::*", path),
                     }
-                    .main();
                 }
-                Err(error) => {
-                    Report {
-                        level: Level::Error,
-                        error,
+                statements => panic!("`{}` parsed as {:?}", source, statements),
+            };
+            assert_eq!(import, expected, "{}", source);
+        }
+
+        for (source, message) in [
+            ("im math::{}", "empty import list"),
+            (
+                "im math::{ a b }",
+                "expected `,` or `}` in the import list, found IdentifierToken",
+            ),
+            (
+                "im math::fn",
+                "expected identifier, found keyword `fn`, reserved words cannot be used as names",
+            ),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_class_inheritance() {
+        let source = "class Dog : Animal {\n    pub override fn speak() {}\n    override fn name() {}\n    fn run() {}\n}";
+        let (statements, result) = parse(source);
+        assert!(result.is_ok());
+
+        match &statements[..] {
+            [Statement::Class {
+                inherit: Some(inherit),
+                member,
+                ..
+            }] => {
+                assert_eq!(inherit.get_string().unwrap(), "Animal");
+                assert!(matches!(
+                    &member[..],
+                    [
+                        Statement::Public { statement },
+                        Statement::Override { .. },
+                        Statement::FunctionDeclaration { .. },
+                    ] if matches!(**statement, Statement::Override { .. })
+                ));
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        for (source, message) in [
+            (
+                "override var a = 1",
+                "`override` is not followed by a method",
+            ),
+            (
+                "class Dog : {}",
+                "Unexpected token LeftCurlyBracketsToken, expected token IdentifierToken",
+            ),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generics() {
+        // `Map<Str, List<Int>>?` as `Map(Str, List(Int))?`
+        fn view(expression: &Expression) -> String {
+            match expression {
+                Expression::Type {
+                    identifier,
+                    arguments,
+                    nullable,
+                } => {
+                    let mut text = identifier.get_string().unwrap();
+                    if !arguments.is_empty() {
+                        text = format!(
+                            "{}({})",
+                            text,
+                            arguments
+                                .iter()
+                                .map(view)
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        );
+                    }
+                    if *nullable {
+                        text.push('?');
                     }
-                    .print(&source, &path);
+                    text
                 }
+                expression => panic!("not a type {:?}", expression),
             }
-        });
+        }
+        let source = "fn max<T, U>(a: T, b: List<U>): T {}\nclass Pair<K, V> {}\nvar m: Map<Str, List<Int>>? = a\nvar n: List<Int>= b";
+        let (statements, result) = parse(source);
+        assert!(result.is_ok(), "{:?}", result);
+
+        let names = |tokens: &Vec<Token>| {
+            tokens
+                .iter()
+                .map(|token| token.get_string().unwrap())
+                .collect::<Vec<String>>()
+        };
+        match &statements[..] {
+            [Statement::FunctionDeclaration {
+                type_parameters,
+                parameters,
+                ..
+            }, Statement::Class {
+                type_parameters: class_parameters,
+                ..
+            }, Statement::VariableDeclaration {
+                type_identifier: Some(map),
+                ..
+            }, Statement::VariableDeclaration {
+                type_identifier: Some(list),
+                value: Some(_),
+                ..
+            }] => {
+                assert_eq!(names(type_parameters), ["T", "U"]);
+                assert_eq!(view(&parameters[1].type_expression), "List(U)");
+                assert_eq!(names(class_parameters), ["K", "V"]);
+                assert_eq!(view(map), "Map(Str, List(Int))?");
+                assert_eq!(view(list), "List(Int)");
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        for (source, message) in [
+            (
+                "fn max<T(a: T) {}",
+                "expected `,` or `>` in the type parameter list, found LeftParenthesesToken",
+            ),
+            ("class List<T", "unclosed type parameter list"),
+            (
+                "var a: List<Int = 1",
+                "expected `,` or `>` in the type argument list, found EqualToken",
+            ),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrays() {
+        // `[[Int]]?` as `[[Int]]?`
+        fn view(expression: &Expression) -> String {
+            match expression {
+                Expression::ArrayType {
+                    element, nullable, ..
+                } => format!("[{}]{}", view(element), if *nullable { "?" } else { "" }),
+                Expression::Type {
+                    identifier,
+                    nullable,
+                    ..
+                } => format!(
+                    "{}{}",
+                    identifier.get_string().unwrap(),
+                    if *nullable { "?" } else { "" }
+                ),
+                expression => panic!("not a type {:?}", expression),
+            }
+        }
+        let source = "var a: [[Int]]? = b\nfn f(c: [Str?]): [Int] {}";
+        let (statements, result) = parse(source);
+        assert!(result.is_ok(), "{:?}", result);
+
+        match &statements[..] {
+            [Statement::VariableDeclaration {
+                type_identifier: Some(array),
+                ..
+            }, Statement::FunctionDeclaration {
+                parameters,
+                return_type: Some(return_type),
+                ..
+            }] => {
+                assert_eq!(view(array), "[[Int]]?");
+                assert_eq!(view(&parameters[0].type_expression), "[Str?]");
+                assert_eq!(view(return_type), "[Int]");
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        for (source, expected) in [
+            ("[1, 2, 3]", "[1, 2, 3]"),
+            ("[]", "[]"),
+            ("[\n    1,\n    2,\n]", "[1, 2]"),
+            ("a[i][j + 1]", "a[i][(Add j 1)]"),
+            ("[1, 2][0] * 2", "(Mul [1, 2][0] 2)"),
+            ("(a)[0]", "a[0]"),
+        ] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[..] {
+                [Statement::Expression { expression }] => {
+                    assert_eq!(tree(expression), expected, "{}", source)
+                }
+                statements => panic!("`{}` parsed as {:?}", source, statements),
+            }
+        }
+
+        // `[` on the next line starts a new statement
+        let (statements, result) = parse("a\n[1]");
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(statements.len(), 2);
+
+        for (source, expected) in [("a[0] = 1", "a[0]"), ("a.b[i][j] += 1", "a.b[i][j]")] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[0] {
+                Statement::Assignment {
+                    target: assign_target,
+                    ..
+                } => assert_eq!(target(assign_target), expected, "{}", source),
+                statement => panic!("`{}` parsed as {:?}", source, statement),
+            }
+        }
+
+        for (source, message) in [
+            ("var a = [1, 2", "unclosed square bracket"),
+            (
+                "var a = [1 2]",
+                "expected `,` or `]` in the array, found LiteralToken",
+            ),
+            ("a[]", "expected an index in `[]`"),
+            ("a[0].b() = 1", "invalid left-hand side of assignment"),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        for (source, expected) in [
+            ("0..10", "(0..10)"),
+            ("a + 1..=b * 2", "((Add a 1)..=(Mul b 2))"),
+            ("a || b..c && d", "((Or a b)..(And c d))"),
+            ("[0..n]", "[(0..n)]"),
+            ("(0..2)", "(0..2)"),
+        ] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[..] {
+                [Statement::Expression { expression }] => {
+                    assert_eq!(tree(expression), expected, "{}", source)
+                }
+                statements => panic!("`{}` parsed as {:?}", source, statements),
+            }
+        }
+
+        let (statements, result) = parse("for i in 0..=n {\n    i\n}");
+        assert!(result.is_ok(), "{:?}", result);
+        match &statements[..] {
+            [Statement::ForLoop {
+                for_var_name, iter, ..
+            }] => {
+                assert_eq!(for_var_name.get_string().unwrap(), "i");
+                match iter.as_ref() {
+                    Statement::Expression { expression } => assert_eq!(tree(expression), "(0..=n)"),
+                    iter => panic!("iterable parsed as {:?}", iter),
+                }
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        for (source, message) in [
+            ("var r = 0..", "expected the end of the range after `..`"),
+            ("var r = 0..=}", "expected the end of the range after `..=`"),
+            ("for i in {}", "expected an iterable after `in`"),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: error, .. }) => {
+                    assert_eq!(error, message, "{}", source)
+                }
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_functions_as_values() {
+        // `(Int, [Int]) -> Int` as `(Int, [Int]) -> Int`
+        fn view(expression: &Expression) -> String {
+            match expression {
+                Expression::FunctionType {
+                    parameters,
+                    return_type,
+                    ..
+                } => format!(
+                    "({}) -> {}",
+                    parameters
+                        .iter()
+                        .map(view)
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    view(return_type)
+                ),
+                Expression::ArrayType { element, .. } => format!("[{}]", view(element)),
+                Expression::Type { identifier, .. } => identifier.get_string().unwrap(),
+                expression => panic!("not a type {:?}", expression),
+            }
+        }
+        let source = "var add: (Int, [Int]) -> Int = fn(a: Int, b: [Int]): Int {\n    a\n}\nfn apply(f: (Int) -> () -> Void, x: Int) {}";
+        let (statements, result) = parse(source);
+        assert!(result.is_ok(), "{:?}", result);
+
+        match &statements[..] {
+            [Statement::VariableDeclaration {
+                type_identifier: Some(function_type),
+                value: Some(value),
+                ..
+            }, Statement::FunctionDeclaration { parameters, .. }] => {
+                assert_eq!(view(function_type), "(Int, [Int]) -> Int");
+                assert!(matches!(
+                    value.as_ref(),
+                    Statement::Expression {
+                        expression: Expression::Function {
+                            parameters,
+                            return_type: Some(_),
+                            block,
+                            ..
+                        }
+                    } if parameters.len() == 2
+                        && matches!(block.as_ref(), Statement::Block { statements, .. } if statements.len() == 1)
+                ));
+                assert_eq!(view(&parameters[0].type_expression), "(Int) -> () -> Void");
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        // an anonymous function can be passed and can start a statement
+        for source in ["apply(fn(x: Int) { x * 2 }, 3)", "fn(x: Int) {}"] {
+            let (statements, result) = parse(source);
+            assert!(result.is_ok(), "{}", source);
+
+            match &statements[..] {
+                [Statement::Expression {
+                    expression: Expression::Call { arguments, .. },
+                }] => assert!(matches!(
+                    arguments[0],
+                    Expression::Function {
+                        return_type: None,
+                        ..
+                    }
+                )),
+                [Statement::Expression {
+                    expression: Expression::Function { parameters, .. },
+                }] => assert_eq!(parameters.len(), 1),
+                statements => panic!("`{}` parsed as {:?}", source, statements),
+            }
+        }
+
+        match parse("var f: (Int) Int = g").1 {
+            Err(ZXError::SyntaxError { message, .. }) => assert_eq!(
+                message,
+                "expected `->` and the return type of the function type"
+            ),
+            result => panic!("gave {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_enums_and_match() {
+        let source = "enum Shape {\n    Circle(Float), Rect(Float, Float)\n    Empty,\n}\nvar area = match shape {\n    Shape::Circle(r) => r * r\n    Shape::Rect(w, _) => { w }, -1 => 0.0\n    _ => 0.0\n}";
+        let (statements, result) = parse(source);
+        assert!(result.is_ok(), "{:?}", result);
+
+        match &statements[..] {
+            [Statement::Enum { variants, .. }, Statement::VariableDeclaration {
+                value: Some(value), ..
+            }] => {
+                assert_eq!(
+                    variants
+                        .iter()
+                        .map(|variant| (variant.name.get_string().unwrap(), variant.payload.len()))
+                        .collect::<Vec<(String, usize)>>(),
+                    vec![
+                        ("Circle".to_string(), 1),
+                        ("Rect".to_string(), 2),
+                        ("Empty".to_string(), 0)
+                    ]
+                );
+                let arms = match value.as_ref() {
+                    Statement::Expression {
+                        expression: Expression::Match { arms, .. },
+                    } => arms,
+                    value => panic!("parsed as {:?}", value),
+                };
+                assert_eq!(arms.len(), 4);
+                assert!(matches!(
+                    &arms[0].pattern,
+                    Pattern::Variant { fields, .. }
+                        if matches!(fields[..], [Pattern::Binding { .. }])
+                ));
+                assert!(matches!(
+                    &arms[1].pattern,
+                    Pattern::Variant { fields, .. }
+                        if matches!(fields[..], [Pattern::Binding { .. }, Pattern::Wildcard { .. }])
+                ));
+                assert!(matches!(arms[1].body.as_ref(), Statement::Block { .. }));
+                assert!(matches!(
+                    arms[2].pattern,
+                    Pattern::Literal {
+                        kid: Literal::NegativeInteger,
+                        ..
+                    }
+                ));
+                assert!(matches!(arms[3].pattern, Pattern::Wildcard { .. }));
+            }
+            statements => panic!("parsed as {:?}", statements),
+        }
+
+        for (source, message) in [
+            ("match a { 1 2 }", "expected `=>` after the pattern"),
+            (
+                "match a { 1 => 2 3 => 4 }",
+                "expected `,` or a line break between match arms, found LiteralToken",
+            ),
+        ] {
+            match parse(source).1 {
+                Err(ZXError::SyntaxError { message: found, .. }) => assert_eq!(found, message),
+                result => panic!("`{}` gave {:?}", source, result),
+            }
+        }
     }
 }
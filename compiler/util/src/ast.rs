@@ -1,4 +1,4 @@
-use crate::token::{Literal, Token};
+use crate::token::{Literal, Position, Token};
 
 #[derive(Debug, Clone)]
 pub enum Statement {
@@ -8,9 +8,18 @@ pub enum Statement {
     Public {
         statement: Box<Statement>,
     },
+    /// `override fn`, a method replacing the one of the base class
+    Override {
+        override_keyword: Token,
+        statement: Box<Statement>,
+    },
     FunctionDeclaration {
+        /// text of the doc comments right before the declaration
+        doc: Option<String>,
         fn_keyword: Token,
         function_name: Token,
+        /// the names in `fn max<T>`
+        type_parameters: Vec<Token>,
         left_parentheses: Token,
         parameters: Vec<Parameter>,
         right_parentheses: Token,
@@ -18,22 +27,38 @@ pub enum Statement {
         block: Box<Statement>,
     },
     VariableDeclaration {
+        doc: Option<String>,
         var_keyword: Token,
         var_name: Token,
         type_identifier: Option<Expression>,
         equal: Option<Token>,
         value: Option<Box<Statement>>,
     },
+    /// `im a::b`, `im a::{ b, c as d }` or `im a::*`
     Import {
         im_keyword: Token,
+        /// the names before `::{` or `::*`, or every name of a single import
+        path: Vec<Token>,
+        kind: ImportKind,
     },
     Class {
+        doc: Option<String>,
         class_keyword: Token,
         class_name: Token,
+        /// the names in `class List<T>`
+        type_parameters: Vec<Token>,
         clone: Option<Token>,
+        /// the base class in `class Dog : Animal`
         inherit: Option<Token>,
         member: Vec<Statement>,
     },
+    /// `enum Shape { Circle(Float), Rect(Float, Float), Empty }`
+    Enum {
+        doc: Option<String>,
+        enum_keyword: Token,
+        enum_name: Token,
+        variants: Vec<Variant>,
+    },
     If {
         if_keyword: Token,
         condition: Expression,
@@ -68,6 +93,18 @@ pub enum Statement {
     Expression {
         expression: Expression,
     },
+    /// `target = value`, a compound assignment like `target += value` has
+    /// the operator in `operator_type`
+    Assignment {
+        target: Expression,
+        equal: Token,
+        operator_type: Option<Operator>,
+        value: Expression,
+    },
+    /// A statement that failed to parse, its error is in the parser reports
+    Error {
+        pos: Position,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -90,8 +127,65 @@ pub enum Expression {
     SubMember {
         sub_member: Box<Expression>,
     },
+    /// `[Int]` or `[Str]?`
+    ArrayType {
+        left_square_brackets: Token,
+        element: Box<Expression>,
+        right_square_brackets: Token,
+        nullable: bool,
+    },
+    /// `[1, 2, 3]`
+    Array {
+        left_square_brackets: Token,
+        elements: Vec<Expression>,
+        right_square_brackets: Token,
+    },
+    /// `target[index]`, `next` is what follows it like `.len()` of
+    /// `a[0].len()`
+    Index {
+        target: Box<Expression>,
+        left_square_brackets: Token,
+        index: Box<Expression>,
+        right_square_brackets: Token,
+        next: Option<Box<Expression>>,
+    },
+    /// `start..end`, `start..=end` when `inclusive`
+    Range {
+        start: Box<Expression>,
+        range_operator: Token,
+        end: Box<Expression>,
+        inclusive: bool,
+    },
+    /// `(Int, Int) -> Int`
+    FunctionType {
+        left_parentheses: Token,
+        parameters: Vec<Expression>,
+        right_parentheses: Token,
+        arrow: Token,
+        return_type: Box<Expression>,
+    },
+    /// `fn(a: Int): Int { a + 1 }`, an anonymous function, it can use the
+    /// local variables around it
+    Function {
+        fn_keyword: Token,
+        left_parentheses: Token,
+        parameters: Vec<Parameter>,
+        right_parentheses: Token,
+        return_type: Option<Box<Expression>>,
+        block: Box<Statement>,
+    },
+    /// `match value { pattern => arm, .. }`, the value of the first arm
+    /// whose pattern matches
+    Match {
+        match_keyword: Token,
+        value: Box<Expression>,
+        arms: Vec<MatchArm>,
+        right_curly_brackets: Token,
+    },
+    /// `Int`, `Str?` or `List<Int>`
     Type {
         identifier: Token,
+        arguments: Vec<Expression>,
         nullable: bool,
     },
     Bool {
@@ -106,9 +200,70 @@ pub enum Expression {
         left: Box<Expression>,
         right: Box<Expression>,
     },
+    /// `-a`, `!a` or `~a`
+    Unary {
+        operator_type: UnaryOperator,
+        operator: Token,
+        expression: Box<Expression>,
+    },
     Brackets {
         content: Box<Expression>,
     },
+    /// `"a ${b} c"`, parts are string `Value`s and embedded expressions
+    StringTemplate {
+        left_quote: Token,
+        parts: Vec<Expression>,
+        right_quote: Token,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportKind {
+    /// `im a::b` or `im a::b as c`, `b` is a module or an item of `a`
+    Single { alias: Option<Token> },
+    /// `im a::{ b, c as d }`
+    Group { items: Vec<ImportItem> },
+    /// `im a::*`, every public item of `a`
+    Glob,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportItem {
+    pub name: Token,
+    pub alias: Option<Token>,
+}
+
+/// `Circle(Float)` of an enum, `payload` are the types of its fields
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: Token,
+    pub payload: Vec<Expression>,
+}
+
+/// `pattern => body`, `body` is an expression or a block
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub fat_arrow: Token,
+    pub body: Box<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `1`, `-1`, `'a'` or `"a"`
+    Literal { kid: Literal, content: Token },
+    /// `true` or `false`
+    Bool { identifier: Token },
+    /// `Shape::Circle(r)`, `Shape::Empty` has no `fields`
+    Variant {
+        enum_name: Token,
+        variant_name: Token,
+        fields: Vec<Pattern>,
+    },
+    /// A name, the matched value is bound to it in the arm
+    Binding { name: Token },
+    /// `_`
+    Wildcard { underscore: Token },
 }
 
 #[derive(Debug, Clone)]
@@ -122,4 +277,26 @@ pub enum Operator {
     Add,
     Sub,
     Mul,
+    Div,
+    Mod,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    More,
+    MoreEqual,
+    /// `&&`, the right side is only evaluated when the left one is `true`
+    And,
+    /// `||`, the right side is only evaluated when the left one is `false`
+    Or,
+}
+
+#[derive(Debug, Clone, Display)]
+pub enum UnaryOperator {
+    /// `-`
+    Neg,
+    /// `!`
+    Not,
+    /// `~`
+    BitNot,
 }
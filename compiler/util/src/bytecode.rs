@@ -1,11 +1,49 @@
+use crate::ast::{Operator, UnaryOperator};
+use crate::token::Position;
 use crate::zx_type::ZXTyped;
 
 #[derive(Clone, Debug)]
 pub enum BytecodeType {
+    /// Call the function at `path` with `arguments`, `type_arguments` pick
+    /// the instance of a generic function
     Call {
         path: String,
         ret_type: ZXTyped,
         argument_types: Vec<ZXTyped>,
+        type_arguments: Vec<ZXTyped>,
+        arguments: Vec<BytecodeType>,
+    },
+    /// Call the method `name` of `object`, a value of the class at `class`.
+    /// The method comes from the vtable of the object unless `direct`, a
+    /// method called on `super` is the one of the base class.
+    CallMethod {
+        object: Box<BytecodeType>,
+        class: String,
+        name: String,
+        direct: bool,
+        ret_type: ZXTyped,
+        arguments: Vec<BytecodeType>,
+    },
+    /// A new object of the class `name` declared at `path`
+    New {
+        name: String,
+        path: String,
+    },
+    /// The field `index` of `object`, a value of the class at `class`.
+    /// Fields are counted in member order, inherited fields first.
+    FieldLoad {
+        object: Box<BytecodeType>,
+        class: String,
+        index: usize,
+        field_type: ZXTyped,
+    },
+    /// Store `value` in the field `index` of `object`, a value of the class
+    /// at `class`
+    FieldStore {
+        object: Box<BytecodeType>,
+        class: String,
+        index: usize,
+        value: Box<BytecodeType>,
     },
     Alloca {
         path: String,
@@ -13,7 +51,7 @@ pub enum BytecodeType {
     },
     Store {
         path: String,
-        value: BytecodeValue,
+        value: Box<BytecodeType>,
     },
     Load {
         path: String,
@@ -31,6 +69,116 @@ pub enum BytecodeType {
     Value {
         value: BytecodeValue,
     },
+    /// Convert a value of `value_type` to `Str`
+    ToStr {
+        value: Box<BytecodeType>,
+        value_type: ZXTyped,
+    },
+    /// Concatenate two `Str` values
+    Concat {
+        left: Box<BytecodeType>,
+        right: Box<BytecodeType>,
+    },
+    /// Arithmetic or comparison on two values of `operand_type`
+    Binary {
+        operator: Operator,
+        operand_type: ZXTyped,
+        left: Box<BytecodeType>,
+        right: Box<BytecodeType>,
+    },
+    /// `-`, `!` or `~` on a value of `operand_type`
+    Unary {
+        operator: UnaryOperator,
+        operand_type: ZXTyped,
+        value: Box<BytecodeType>,
+    },
+    /// A new array holding `elements`
+    Array {
+        element_type: ZXTyped,
+        elements: Vec<BytecodeType>,
+    },
+    /// The element at `index` of `array`, an index out of bounds stops the
+    /// program with an error at `pos`
+    Index {
+        element_type: ZXTyped,
+        array: Box<BytecodeType>,
+        index: Box<BytecodeType>,
+        pos: Position,
+    },
+    /// Store `value` at `index` of `array`, bounds checked like `Index`
+    IndexStore {
+        element_type: ZXTyped,
+        array: Box<BytecodeType>,
+        index: Box<BytecodeType>,
+        value: Box<BytecodeType>,
+        pos: Position,
+    },
+    /// The number of elements of `array`, as an `Int`
+    Len {
+        array: Box<BytecodeType>,
+    },
+    /// `start..end` of `element_type` values, `end` is included when
+    /// `inclusive`
+    Range {
+        element_type: ZXTyped,
+        start: Box<BytecodeType>,
+        end: Box<BytecodeType>,
+        inclusive: bool,
+    },
+    /// Run `body` once for every value of `iterable`, a range, an array or
+    /// a `Str`, stored in the variable at `variable` first
+    ForLoop {
+        variable: String,
+        element_type: ZXTyped,
+        iterable: Box<BytecodeType>,
+        iterable_type: ZXTyped,
+        body: Box<BytecodeType>,
+    },
+    /// The function at `path` used as a value, it captures nothing
+    FunctionRef {
+        path: String,
+        function_type: ZXTyped,
+    },
+    /// An anonymous function used as a value, the `captures` are the local
+    /// variables around it that `body` uses, their values are copied into
+    /// the closure when it is created
+    Closure {
+        path: String,
+        parameters: Vec<Variable>,
+        return_type: ZXTyped,
+        captures: Vec<Variable>,
+        body: Box<BytecodeType>,
+    },
+    /// Call the function value `function` of `function_type`, a function
+    /// or a closure
+    CallValue {
+        function: Box<BytecodeType>,
+        function_type: ZXTyped,
+        arguments: Vec<BytecodeType>,
+    },
+    /// A new value of the variant `variant` of the enum `name`, tagged with
+    /// `tag`
+    EnumValue {
+        name: String,
+        variant: String,
+        tag: u32,
+        payload: Vec<BytecodeType>,
+    },
+    /// Run the first of `arms` whose pattern matches `value`, the last
+    /// bytecode of the arm is the result unless `result_type` is `Void`
+    Match {
+        value: Box<BytecodeType>,
+        value_type: ZXTyped,
+        result_type: ZXTyped,
+        arms: Vec<MatchArm>,
+    },
+    /// `&&` or `||` on two `Bool` values, `right` is only evaluated when
+    /// `left` does not decide the result
+    Logical {
+        operator: Operator,
+        left: Box<BytecodeType>,
+        right: Box<BytecodeType>,
+    },
 }
 
 impl BytecodeType {
@@ -40,6 +188,12 @@ impl BytecodeType {
         }
     }
 
+    pub fn long_value(value: i64) -> BytecodeType {
+        BytecodeType::Value {
+            value: BytecodeValue::Long { value },
+        }
+    }
+
     pub fn char_value(value: char) -> BytecodeType {
         BytecodeType::Value {
             value: BytecodeValue::Char { value },
@@ -52,6 +206,12 @@ impl BytecodeType {
         }
     }
 
+    pub fn double_value(value: f64) -> BytecodeType {
+        BytecodeType::Value {
+            value: BytecodeValue::Double { value },
+        }
+    }
+
     pub fn bool_value(value: bool) -> BytecodeType {
         BytecodeType::Value {
             value: BytecodeValue::Bool { value },
@@ -71,10 +231,44 @@ impl BytecodeType {
     }
 }
 
+/// A parameter or a captured variable of a closure
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub path: String,
+    pub var_type: ZXTyped,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Vec<BytecodeType>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Matches every value, a name binds it to the variable at `path`
+    Binding { path: Option<String> },
+    /// Matches a value equal to `value` of `value_type`
+    Value {
+        value: Box<BytecodeType>,
+        value_type: ZXTyped,
+    },
+    /// Matches the variant tagged `tag` of the enum `name` when its fields
+    /// match `fields`
+    Variant {
+        name: String,
+        variant: String,
+        tag: u32,
+        fields: Vec<Pattern>,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum BytecodeValue {
     Int { value: i32 },
+    Long { value: i64 },
     Float { value: f32 },
+    Double { value: f64 },
     Bool { value: bool },
     String { value: String },
     Char { value: char },
@@ -1,6 +1,6 @@
 use crate::token::Position;
 
-#[derive(Debug, Display)]
+#[derive(Debug, Display, Clone)]
 pub enum ZXError {
     SyntaxError { message: String, pos: Position },
     TypeError { message: String, pos: Position },
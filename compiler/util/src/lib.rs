@@ -7,6 +7,8 @@ pub mod bytecode;
 pub mod error;
 pub mod report;
 pub mod scope;
+pub mod source_map;
+pub mod symbol;
 pub mod token;
 pub mod view_ast_tree;
 pub mod zx_type;
@@ -1,7 +1,8 @@
 use crate::error::ZXError;
-use crate::token::Position;
+use crate::source_map::SourceMap;
 use std::fs;
 use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
 
 pub enum Level {
     Error,
@@ -10,10 +11,13 @@ pub enum Level {
 }
 
 #[derive(Debug)]
-struct PrintSource {
-    source: String,
-    line_number: i32,
-    arrow_position: Position,
+struct PrintSource<'a> {
+    source: &'a str,
+    line_number: usize,
+    column: usize,
+    // display columns of the underline, end exclusive
+    arrow_start: usize,
+    arrow_end: usize,
 }
 
 pub struct Report {
@@ -22,10 +26,7 @@ pub struct Report {
 }
 
 impl Report {
-    pub fn print(&self, source: &String, path: &String) {
-        let mut source_index: usize = 0;
-        let mut line_number = 1;
-        let mut print_source = vec![];
+    pub fn print(&self, source_map: &SourceMap) {
         let color_char = match self.level {
             Level::Error => "\x1b[31m".to_string(),
             Level::Warning => "\x1b[33m".to_string(),
@@ -45,53 +46,51 @@ impl Report {
         self.print_error_message(color_char, message.to_string());
 
         if let Some(pos) = pos {
-            for line_code in source.split('\n') {
-                if source_index + line_code.len() >= pos.start {
-                    print_source.push(PrintSource {
-                        source: line_code.to_string(),
-                        line_number,
-                        arrow_position: Position {
-                            start: pos.start - source_index,
-                            end: if source_index + line_code.len() >= pos.end {
-                                if pos.end - source_index > pos.start - source_index {
-                                    pos.end - source_index - 1
-                                } else {
-                                    pos.end - source_index
-                                }
-                            } else {
-                                source_index + line_code.len()
-                            },
-                        },
-                    });
+            let file = source_map.file(pos.file);
+            let (start, end) = source_map.span(pos);
+            // a multi-line span ending at column 0 does not touch its last line
+            let end_line = if end.line > start.line && end.column == 0 {
+                end.line - 1
+            } else {
+                end.line
+            };
+            let print_source = (start.line..=end_line)
+                .map(|line| {
+                    let source = file.line(line);
+                    let start_column = if line == start.line {
+                        start.column.min(source.len())
+                    } else {
+                        0
+                    };
+                    let end_column = if line == end.line {
+                        end.column.min(source.len())
+                    } else {
+                        source.len()
+                    };
+                    let arrow_start = source[..start_column].width();
+                    let arrow_end = source[..end_column].width();
 
-                    if source_index + line_code.len() >= pos.end {
-                        break;
+                    PrintSource {
+                        source,
+                        line_number: line + 1,
+                        column: source[..start_column].chars().count() + 1,
+                        arrow_start,
+                        arrow_end: arrow_end.max(arrow_start + 1),
                     }
-                }
+                })
+                .collect::<Vec<PrintSource>>();
 
-                line_number += 1;
-                source_index += line_code.len() + 1;
-            }
-
-            let max_number = print_source
-                .iter()
-                .max_by(|a, b| a.line_number.cmp(&b.line_number))
-                .unwrap();
-            let max_number = format!("{}", max_number.line_number).len() + 1;
+            let max_number = format!("{}", end_line + 1).len() + 1;
 
-            let src_dir = PathBuf::from(path);
+            let src_dir = PathBuf::from(&file.path);
             let path_string = fs::canonicalize(&src_dir)
-                .unwrap()
-                .into_os_string()
-                .into_string()
-                .unwrap();
+                .map(|path| path.display().to_string())
+                .unwrap_or(file.path.to_string());
 
             for source in print_source {
                 println!(
                     " ===> {}:{}:{}",
-                    path_string,
-                    source.line_number,
-                    source.arrow_position.start + 1
+                    path_string, source.line_number, source.column
                 );
                 println!("{:<width$}|", "", width = max_number);
                 println!(
@@ -104,8 +103,8 @@ impl Report {
                     "{space:<width$}| {space:>arrow_start$}{space:^>arrow_width$}",
                     space = "",
                     width = max_number,
-                    arrow_start = source.arrow_position.start,
-                    arrow_width = source.arrow_position.end - source.arrow_position.start + 1
+                    arrow_start = source.arrow_start,
+                    arrow_width = source.arrow_end - source.arrow_start
                 );
                 println!("{:<width$}|", "", width = max_number);
             }
@@ -1,10 +1,14 @@
 use crate::bytecode::BytecodeType;
+use crate::symbol::Symbol;
 use crate::token::Position;
 use crate::zx_type::ZXTyped;
 
 #[derive(Clone, Debug)]
 pub enum ScopeType {
     DefFunction {
+        /// `T` of `fn max<T>`, parameters and the return type use them as
+        /// `ZXTyped::Param`
+        type_parameters: Vec<Symbol>,
         parameters: Vec<Scope>,
         block: BytecodeType,
         return_type: ZXTyped,
@@ -14,9 +18,23 @@ pub enum ScopeType {
         var_type: ZXTyped,
         value: Option<BytecodeType>,
     },
+    /// `members` start with the ones inherited from `base`, in the order of
+    /// the base class, an overriding method takes the place of the one it
+    /// overrides
     DefClass {
+        type_parameters: Vec<Symbol>,
         members: Scopes,
+        base: Option<Symbol>,
     },
+    /// `variants` in declaration order, the tag of a variant is its index
+    DefEnum { variants: Scopes },
+    /// A variant of an enum, `payload` are the types of its fields
+    DefVariant { tag: u32, payload: Vec<ZXTyped> },
+    /// `T` of `fn max<T>` or `class List<T>`, only visible in the
+    /// declaration
+    DefTypeParameter,
+    /// An imported module, `members` are its public items
+    DefModule { members: Scopes },
     Block {
         children: Scopes,
         ret: (ZXTyped, Option<Position>),
@@ -26,11 +44,101 @@ pub enum ScopeType {
 
 #[derive(Clone, Debug)]
 pub struct Scope {
-    pub name: String,
+    pub name: Symbol,
     pub path: String,
     pub pos: Position,
     pub scope_type: ScopeType,
     pub uses_num: i32,
+    /// doc comment of the declaration
+    pub doc: Option<String>,
+    /// declared with `pub`, other modules can import it
+    pub public: bool,
+}
+
+impl Scope {
+    /// The generic function or class with its type parameters replaced by
+    /// `arguments`. The instance has its own path, `$max<Int>` for `$max`,
+    /// and so do the members of a class instance.
+    pub fn instantiate(&self, arguments: &[ZXTyped]) -> Scope {
+        let path = format!(
+            "{}<{}>",
+            self.path,
+            arguments
+                .iter()
+                .map(|argument| argument.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+        let scope_type = match &self.scope_type {
+            ScopeType::DefFunction {
+                type_parameters, ..
+            } => substitute(&self.scope_type, type_parameters, arguments),
+            ScopeType::DefClass {
+                type_parameters,
+                members,
+                base,
+            } => ScopeType::DefClass {
+                type_parameters: vec![],
+                members: Scopes {
+                    scopes: members
+                        .scopes
+                        .iter()
+                        .map(|member| Scope {
+                            path: match member.path.strip_prefix(&self.path) {
+                                Some(rest) if rest.starts_with('$') => format!("{}{}", path, rest),
+                                _ => member.path.clone(),
+                            },
+                            scope_type: substitute(&member.scope_type, type_parameters, arguments),
+                            ..member.clone()
+                        })
+                        .collect(),
+                },
+                base: *base,
+            },
+            scope_type => scope_type.clone(),
+        };
+
+        Scope {
+            path,
+            scope_type,
+            ..self.clone()
+        }
+    }
+}
+
+// the types of a variable or the signature of a function with `parameters`
+// replaced, the type parameters of a function left are its own
+fn substitute(scope_type: &ScopeType, parameters: &[Symbol], arguments: &[ZXTyped]) -> ScopeType {
+    match scope_type {
+        ScopeType::DefVariable { var_type, value } => ScopeType::DefVariable {
+            var_type: var_type.substitute(parameters, arguments),
+            value: value.clone(),
+        },
+        ScopeType::DefFunction {
+            type_parameters,
+            parameters: function_parameters,
+            block,
+            return_type,
+            children,
+        } => ScopeType::DefFunction {
+            type_parameters: type_parameters
+                .iter()
+                .filter(|type_parameter| !parameters.contains(type_parameter))
+                .cloned()
+                .collect(),
+            parameters: function_parameters
+                .iter()
+                .map(|parameter| Scope {
+                    scope_type: substitute(&parameter.scope_type, parameters, arguments),
+                    ..parameter.clone()
+                })
+                .collect(),
+            block: block.clone(),
+            return_type: return_type.substitute(parameters, arguments),
+            children: children.clone(),
+        },
+        scope_type => scope_type.clone(),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,10 +151,10 @@ impl Scopes {
         Scopes { scopes: vec![] }
     }
 
-    pub fn find_scope(&mut self, name: &String) -> Option<Scope> {
+    pub fn find_scope(&mut self, name: Symbol) -> Option<Scope> {
         let mut find: Option<Scope> = None;
         for scope in self.scopes.iter_mut() {
-            if scope.name.eq(name) {
+            if scope.name == name {
                 scope.uses_num += 1;
                 find = Some(scope.clone())
             }
@@ -59,6 +167,17 @@ impl Scopes {
         self.scopes.push(scope);
     }
 
+    pub fn public_scopes(&self) -> Scopes {
+        Scopes {
+            scopes: self
+                .scopes
+                .iter()
+                .filter(|scope| scope.public)
+                .cloned()
+                .collect(),
+        }
+    }
+
     pub fn no_used_variables_or_functions(&self) -> Vec<Scope> {
         self.scopes
             .iter()
@@ -0,0 +1,159 @@
+use crate::token::Position;
+use std::fs;
+use std::io;
+
+/// Index of a file loaded into a `SourceMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(pub usize);
+
+/// A zero-based line and column inside a source file.
+///
+/// `column` counts UTF-8 bytes from the start of the line, `utf16_column`
+/// counts UTF-16 code units (what editors speaking LSP expect).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+    pub utf16_column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub source: String,
+    // byte offset of the first character of every line
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(path: &String, source: &String) -> SourceFile {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|(_, byte)| *byte == b'\n')
+                .map(|(index, _)| index + 1),
+        );
+
+        SourceFile {
+            path: path.to_string(),
+            source: source.to_string(),
+            line_starts,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Line index containing the byte `offset`
+    pub fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    /// Text of the line `line` without its line break
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map(|next| next - 1)
+            .unwrap_or(self.source.len());
+
+        self.source[start..end].trim_end_matches('\r')
+    }
+
+    /// Convert a byte offset into a line and column.
+    /// Offsets past the end or inside a character are clamped.
+    pub fn line_column(&self, offset: usize) -> LineColumn {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_index(offset);
+        let line_start = self.line_starts[line];
+
+        LineColumn {
+            line,
+            column: offset - line_start,
+            utf16_column: self.source[line_start..offset].encode_utf16().count(),
+        }
+    }
+}
+
+/// Owns every source file of a compilation and resolves `Position`s
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: vec![] }
+    }
+
+    pub fn add_file(&mut self, path: &String, source: &String) -> FileId {
+        self.files.push(SourceFile::new(path, source));
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn load_file(&mut self, path: &String) -> io::Result<FileId> {
+        let source = fs::read_to_string(path)?;
+        Ok(self.add_file(path, &source))
+    }
+
+    pub fn file(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0]
+    }
+
+    pub fn source(&self, file: FileId) -> &String {
+        &self.file(file).source
+    }
+
+    /// Start and end line/column of `pos`, the end is exclusive
+    pub fn span(&self, pos: &Position) -> (LineColumn, LineColumn) {
+        let file = self.file(pos.file);
+        (file.line_column(pos.start), file.line_column(pos.end))
+    }
+}
+
+#[cfg(test)]
+mod source_map_test {
+    use crate::source_map::{LineColumn, SourceMap};
+    use crate::token::Position;
+
+    #[test]
+    fn test_line_column() {
+        let mut source_map = SourceMap::new();
+        let file = source_map.add_file(&"test.zx".into(), &"var a = 1\n// 中文 😀 x\n".into());
+        let source = source_map.source(file).clone();
+        let x = source.find('x').unwrap();
+
+        assert_eq!(source_map.file(file).line_count(), 3);
+        assert_eq!(source_map.file(file).line(1), "// 中文 😀 x");
+        assert_eq!(
+            source_map.span(&Position {
+                file,
+                start: 4,
+                end: x,
+            }),
+            (
+                LineColumn {
+                    line: 0,
+                    column: 4,
+                    utf16_column: 4,
+                },
+                LineColumn {
+                    line: 1,
+                    column: 15,
+                    utf16_column: 9,
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// An interned identifier, copying and comparing it does not touch the string
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+// one interner for the whole compiler, so symbols from every file and thread
+// can be compared with each other
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    pub fn intern(string: &str) -> Symbol {
+        let mut interner = interner().lock().unwrap();
+
+        if let Some(&symbol) = interner.symbols.get(string) {
+            return symbol;
+        }
+
+        // interned strings are kept until the compiler exits
+        let string: &'static str = Box::leak(string.into());
+        let symbol = Symbol(interner.strings.len() as u32);
+        interner.strings.push(string);
+        interner.symbols.insert(string, symbol);
+
+        symbol
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        interner().lock().unwrap().strings[self.0 as usize]
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::symbol::Symbol;
+
+    #[test]
+    fn test_intern() {
+        let a = Symbol::intern("zan_zan");
+        let b = Symbol::intern("zan_zan");
+
+        assert_eq!(a, b);
+        assert_ne!(a, Symbol::intern("dian_shen"));
+        assert_eq!(a.as_str(), "zan_zan");
+        assert_eq!(format!("{} {:?}", a, a), "zan_zan \"zan_zan\"");
+    }
+}
@@ -1,9 +1,16 @@
 use crate::error::ZXError;
+use crate::source_map::FileId;
+use crate::symbol::Symbol;
 
-#[derive(Display, Debug, Clone)]
+/// `TokenKind` is the same enum without the token contents, use it to compare
+/// tokens by kind
+#[derive(Display, Debug, Clone, EnumDiscriminants)]
+#[strum_discriminants(name(TokenKind), derive(Display, Hash))]
 pub enum Tokens {
     /// End of file
     EOF,
+    /// Source text the lexer could not tokenize, already reported
+    ErrorToken,
     /// `\n`
     LineSeparatorToken,
     /// `,`
@@ -50,14 +57,137 @@ pub enum Tokens {
     AmpersandToken,
     /// `%`
     PercentToken,
+    /// `~`
+    TildeToken,
     /// `$`
     StdToken,
+    /// `::`
+    DoubleColonToken,
+    /// `==`
+    DoubleEqualToken,
+    /// `!=`
+    NotEqualToken,
+    /// `<=`
+    LessEqualToken,
+    /// `>=`
+    MoreEqualToken,
+    /// `&&`
+    AndToken,
+    /// `||`
+    OrToken,
+    /// `->`
+    ArrowToken,
+    /// `=>`
+    FatArrowToken,
+    /// `+=`
+    PlusEqualToken,
+    /// `-=`
+    MinusEqualToken,
+    /// `*=`
+    MultiplyEqualToken,
+    /// `/=`
+    SlashEqualToken,
+    /// `%=`
+    PercentEqualToken,
+    /// `<<`
+    LeftShiftToken,
+    /// `>>`
+    RightShiftToken,
+    /// `..`
+    DoubleDotToken,
+    /// `..=`
+    DoubleDotEqualToken,
+    /// `fn`
+    FnToken,
+    /// `var`
+    VarToken,
+    /// `class`
+    ClassToken,
+    /// `if`
+    IfToken,
+    /// `else`
+    ElseToken,
+    /// `while`
+    WhileToken,
+    /// `for`
+    ForToken,
+    /// `in`
+    InToken,
+    /// `return`
+    ReturnToken,
+    /// `pub`
+    PubToken,
+    /// `static`
+    StaticToken,
+    /// `im`
+    ImToken,
+    /// `as`
+    AsToken,
+    /// `override`
+    OverrideToken,
+    /// `enum`
+    EnumToken,
+    /// `match`
+    MatchToken,
+    /// `"` opening a string with `${...}` interpolations
+    TemplateStartToken,
+    /// `"` closing a string with `${...}` interpolations
+    TemplateEndToken,
+    /// `${` inside a string
+    InterpolationStartToken,
+    /// `}` closing an interpolation
+    InterpolationEndToken,
+    /// `/// example` or `/** example */`, the text without the comment markers
+    DocCommentToken { literal: String },
     /// `example`
-    IdentifierToken { literal: String },
+    IdentifierToken { literal: Symbol },
     ///`'a'` or `"example"` or `123` or `.3` or `0.3`
     LiteralToken { kid: Literal, literal: String },
 }
 
+/// Reserved words, they are lexed into their own tokens and can not be used
+/// as identifiers
+pub const KEYWORDS: [(&str, Tokens); 16] = [
+    ("fn", Tokens::FnToken),
+    ("var", Tokens::VarToken),
+    ("class", Tokens::ClassToken),
+    ("if", Tokens::IfToken),
+    ("else", Tokens::ElseToken),
+    ("while", Tokens::WhileToken),
+    ("for", Tokens::ForToken),
+    ("in", Tokens::InToken),
+    ("return", Tokens::ReturnToken),
+    ("pub", Tokens::PubToken),
+    ("static", Tokens::StaticToken),
+    ("im", Tokens::ImToken),
+    ("as", Tokens::AsToken),
+    ("override", Tokens::OverrideToken),
+    ("enum", Tokens::EnumToken),
+    ("match", Tokens::MatchToken),
+];
+
+impl Tokens {
+    /// The keyword token spelled `word`
+    pub fn keyword(word: &str) -> Option<Tokens> {
+        KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == word)
+            .map(|(_, token)| token.clone())
+    }
+
+    /// How a keyword token is spelled, `None` for every other token
+    pub fn keyword_str(&self) -> Option<&'static str> {
+        KEYWORDS
+            .iter()
+            .find(|(_, token)| token.kind() == self.kind())
+            .map(|(keyword, _)| *keyword)
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        TokenKind::from(self)
+    }
+}
+
 #[derive(Display, Debug, Clone, EnumString)]
 pub enum Literal {
     String,
@@ -67,12 +197,25 @@ pub enum Literal {
     NegativeInteger,
 }
 
+/// Byte span `start..end` (end exclusive) inside the file `file`
 #[derive(Debug, Clone)]
 pub struct Position {
+    pub file: FileId,
     pub start: usize,
     pub end: usize,
 }
 
+impl Position {
+    /// Span from the start of `self` to the end of `end`
+    pub fn to(&self, end: &Position) -> Position {
+        Position {
+            file: self.file,
+            start: self.start,
+            end: end.end,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: Tokens,
@@ -80,18 +223,33 @@ pub struct Token {
 }
 
 impl Token {
+    pub fn kind(&self) -> TokenKind {
+        self.token_type.kind()
+    }
+
     pub fn is_token_type(&self, token_type: &Tokens) -> bool {
-        token_type.to_string() == self.token_type.to_string()
+        self.kind() == token_type.kind()
+    }
+
+    pub fn is_kind(&self, kind: TokenKind) -> bool {
+        self.kind() == kind
     }
 
-    pub fn is_token_type_str(&self, token_type: &str) -> bool {
-        self.token_type.to_string() == token_type
+    /// Name of an identifier token
+    pub fn get_symbol(&self) -> Result<Symbol, ZXError> {
+        match &self.token_type {
+            Tokens::IdentifierToken { literal } => Ok(*literal),
+            _ => Err(ZXError::InternalError {
+                message: "Token to symbol error".into(),
+            }),
+        }
     }
 
     pub fn get_string(&self) -> Result<String, ZXError> {
         match &self.token_type {
             Tokens::IdentifierToken { literal } => Ok(literal.to_string()),
             Tokens::LiteralToken { literal, .. } => Ok(literal.to_string()),
+            Tokens::DocCommentToken { literal } => Ok(literal.to_string()),
             _ => Err(ZXError::InternalError {
                 message: "Token to string error".into(),
             }),
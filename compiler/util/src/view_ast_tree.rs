@@ -1,8 +1,8 @@
 use crate::ast::Expression;
 use crate::ast::Expression::*;
 use crate::ast::Statement::*;
-use crate::ast::{Parameter, Statement};
-use crate::token::{Token, Tokens};
+use crate::ast::{ImportKind, Parameter, Pattern, Statement};
+use crate::token::{Literal, Token, Tokens};
 
 pub struct ViewASTTree {
     pub ast_tree: Vec<Statement>,
@@ -46,6 +46,22 @@ impl ViewASTTree {
                 ..
             } => self.variable_declaration(index, var_name, type_identifier, value),
             Statement::Expression { expression } => self.expression(expression, index),
+            Assignment {
+                target,
+                operator_type,
+                value,
+                ..
+            } => {
+                let line_start = self.line_start(index);
+                match operator_type {
+                    Some(operator_type) => println!("{line_start}├── Assignment {}", operator_type),
+                    None => println!("{line_start}├── Assignment"),
+                }
+                println!("{line_start}|    ├── target");
+                self.expression(target, index + 2);
+                println!("{line_start}|    ├── value");
+                self.expression(value, index + 2);
+            }
             If {
                 condition,
                 else_statement,
@@ -68,6 +84,59 @@ impl ViewASTTree {
                 println!("{}├── Return", self.line_start(index));
                 self.statement(index + 1, return_expression);
             }
+            Enum {
+                enum_name,
+                variants,
+                ..
+            } => {
+                let line_start = self.line_start(index);
+                println!("{line_start}├── Enum {}", self.literal(enum_name));
+                variants.iter().for_each(|variant| {
+                    println!("{line_start}|    ├── {}", self.literal(&variant.name));
+                    variant
+                        .payload
+                        .iter()
+                        .for_each(|field| self.expression(field, index + 2));
+                });
+            }
+            Statement::Error { .. } => println!("{}├── Error", self.line_start(index)),
+            Import { path, kind, .. } => {
+                let path = path
+                    .iter()
+                    .map(|name| self.literal(name))
+                    .collect::<Vec<String>>()
+                    .join("::");
+                match kind {
+                    ImportKind::Single { alias } => match alias {
+                        Some(alias) => println!(
+                            "{}├── Import {} as {}",
+                            self.line_start(index),
+                            path,
+                            self.literal(alias)
+                        ),
+                        None => println!("{}├── Import {}", self.line_start(index), path),
+                    },
+                    ImportKind::Group { items } => {
+                        println!("{}├── Import {}", self.line_start(index), path);
+                        items.iter().for_each(|item| match &item.alias {
+                            Some(alias) => println!(
+                                "{}|    ├── {} as {}",
+                                self.line_start(index),
+                                self.literal(&item.name),
+                                self.literal(alias)
+                            ),
+                            None => println!(
+                                "{}|    ├── {}",
+                                self.line_start(index),
+                                self.literal(&item.name)
+                            ),
+                        })
+                    }
+                    ImportKind::Glob => {
+                        println!("{}├── Import {}::*", self.line_start(index), path)
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -117,9 +186,13 @@ impl ViewASTTree {
         match expression {
             Type {
                 identifier,
+                arguments,
                 nullable,
             } => {
                 println!("{line_start}|    ├── {} type", self.literal(identifier));
+                arguments
+                    .iter()
+                    .for_each(|argument| self.expression(argument, index + 1));
                 println!("{line_start}|    └── nullable {}", nullable);
             }
             Value { kid, next, content } => {
@@ -175,10 +248,140 @@ impl ViewASTTree {
                 println!("{line_start}|    ├── right");
                 self.expression(right, index + 2);
             }
+            Unary {
+                operator_type,
+                expression,
+                ..
+            } => {
+                println!("{line_start}├── {}", operator_type);
+                self.expression(expression, index + 1);
+            }
             Brackets { content } => {
                 println!("{line_start}├── Brackets");
                 self.expression(content, index + 1);
             }
+            StringTemplate { parts, .. } => {
+                println!("{line_start}├── String template");
+                parts.iter().for_each(|part| {
+                    println!("{line_start}|    ├── part");
+                    self.expression(part, index + 2);
+                });
+            }
+            ArrayType {
+                element, nullable, ..
+            } => {
+                println!("{line_start}|    ├── array type");
+                self.expression(element, index + 1);
+                println!("{line_start}|    └── nullable {}", nullable);
+            }
+            Array { elements, .. } => {
+                println!("{line_start}├── Array");
+                elements.iter().for_each(|element| {
+                    println!("{line_start}|    ├── element");
+                    self.expression(element, index + 2);
+                });
+            }
+            Index {
+                target,
+                index: element_index,
+                next,
+                ..
+            } => {
+                println!("{line_start}├── Index");
+                self.expression(target, index + 1);
+                println!("{line_start}|    ├── index");
+                self.expression(element_index, index + 2);
+                if let Some(next) = next {
+                    println!("{line_start}|    └── next");
+                    self.expression(next, index + 2);
+                }
+            }
+            Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                println!(
+                    "{line_start}├── Range {}",
+                    if *inclusive { "..=" } else { ".." }
+                );
+                self.expression(start, index + 1);
+                self.expression(end, index + 1);
+            }
+            FunctionType {
+                parameters,
+                return_type,
+                ..
+            } => {
+                println!("{line_start}|    ├── function type");
+                parameters
+                    .iter()
+                    .for_each(|parameter| self.expression(parameter, index + 1));
+                println!("{line_start}|    ├── return type");
+                self.expression(return_type, index + 1);
+            }
+            Function {
+                parameters,
+                return_type,
+                block,
+                ..
+            } => {
+                println!("{line_start}├── Anonymous function");
+                self.function_parameters(parameters, index + 1);
+                if let Some(type_expression) = return_type {
+                    println!("{line_start}|    ├── Return Type");
+                    self.expression(type_expression, index + 1);
+                }
+                self.statement(index + 1, block)
+            }
+            Match { value, arms, .. } => {
+                println!("{line_start}├── Match");
+                self.expression(value, index + 1);
+                arms.iter().for_each(|arm| {
+                    println!("{line_start}|    ├── {} =>", self.pattern(&arm.pattern));
+                    self.statement(index + 2, &arm.body);
+                });
+            }
+        }
+    }
+
+    // `Shape::Circle(r)` as written
+    fn pattern(&self, pattern: &Pattern) -> String {
+        match pattern {
+            Pattern::Literal {
+                kid: Literal::NegativeInteger,
+                content,
+            } => format!("-{}", self.literal(content)),
+            Pattern::Literal { content, .. } => self.literal(content),
+            Pattern::Bool { identifier } => self.literal(identifier),
+            Pattern::Variant {
+                enum_name,
+                variant_name,
+                fields,
+            } if fields.is_empty() => {
+                format!(
+                    "{}::{}",
+                    self.literal(enum_name),
+                    self.literal(variant_name)
+                )
+            }
+            Pattern::Variant {
+                enum_name,
+                variant_name,
+                fields,
+            } => format!(
+                "{}::{}({})",
+                self.literal(enum_name),
+                self.literal(variant_name),
+                fields
+                    .iter()
+                    .map(|field| self.pattern(field))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Pattern::Binding { name } => self.literal(name),
+            Pattern::Wildcard { .. } => "_".to_string(),
         }
     }
 
@@ -270,10 +473,15 @@ impl ViewASTTree {
 #[cfg(test)]
 mod token_tree_test {
     use crate::ast::Statement;
+    use crate::source_map::FileId;
     use crate::token::{Position, Token, Tokens};
     use crate::view_ast_tree::ViewASTTree;
 
-    const POS: Position = Position { start: 0, end: 0 };
+    const POS: Position = Position {
+        file: FileId(0),
+        start: 0,
+        end: 0,
+    };
     const TOKEN: Token = Token {
         token_type: Tokens::EOF,
         pos: POS,
@@ -282,8 +490,10 @@ mod token_tree_test {
     #[test]
     fn test() {
         let ast = vec![Statement::FunctionDeclaration {
+            doc: None,
             fn_keyword: TOKEN,
             function_name: TOKEN,
+            type_parameters: vec![],
             left_parentheses: TOKEN,
             parameters: vec![],
             right_parentheses: TOKEN,
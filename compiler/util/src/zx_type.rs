@@ -1,16 +1,101 @@
 use std::fmt;
 use std::fmt::Formatter;
 
+use crate::symbol::Symbol;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ZXTyped {
-    String { nullable: bool },
-    Integer { nullable: bool },
-    Char { nullable: bool },
-    Float { nullable: bool },
+    String {
+        nullable: bool,
+    },
+    Integer {
+        nullable: bool,
+    },
+    Long {
+        nullable: bool,
+    },
+    Char {
+        nullable: bool,
+    },
+    Float {
+        nullable: bool,
+    },
+    Double {
+        nullable: bool,
+    },
+    Bool {
+        nullable: bool,
+    },
     Other(String),
+    /// A type parameter of the generic function or class it is used in
+    Param(String),
+    /// An instance of a generic class like `List<Int>`, `arguments` is empty
+    /// for `List()` until the type it is assigned to gives them
+    Generic {
+        name: String,
+        arguments: Vec<ZXTyped>,
+    },
+    /// `[Int]`, the elements of `[]` are `Void` until the type it is assigned
+    /// to gives them
+    Array {
+        element: Box<ZXTyped>,
+        nullable: bool,
+    },
+    /// `0..10`, a range of `Int` or `Long` values
+    Range {
+        element: Box<ZXTyped>,
+    },
+    /// `(Int, Int) -> Int`, a function or a closure
+    Function {
+        parameters: Vec<ZXTyped>,
+        return_type: Box<ZXTyped>,
+    },
     Void,
 }
 
+impl ZXTyped {
+    /// Replace every type parameter in `parameters` with the argument at the
+    /// same index
+    pub fn substitute(&self, parameters: &[Symbol], arguments: &[ZXTyped]) -> ZXTyped {
+        match self {
+            ZXTyped::Param(name) => parameters
+                .iter()
+                .position(|parameter| parameter.as_str() == name)
+                .and_then(|index| arguments.get(index))
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            ZXTyped::Generic {
+                name,
+                arguments: type_arguments,
+            } => ZXTyped::Generic {
+                name: name.clone(),
+                arguments: type_arguments
+                    .iter()
+                    .map(|argument| argument.substitute(parameters, arguments))
+                    .collect(),
+            },
+            ZXTyped::Array { element, nullable } => ZXTyped::Array {
+                element: Box::new(element.substitute(parameters, arguments)),
+                nullable: *nullable,
+            },
+            ZXTyped::Range { element } => ZXTyped::Range {
+                element: Box::new(element.substitute(parameters, arguments)),
+            },
+            ZXTyped::Function {
+                parameters: function_parameters,
+                return_type,
+            } => ZXTyped::Function {
+                parameters: function_parameters
+                    .iter()
+                    .map(|parameter| parameter.substitute(parameters, arguments))
+                    .collect(),
+                return_type: Box::new(return_type.substitute(parameters, arguments)),
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
 impl fmt::Display for ZXTyped {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
@@ -19,9 +104,39 @@ impl fmt::Display for ZXTyped {
             match self {
                 ZXTyped::String { .. } => "Str".to_string(),
                 ZXTyped::Integer { .. } => "Int".to_string(),
+                ZXTyped::Long { .. } => "Long".to_string(),
                 ZXTyped::Char { .. } => "Char".to_string(),
                 ZXTyped::Float { .. } => "Float".to_string(),
+                ZXTyped::Double { .. } => "Double".to_string(),
+                ZXTyped::Bool { .. } => "Bool".to_string(),
                 ZXTyped::Other(type_string) => type_string.to_string(),
+                ZXTyped::Param(name) => name.to_string(),
+                ZXTyped::Generic { name, arguments } if arguments.is_empty() =>
+                    format!("{}<_>", name),
+                ZXTyped::Generic { name, arguments } => format!(
+                    "{}<{}>",
+                    name,
+                    arguments
+                        .iter()
+                        .map(|argument| argument.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                ZXTyped::Array { element, .. } if **element == ZXTyped::Void => "[_]".to_string(),
+                ZXTyped::Array { element, .. } => format!("[{}]", element),
+                ZXTyped::Range { element } => format!("Range<{}>", element),
+                ZXTyped::Function {
+                    parameters,
+                    return_type,
+                } => format!(
+                    "({}) -> {}",
+                    parameters
+                        .iter()
+                        .map(|parameter| parameter.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    return_type
+                ),
                 ZXTyped::Void => "Void".to_string(),
             }
         )